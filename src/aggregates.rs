@@ -19,6 +19,265 @@
  * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
  */
 
-pub fn avg() -> f64 {
+//! Aggregate functions.
+//!
+//! These functions are never called: they exist only for type checking the aggregate queries and
+//! for choosing the type of the aggregated values.
+//!
+//! Except for the counts, the aggregated values are optional since the aggregate functions return
+//! NULL when there are no rows to aggregate.
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+
+use unsigned::Unsigned;
+
+/// The type of the average of values of this type.
+pub trait AvgType {
+    type Output;
+}
+
+impl AvgType for i16 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for i32 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for i64 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for u8 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for u16 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for u32 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for u64 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for f32 {
+    type Output = Option<f64>;
+}
+
+impl AvgType for f64 {
+    type Output = Option<f64>;
+}
+
+#[cfg(feature = "decimal")]
+impl AvgType for Decimal {
+    type Output = Option<Decimal>;
+}
+
+impl<T: AvgType> AvgType for Option<T> {
+    type Output = T::Output;
+}
+
+/// The type of the minimum and the maximum of values of this type.
+pub trait MinMaxType {
+    type Output;
+}
+
+macro_rules! min_max_type {
+    ($($typ:ty),*) => {
+        $(
+            impl MinMaxType for $typ {
+                type Output = Option<$typ>;
+            }
+        )*
+    };
+}
+
+min_max_type!(char, i8, i16, i32, i64, u8, u16, u32, u64, f32, f64, String);
+
+#[cfg(feature = "chrono")]
+min_max_type!(DateTime<Local>, DateTime<Utc>, NaiveDate, NaiveDateTime, NaiveTime);
+
+#[cfg(feature = "decimal")]
+min_max_type!(Decimal);
+
+impl<T> MinMaxType for Vec<T> {
+    type Output = Option<Vec<T>>;
+}
+
+impl<T: MinMaxType> MinMaxType for Option<T> {
+    type Output = T::Output;
+}
+
+/// The type of the sum of values of this type.
+pub trait SumType {
+    type Output;
+}
+
+impl SumType for i16 {
+    type Output = Option<i64>;
+}
+
+impl SumType for i32 {
+    type Output = Option<i64>;
+}
+
+impl SumType for i64 {
+    type Output = Option<i64>;
+}
+
+impl SumType for u8 {
+    type Output = Option<i64>;
+}
+
+impl SumType for u16 {
+    type Output = Option<i64>;
+}
+
+impl SumType for u32 {
+    type Output = Option<i64>;
+}
+
+// NOTE: the sum is cast to a BIGINT, so the query returns an error when it overflows.
+impl SumType for u64 {
+    type Output = Option<i64>;
+}
+
+impl SumType for f32 {
+    type Output = Option<f32>;
+}
+
+impl SumType for f64 {
+    type Output = Option<f64>;
+}
+
+#[cfg(feature = "decimal")]
+impl SumType for Decimal {
+    type Output = Option<Decimal>;
+}
+
+impl<T: SumType> SumType for Option<T> {
+    type Output = T::Output;
+}
+
+pub fn avg<T: AvgType>(_field: &T) -> T::Output {
+    unreachable!()
+}
+
+pub fn count<T>(_field: &T) -> i64 {
+    unreachable!()
+}
+
+pub fn count_distinct<T>(_field: &T) -> i64 {
+    unreachable!()
+}
+
+pub fn group_concat(_field: &str) -> Option<String> {
+    unreachable!()
+}
+
+pub fn max<T: MinMaxType>(_field: &T) -> T::Output {
+    unreachable!()
+}
+
+pub fn min<T: MinMaxType>(_field: &T) -> T::Output {
+    unreachable!()
+}
+
+pub fn string_agg(_field: &str) -> Option<String> {
+    unreachable!()
+}
+
+pub fn sum<T: SumType>(_field: &T) -> T::Output {
     unreachable!()
 }
+
+/// The type of the values an aggregate is compared to in a `filter()`.
+#[doc(hidden)]
+pub trait AggregateValue {
+    type Value;
+}
+
+impl AggregateValue for i64 {
+    type Value = i64;
+}
+
+impl<T> AggregateValue for Option<T> {
+    type Value = T;
+}
+
+/// Returns the type of the values the `_aggregate` is compared to.
+#[doc(hidden)]
+pub fn compared_value<T: AggregateValue>(_aggregate: T) -> T::Value {
+    unreachable!()
+}
+
+/// The type of an aggregated value with the type it is read as from the database.
+#[doc(hidden)]
+pub trait AggregateOutput {
+    type Stored;
+
+    fn from_stored(value: Self::Stored) -> Self;
+}
+
+macro_rules! aggregate_output {
+    ($($typ:ty),*) => {
+        $(
+            impl AggregateOutput for $typ {
+                type Stored = $typ;
+
+                fn from_stored(value: $typ) -> $typ {
+                    value
+                }
+            }
+        )*
+    };
+}
+
+aggregate_output!(i64, Option<char>, Option<i8>, Option<i16>, Option<i32>, Option<i64>, Option<f32>, Option<f64>,
+    Option<String>);
+
+#[cfg(feature = "chrono")]
+aggregate_output!(Option<DateTime<Local>>, Option<DateTime<Utc>>, Option<NaiveDate>, Option<NaiveDateTime>,
+    Option<NaiveTime>);
+
+#[cfg(feature = "decimal")]
+aggregate_output!(Option<Decimal>);
+
+impl<T> AggregateOutput for Option<Vec<T>> {
+    type Stored = Option<Vec<T>>;
+
+    fn from_stored(value: Option<Vec<T>>) -> Option<Vec<T>> {
+        value
+    }
+}
+
+// NOTE: the unsigned integers are stored as signed integers.
+macro_rules! unsigned_aggregate_output {
+    ($($typ:ty),*) => {
+        $(
+            impl AggregateOutput for Option<$typ> {
+                type Stored = Unsigned<Option<$typ>>;
+
+                fn from_stored(value: Unsigned<Option<$typ>>) -> Option<$typ> {
+                    value.0
+                }
+            }
+        )*
+    };
+}
+
+unsigned_aggregate_output!(u8, u16, u32, u64);
+
+/// Returns the `value` read from the database while giving it the type returned by the `_infer`
+/// closure.
+#[doc(hidden)]
+pub fn infer<T: AggregateOutput, F: FnOnce() -> T>(value: T::Stored, _infer: F) -> T {
+    T::from_stored(value)
+}
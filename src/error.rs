@@ -0,0 +1,90 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Error of the save() methods.
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+use validation::ValidationError;
+
+#[cfg(feature = "postgres")]
+pub type DatabaseError = ::postgres::Error;
+#[cfg(feature = "rusqlite")]
+pub type DatabaseError = ::rusqlite::Error;
+
+/// Error returned by the save() method of a table struct with a `#[tql(version)]` field, by the
+/// insert() and save() methods of a table struct with validation attributes and by
+/// `Tracked::save()`.
+#[derive(Debug)]
+pub enum SaveError {
+    /// An error from the database.
+    Database(DatabaseError),
+    /// The version of the row is not the version of the instance anymore: another connection
+    /// updated the row since the instance was fetched.
+    StaleObject,
+    /// Some fields do not respect their validation attributes.
+    Validation(Vec<ValidationError>),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            SaveError::Database(ref error) => error.fmt(formatter),
+            SaveError::StaleObject => write!(formatter, "the row was updated since the object was fetched"),
+            SaveError::Validation(ref errors) => {
+                let errors: Vec<_> = errors.iter()
+                    .map(|error| error.to_string())
+                    .collect();
+                write!(formatter, "invalid fields: {}", errors.join(", "))
+            },
+        }
+    }
+}
+
+impl error::Error for SaveError {
+    fn description(&self) -> &str {
+        match *self {
+            SaveError::Database(ref error) => error.description(),
+            SaveError::StaleObject => "stale object",
+            SaveError::Validation(_) => "invalid fields",
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            SaveError::Database(ref error) => Some(error),
+            SaveError::StaleObject | SaveError::Validation(_) => None,
+        }
+    }
+}
+
+impl From<DatabaseError> for SaveError {
+    fn from(error: DatabaseError) -> Self {
+        SaveError::Database(error)
+    }
+}
+
+impl From<Vec<ValidationError>> for SaveError {
+    fn from(errors: Vec<ValidationError>) -> Self {
+        SaveError::Validation(errors)
+    }
+}
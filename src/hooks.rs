@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Hooks called by the methods of a table struct.
+
+use error::DatabaseError;
+
+// NOTE: the lifetime avoids the 'static default bound of the trait object.
+#[cfg(feature = "postgres")]
+type Connection<'a> = ::postgres::GenericConnection + 'a;
+#[cfg(feature = "rusqlite")]
+type Connection<'a> = ::rusqlite::Connection;
+
+/// Code to run before or after the methods of a table struct write its row.
+///
+/// `#[derive(SqlTable)]` implements this trait with hooks doing nothing, unless the struct has the
+/// `#[tql(hooks)]` attribute, in which case the struct must implement it.
+///
+/// The hooks are only called by the `insert()`, `save()` and `delete()` methods of the instances
+/// and by `Tracked::save()`: the queries of `sql!()`, like `Table.filter(...).delete()`, bypass
+/// them.
+pub trait Hooks {
+    /// Called by `insert()` before inserting the row.
+    fn before_insert(&mut self, _connection: &Connection) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+
+    /// Called by `insert()` after inserting the row, when the primary key is assigned.
+    fn after_insert(&mut self, _connection: &Connection) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+
+    /// Called by `save()` and `Tracked::save()` before updating the row.
+    fn before_update(&mut self, _connection: &Connection) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+
+    /// Called by `delete()` after deleting the row.
+    fn after_delete(&self, _connection: &Connection) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Runtime support for the `insert_many()` method.
+
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use std::cmp;
+#[cfg(feature = "postgres")]
+use std::io;
+
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use error::DatabaseError;
+use validation::ValidationError;
+
+/// The maximum number of parameters in a query.
+#[cfg(feature = "postgres")]
+const MAX_PARAMETERS: usize = 65_535;
+
+/// The maximum number of parameters in a query.
+// NOTE: this is the default value of SQLITE_MAX_VARIABLE_NUMBER before SQLite 3.32.0.
+#[cfg(feature = "rusqlite")]
+const MAX_PARAMETERS: usize = 999;
+
+/// A row that can be inserted in the table `T` with the `insert_many()` method.
+///
+/// `#[derive(SqlTable)]` implements this trait for the table struct (whose primary key is not
+/// inserted) and for the tuple of the types of the other fields, in the order they are declared.
+pub trait InsertRow<T> {
+    #[doc(hidden)]
+    const FIELD_COUNT: usize;
+
+    #[cfg(feature = "postgres")]
+    #[doc(hidden)]
+    fn to_sql_values(&self) -> Vec<&::postgres::types::ToSql>;
+
+    #[cfg(feature = "rusqlite")]
+    #[doc(hidden)]
+    fn to_sql_values(&self) -> Vec<&::rusqlite::types::ToSql>;
+
+    /// Check that the fields of the row respect their validation attributes.
+    #[doc(hidden)]
+    fn validate(&self) -> Result<(), Vec<ValidationError>>;
+}
+
+impl<'a, T, R: InsertRow<T>> InsertRow<T> for &'a R {
+    const FIELD_COUNT: usize = R::FIELD_COUNT;
+
+    #[cfg(feature = "postgres")]
+    fn to_sql_values(&self) -> Vec<&::postgres::types::ToSql> {
+        (**self).to_sql_values()
+    }
+
+    #[cfg(feature = "rusqlite")]
+    fn to_sql_values(&self) -> Vec<&::rusqlite::types::ToSql> {
+        (**self).to_sql_values()
+    }
+
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        (**self).validate()
+    }
+}
+
+/// An iterator over the chunks of rows inserted by a single query.
+///
+/// The rows are taken from the underlying iterator one chunk at a time.
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+#[doc(hidden)]
+pub struct InsertManyChunks<I> {
+    chunk_size: usize,
+    rows: I,
+}
+
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+impl<I: Iterator> Iterator for InsertManyChunks<I> {
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<_> = self.rows.by_ref().take(self.chunk_size).collect();
+        if chunk.is_empty() {
+            None
+        }
+        else {
+            Some(chunk)
+        }
+    }
+}
+
+/// Split the `rows` in chunks that do not exceed the maximum number of parameters of a query.
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+pub fn insert_many_chunks<T, R, I>(rows: I) -> InsertManyChunks<I::IntoIter>
+where I: IntoIterator<Item=R>,
+      R: InsertRow<T>,
+{
+    InsertManyChunks {
+        chunk_size: cmp::max(MAX_PARAMETERS / cmp::max(R::FIELD_COUNT, 1), 1),
+        rows: rows.into_iter(),
+    }
+}
+
+/// Check that the `rows` respect the validation attributes of the table, returning the first
+/// validation error as an error of the database driver.
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+pub fn validate_rows<T, R: InsertRow<T>>(rows: &[R]) -> Result<(), DatabaseError> {
+    for row in rows {
+        if let Err(mut errors) = row.validate() {
+            return Err(validation_error_to_database_error(errors.swap_remove(0)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "postgres")]
+fn validation_error_to_database_error(error: ValidationError) -> DatabaseError {
+    io::Error::new(io::ErrorKind::InvalidInput, error).into()
+}
+
+#[cfg(feature = "rusqlite")]
+fn validation_error_to_database_error(error: ValidationError) -> DatabaseError {
+    ::rusqlite::Error::ToSqlConversionFailure(Box::new(error))
+}
+
+/// Get the parameters to send with the query inserting the `rows`.
+#[cfg(feature = "postgres")]
+pub fn insert_many_parameters<T, R: InsertRow<T>>(rows: &[R]) -> Vec<&::postgres::types::ToSql> {
+    rows.iter()
+        .flat_map(|row| row.to_sql_values())
+        .collect()
+}
+
+/// Get the parameters to send with the query inserting the `rows`.
+#[cfg(feature = "rusqlite")]
+pub fn insert_many_parameters<T, R: InsertRow<T>>(rows: &[R]) -> Vec<&::rusqlite::types::ToSql> {
+    rows.iter()
+        .flat_map(|row| row.to_sql_values())
+        .collect()
+}
+
+/// Get the placeholders of the VALUES clause inserting the `rows`, followed by the
+/// `automatic_values` in each row, e.g. ($1, $2, CURRENT_TIMESTAMP), ($3, $4, CURRENT_TIMESTAMP).
+pub fn insert_many_values<T, R: InsertRow<T>>(rows: &[R], automatic_values: &str) -> String {
+    let mut index = 1;
+    let mut values = vec![];
+    for _ in rows {
+        let mut placeholders = vec![];
+        for _ in 0..R::FIELD_COUNT {
+            placeholders.push(format!("${}", index));
+            index += 1;
+        }
+        values.push(format!("({}{})", placeholders.join(", "), automatic_values));
+    }
+    values.join(", ")
+}
@@ -0,0 +1,117 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! A JSON column containing a serializable value.
+
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use std::error::Error;
+use std::ops::{Deref, DerefMut};
+
+#[cfg(feature = "postgres")]
+use postgres::types::{self as pg_types, FromSql as PgFromSql, IsNull, JSON, JSONB, ToSql as PgToSql};
+#[cfg(feature = "rusqlite")]
+use rusqlite::{self, types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef}};
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use serde::Serialize;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use serde::de::DeserializeOwned;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use serde_json;
+
+/// A field of type `Json<T>` is saved as JSON in the database.
+///
+/// The value is serialized when it is sent to the database and deserialized when the row is
+/// read.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Json<T>(pub T);
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Json<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+// NOTE: the binary format of JSONB is the JSON text preceded by the version 1.
+#[cfg(feature = "postgres")]
+const JSONB_VERSION: u8 = 1;
+
+#[cfg(feature = "postgres")]
+impl<T: Serialize> PgToSql for Json<T> {
+    fn to_sql(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        if *ty == JSONB {
+            out.push(JSONB_VERSION);
+        }
+        serde_json::to_writer(out, &self.0)?;
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &pg_types::Type) -> bool {
+        *ty == JSON || *ty == JSONB
+    }
+
+    fn to_sql_checked(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        pg_types::__to_sql_checked(self, ty, out)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<T: DeserializeOwned> PgFromSql for Json<T> {
+    fn from_sql(ty: &pg_types::Type, mut raw: &[u8]) -> Result<Self, Box<Error + Sync + Send>> {
+        if *ty == JSONB {
+            match raw.split_first() {
+                Some((&JSONB_VERSION, rest)) => raw = rest,
+                _ => return Err("unsupported JSONB encoding version".into()),
+            }
+        }
+        Ok(Json(serde_json::from_slice(raw)?))
+    }
+
+    fn accepts(ty: &pg_types::Type) -> bool {
+        *ty == JSON || *ty == JSONB
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl<T: Serialize> ToSql for Json<T> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput> {
+        serde_json::to_string(&self.0)
+            .map(ToSqlOutput::from)
+            .map_err(|error| rusqlite::Error::ToSqlConversionFailure(Box::new(error)))
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl<T: DeserializeOwned> FromSql for Json<T> {
+    fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        serde_json::from_str(text)
+            .map(Json)
+            .map_err(|error| FromSqlError::Other(Box::new(error) as Box<Error + Send + Sync>))
+    }
+}
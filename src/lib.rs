@@ -27,16 +27,65 @@
 extern crate chrono;
 #[cfg(feature = "postgres")]
 extern crate postgres;
+extern crate regex;
 #[cfg(feature = "rusqlite")]
 extern crate rusqlite;
+#[cfg(feature = "decimal")]
+extern crate rust_decimal;
+#[cfg(feature = "json")]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "uuid")]
+extern crate uuid;
 
 pub mod aggregates;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+mod error;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+mod hooks;
+mod insert;
+#[cfg(feature = "json")]
+mod json;
 mod methods;
+mod tracked;
 mod types;
-
+mod unsigned;
+#[cfg(all(feature = "rusqlite", feature = "uuid"))]
+mod uuid_blob;
+mod validation;
+
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+pub use error::SaveError;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+pub use hooks::Hooks;
+pub use insert::InsertRow;
+#[cfg(feature = "json")]
+pub use json::Json;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+#[doc(hidden)]
+pub use insert::{InsertManyChunks, insert_many_chunks, insert_many_parameters, validate_rows};
+#[doc(hidden)]
+pub use insert::insert_many_values;
+pub use tracked::Tracked;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+#[doc(hidden)]
+pub use tracked::update_fields;
+#[doc(hidden)]
+pub use tracked::TrackedTable;
 pub use types::{Date, DateTime, Time, ToTqlType};
+#[doc(hidden)]
+pub use types::{TqlDecimal, TqlJson, TqlUuid};
 use types::StdI32;
 pub use types::numbers::{i16, i32, i64, i8, u16, u32, u64, u8};
+#[doc(hidden)]
+pub use unsigned::{Unsigned, UnsignedInt};
+#[cfg(all(feature = "rusqlite", feature = "uuid"))]
+#[doc(hidden)]
+pub use uuid_blob::UuidBlob;
+pub use validation::{ValidationError, ValidationErrorKind};
+#[doc(hidden)]
+pub use validation::{Validated, regex_is_match};
 
 /// The `ForeignKey` is optional.
 ///
@@ -46,10 +95,19 @@ pub type ForeignKey<T> = Option<T>;
 /// A `PrimaryKey` is a 4-byte integer.
 pub type PrimaryKey = StdI32;
 
+#[doc(hidden)]
+// Marker trait used for error reporting:
+// when a type is used as the type of a field, but it is not annotated with #[derive(SqlEnum)] or
+// #[derive(SqlType)].
+pub trait SqlType {
+}
+
 #[doc(hidden)]
 // Marker trait used for error reporting:
 // when a struct is used in a ForeignKey, but it is not annotated with #[derive(SqlTable)].
 pub unsafe trait SqlTable {
+    type PrimaryKey;
+
     #[cfg(feature = "postgres")]
     const FIELD_COUNT: usize;
     #[cfg(feature = "rusqlite")]
@@ -101,6 +159,19 @@ macro_rules! check_missing_fields {
     }};
 }
 
+#[cfg(not(unstable))]
+#[macro_export]
+macro_rules! check_field_values {
+    ($($tt:tt)*) => {{
+        #[derive(StableCheckFieldValues)]
+        enum __TqlStableCheckFieldValuesEnum {
+            Input = (stringify!($($tt)*), 0).1,
+        }
+
+        __tql_call_macro_field_values!()
+    }};
+}
+
 #[cfg(not(unstable))]
 #[macro_export]
 macro_rules! sql {
@@ -113,3 +184,16 @@ macro_rules! sql {
         __tql_call_macro!($connection, $($tt)*)
     }};
 }
+
+#[cfg(not(unstable))]
+#[macro_export]
+macro_rules! sql_raw {
+    ($connection:ident, $($tt:tt)*) => {{
+        #[derive(StableSqlRaw)]
+        enum __TqlStableSqlRawEnum {
+            Input = (stringify!($($tt)*), 0).1,
+        }
+
+        __tql_call_macro_raw!($connection, $($tt)*)
+    }};
+}
@@ -22,7 +22,9 @@
 //! These methods should not be used directly:
 //! they exist only for type checking.
 
-use types::{Date, DateTime, Time, TqlOption, TqlString};
+use std::borrow::Borrow;
+
+use types::{Date, DateTime, Time, ToTqlType, TqlJson, TqlOption, TqlString, TqlVec};
 
 impl Date {
     pub fn day(&self) -> i32 { 0 }
@@ -55,7 +57,20 @@ impl TqlString {
     pub fn starts_with(&self, _string: &str) -> bool { false }
 }
 
-impl TqlOption {
+impl TqlJson {
+    pub fn contains<T: ToTqlType<Target = TqlJson>>(&self, _value: &T) -> bool { false }
+    pub fn get(&self, _key: &str) -> &str { "" }
+}
+
+impl<T> TqlVec<T> {
+    pub fn contains<U: ?Sized>(&self, _value: &U) -> bool where T: Borrow<U> { false }
+    pub fn len(&self) -> usize { 0 }
+    pub fn overlaps(&self, _values: &[T]) -> bool { false }
+}
+
+impl<T> TqlOption<T> {
+    pub fn is_distinct_from(&self, _value: Option<T>) -> bool { false }
+    pub fn is_not_distinct_from(&self, _value: Option<T>) -> bool { false }
     pub fn is_some(&self) -> bool { false }
     pub fn is_none(&self) -> bool { false }
 }
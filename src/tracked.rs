@@ -0,0 +1,276 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Change tracking of the fields of a table struct.
+
+use std::ops::Deref;
+
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use error::SaveError;
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+use hooks::Hooks;
+use insert::InsertRow;
+use validation::ValidationError;
+
+/// A table struct that records the fields that are assigned with the `set!()` macro.
+///
+/// Its `save()` method only updates the columns of the fields that changed.
+pub struct Tracked<T> {
+    changed_fields: Vec<&'static str>,
+    value: T,
+}
+
+impl<T> Tracked<T> {
+    pub fn new(value: T) -> Self {
+        Tracked {
+            changed_fields: vec![],
+            value,
+        }
+    }
+
+    /// Get the names of the fields that changed since the creation or the last save.
+    pub fn changed_fields(&self) -> &[&'static str] {
+        &self.changed_fields
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    #[doc(hidden)]
+    pub fn __tql_change_field(&mut self, field: &'static str) -> &mut T {
+        if !self.changed_fields.contains(&field) {
+            self.changed_fields.push(field);
+        }
+        &mut self.value
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: TrackedTable> Tracked<T> {
+    /// Update the columns of the changed fields in the row of this instance.
+    /// The fields changed by the `before_update()` hook are not saved since they are not tracked.
+    /// The `StaleObject` error is returned when the table struct has a version field and the version
+    /// of the row is not the version of this instance anymore.
+    /// The `Validation` error is returned when some fields do not respect their validation
+    /// attributes.
+    #[cfg(feature = "postgres")]
+    pub fn save(&mut self, connection: &::postgres::GenericConnection) -> Result<u64, SaveError>
+        where T: Hooks
+    {
+        if !has_updated_columns::<T>(&self.changed_fields) {
+            self.changed_fields.clear();
+            return Ok(0);
+        }
+        self.value.before_update(connection)?;
+        self.value.validate()?;
+        let count = update_fields(&self.value, &self.changed_fields, connection)?;
+        if count == 0 && T::VERSION_FIELD.is_some() {
+            return Err(SaveError::StaleObject);
+        }
+        self.value.increment_version();
+        self.changed_fields.clear();
+        Ok(count)
+    }
+
+    /// Update the columns of the changed fields in the row of this instance.
+    /// The fields changed by the `before_update()` hook are not saved since they are not tracked.
+    /// The `StaleObject` error is returned when the table struct has a version field and the version
+    /// of the row is not the version of this instance anymore.
+    /// The `Validation` error is returned when some fields do not respect their validation
+    /// attributes.
+    #[cfg(feature = "rusqlite")]
+    pub fn save(&mut self, connection: &::rusqlite::Connection) -> Result<usize, SaveError>
+        where T: Hooks
+    {
+        if !has_updated_columns::<T>(&self.changed_fields) {
+            self.changed_fields.clear();
+            return Ok(0);
+        }
+        self.value.before_update(connection)?;
+        self.value.validate()?;
+        let count = update_fields(&self.value, &self.changed_fields, connection)?;
+        if count == 0 && T::VERSION_FIELD.is_some() {
+            return Err(SaveError::StaleObject);
+        }
+        self.value.increment_version();
+        self.changed_fields.clear();
+        Ok(count)
+    }
+}
+
+/// A table struct that can be wrapped in `Tracked`.
+///
+/// `#[derive(SqlTable)]` implements this trait when the table has a primary key.
+#[doc(hidden)]
+pub trait TrackedTable: InsertRow<Self> + Sized {
+    /// The SQL assignments of the fields with the `#[tql(auto_now)]` attribute, e.g.
+    /// `", date_modified = CURRENT_TIMESTAMP"`.
+    const AUTOMATIC_ASSIGNMENTS: &'static str;
+    /// The names of the fields with the `#[tql(auto_now)]` or `#[tql(auto_now_add)]` attribute.
+    const AUTOMATIC_FIELDS: &'static [&'static str];
+    /// The names of the columns in the order of the values of `InsertRow::to_sql_values()`.
+    const FIELD_NAMES: &'static [&'static str];
+    const PRIMARY_KEY: &'static str;
+    const TABLE_NAME: &'static str;
+    /// The name of the field with the `#[tql(version)]` attribute.
+    const VERSION_FIELD: Option<&'static str>;
+
+    fn increment_version(&mut self);
+
+    /// Check the fields with validation attributes.
+    fn validate(&self) -> Result<(), Vec<ValidationError>>;
+
+    #[cfg(feature = "postgres")]
+    fn primary_key_value(&self) -> &::postgres::types::ToSql;
+
+    #[cfg(feature = "rusqlite")]
+    fn primary_key_value(&self) -> &::rusqlite::types::ToSql;
+}
+
+/// Update the columns of the `fields` in the row of the `value` and increment its version.
+/// The version of the row is checked: no row is updated when it is not the version of the `value`.
+/// No query is executed when there are no columns to update.
+#[cfg(feature = "postgres")]
+#[doc(hidden)]
+pub fn update_fields<T: TrackedTable>(value: &T, fields: &[&str], connection: &::postgres::GenericConnection)
+    -> Result<u64, ::postgres::Error>
+{
+    if !has_updated_columns::<T>(fields) && T::VERSION_FIELD.is_none() {
+        return Ok(0);
+    }
+    let (query, parameters) = update_query_parameters(value, fields);
+    connection.prepare(&query)?.execute(&parameters)
+}
+
+/// Update the columns of the `fields` in the row of the `value` and increment its version.
+/// The version of the row is checked: no row is updated when it is not the version of the `value`.
+/// No query is executed when there are no columns to update.
+#[cfg(feature = "rusqlite")]
+#[doc(hidden)]
+pub fn update_fields<T: TrackedTable>(value: &T, fields: &[&str], connection: &::rusqlite::Connection)
+    -> Result<usize, ::rusqlite::Error>
+{
+    if !has_updated_columns::<T>(fields) && T::VERSION_FIELD.is_none() {
+        return Ok(0);
+    }
+    let (query, parameters) = update_query_parameters(value, fields);
+    let mut statement = connection.prepare(&query)?;
+    statement.execute(&parameters)
+}
+
+/// Create the UPDATE query of the `fields` and get its parameters.
+#[cfg(feature = "postgres")]
+fn update_query_parameters<'a, T: TrackedTable>(value: &'a T, fields: &[&str])
+    -> (String, Vec<&'a ::postgres::types::ToSql>)
+{
+    let (query, indexes) = update_query::<T>(fields);
+    let values = value.to_sql_values();
+    let mut parameters: Vec<_> = indexes.iter()
+        .map(|&index| values[index])
+        .collect();
+    parameters.push(value.primary_key_value());
+    if let Some(index) = version_index::<T>() {
+        parameters.push(values[index]);
+    }
+    (query, parameters)
+}
+
+/// Create the UPDATE query of the `fields` and get its parameters.
+#[cfg(feature = "rusqlite")]
+fn update_query_parameters<'a, T: TrackedTable>(value: &'a T, fields: &[&str])
+    -> (String, Vec<&'a ::rusqlite::types::ToSql>)
+{
+    let (query, indexes) = update_query::<T>(fields);
+    let values = value.to_sql_values();
+    let mut parameters: Vec<_> = indexes.iter()
+        .map(|&index| values[index])
+        .collect();
+    parameters.push(value.primary_key_value());
+    if let Some(index) = version_index::<T>() {
+        parameters.push(values[index]);
+    }
+    (query, parameters)
+}
+
+/// Check whether some of the `changed_fields` are columns updated by `update_query()`.
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+fn has_updated_columns<T: TrackedTable>(changed_fields: &[&str]) -> bool {
+    T::FIELD_NAMES.iter()
+        .any(|field| changed_fields.contains(field) && is_updated_column::<T>(field))
+}
+
+/// Check whether the `field` is a column updated by `update_query()`.
+// NOTE: the fields which are not columns, like the primary key, the version and the automatic fields
+// are not updated.
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+fn is_updated_column<T: TrackedTable>(field: &str) -> bool {
+    field != T::PRIMARY_KEY && Some(field) != T::VERSION_FIELD && !T::AUTOMATIC_FIELDS.contains(&field)
+}
+
+/// Create the UPDATE query of the `changed_fields` and get the indexes of their values.
+/// The placeholder of the primary key comes after the placeholders of the values and is followed by
+/// the placeholder of the version, if any.
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+fn update_query<T: TrackedTable>(changed_fields: &[&str]) -> (String, Vec<usize>) {
+    let mut assignments = vec![];
+    let mut indexes = vec![];
+    for (index, field) in T::FIELD_NAMES.iter().enumerate() {
+        if changed_fields.contains(field) && is_updated_column::<T>(field) {
+            indexes.push(index);
+            assignments.push(format!("{} = ${}", field, indexes.len()));
+        }
+    }
+    let mut filter = format!("{}.{} = ${}", T::TABLE_NAME, T::PRIMARY_KEY, indexes.len() + 1);
+    if let Some(version) = T::VERSION_FIELD {
+        assignments.push(format!("{version} = {version} + 1", version = version));
+        filter += &format!(" AND {}.{} = ${}", T::TABLE_NAME, version, indexes.len() + 2);
+    }
+    let query = format!("UPDATE {} SET {}{} WHERE {}", T::TABLE_NAME, assignments.join(", "),
+                        T::AUTOMATIC_ASSIGNMENTS, filter);
+    (query, indexes)
+}
+
+/// Get the index of the value of the version field.
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+fn version_index<T: TrackedTable>() -> Option<usize> {
+    T::VERSION_FIELD.and_then(|version| T::FIELD_NAMES.iter().position(|field| *field == version))
+}
+
+/// Assign a value to a field of a `Tracked` table struct and record that this field changed.
+///
+/// ```ignore
+/// set!(tracked.text = "New text".to_string());
+/// ```
+#[macro_export]
+macro_rules! set {
+    ($tracked:ident . $field:ident = $value:expr) => {{
+        let value = $value;
+        $tracked.__tql_change_field(stringify!($field)).$field = value;
+    }};
+}
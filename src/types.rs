@@ -24,8 +24,18 @@
 
 #![allow(dead_code, non_camel_case_types)]
 
+use std::marker::PhantomData;
+
 #[cfg(feature = "chrono")]
 use chrono::{self, Local, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+#[cfg(feature = "json")]
+use json::Json;
+#[cfg(feature = "decimal")]
+use rust_decimal;
+#[cfg(feature = "json")]
+use serde_json;
+#[cfg(feature = "uuid")]
+use uuid;
 
 type StdI8 = i8;
 
@@ -183,11 +193,11 @@ impl ToTqlType for NaiveTime {
 }
 
 #[doc(hidden)]
-pub struct TqlOption;
+pub struct TqlOption<T>(PhantomData<T>);
 
 impl<T> ToTqlType for Option<T> {
-    type Target = TqlOption;
-    fn to_tql_type(&self) -> Self::Target { TqlOption }
+    type Target = TqlOption<T>;
+    fn to_tql_type(&self) -> Self::Target { TqlOption(PhantomData) }
 }
 
 #[doc(hidden)]
@@ -198,6 +208,47 @@ impl ToTqlType for String {
     fn to_tql_type(&self) -> Self::Target { TqlString }
 }
 
+#[doc(hidden)]
+pub struct TqlVec<T>(PhantomData<T>);
+
+impl<T> ToTqlType for Vec<T> {
+    type Target = TqlVec<T>;
+    fn to_tql_type(&self) -> Self::Target { TqlVec(PhantomData) }
+}
+
+#[doc(hidden)]
+pub struct TqlJson;
+
+#[cfg(feature = "json")]
+impl ToTqlType for serde_json::Value {
+    type Target = TqlJson;
+    fn to_tql_type(&self) -> Self::Target { TqlJson }
+}
+
+#[cfg(feature = "json")]
+impl<T> ToTqlType for Json<T> {
+    type Target = TqlJson;
+    fn to_tql_type(&self) -> Self::Target { TqlJson }
+}
+
+#[doc(hidden)]
+pub struct TqlUuid;
+
+#[cfg(feature = "uuid")]
+impl ToTqlType for uuid::Uuid {
+    type Target = TqlUuid;
+    fn to_tql_type(&self) -> Self::Target { TqlUuid }
+}
+
+#[doc(hidden)]
+pub struct TqlDecimal;
+
+#[cfg(feature = "decimal")]
+impl ToTqlType for rust_decimal::Decimal {
+    type Target = TqlDecimal;
+    fn to_tql_type(&self) -> Self::Target { TqlDecimal }
+}
+
 pub trait ToTqlType {
     type Target;
 
@@ -0,0 +1,217 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Conversion of the unsigned integers to the signed integers stored in the database.
+//!
+//! The SQL databases have no unsigned integer types, so a value is stored in a signed integer
+//! type large enough to hold it (a `u64` is stored in a `BIGINT`, so it is checked instead).
+
+#[cfg(feature = "postgres")]
+use std::error::Error;
+
+#[cfg(feature = "postgres")]
+use postgres::types::{self as pg_types, FromSql as PgFromSql, IsNull, ToSql as PgToSql};
+#[cfg(feature = "rusqlite")]
+use rusqlite::{self, types::{FromSql, FromSqlError, FromSqlResult, Null, ToSql, ToSqlOutput, ValueRef}};
+
+#[cfg(any(feature = "postgres", feature = "rusqlite"))]
+const OUT_OF_RANGE: &str = "the integer is out of the range of its column type";
+
+/// A wrapper converting an unsigned integer from and to the signed integer stored in the
+/// database.
+#[doc(hidden)]
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct Unsigned<T>(pub T);
+
+impl<T> Unsigned<T> {
+    /// Wrap a reference to an unsigned integer to send it as a parameter.
+    pub fn from_ref(value: &T) -> &Unsigned<T> {
+        // NOTE: this is safe because Unsigned has the same representation as T.
+        unsafe { &*(value as *const T as *const Unsigned<T>) }
+    }
+}
+
+/// An unsigned integer type with the signed integer type used to store its values.
+#[doc(hidden)]
+pub trait UnsignedInt: Copy + Sized {
+    type Signed;
+
+    /// Convert to the signed type, returning `None` if the value does not fit.
+    fn to_signed(self) -> Option<Self::Signed>;
+
+    /// Convert from the signed type, returning `None` if the value is out of range.
+    fn from_signed(value: Self::Signed) -> Option<Self>;
+}
+
+macro_rules! unsigned_int {
+    ($($unsigned:ident => $signed:ident),*) => {
+        $(
+            impl UnsignedInt for $unsigned {
+                type Signed = $signed;
+
+                fn to_signed(self) -> Option<$signed> {
+                    if self as u64 <= $signed::max_value() as u64 {
+                        Some(self as $signed)
+                    }
+                    else {
+                        None
+                    }
+                }
+
+                fn from_signed(value: $signed) -> Option<$unsigned> {
+                    if value >= 0 && value as u64 <= $unsigned::max_value() as u64 {
+                        Some(value as $unsigned)
+                    }
+                    else {
+                        None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+unsigned_int!(u8 => i16, u16 => i32, u32 => i64, u64 => i64);
+
+#[cfg(feature = "postgres")]
+impl<T: UnsignedInt + ::std::fmt::Debug> PgToSql for Unsigned<T>
+    where T::Signed: PgToSql,
+{
+    fn to_sql(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        match self.0.to_signed() {
+            Some(value) => value.to_sql(ty, out),
+            None => Err(OUT_OF_RANGE.into()),
+        }
+    }
+
+    fn accepts(ty: &pg_types::Type) -> bool {
+        T::Signed::accepts(ty)
+    }
+
+    fn to_sql_checked(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        pg_types::__to_sql_checked(self, ty, out)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<T: UnsignedInt + ::std::fmt::Debug> PgToSql for Unsigned<Option<T>>
+    where T::Signed: PgToSql,
+{
+    fn to_sql(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        match self.0 {
+            Some(value) => Unsigned(value).to_sql(ty, out),
+            None => Ok(IsNull::Yes),
+        }
+    }
+
+    fn accepts(ty: &pg_types::Type) -> bool {
+        T::Signed::accepts(ty)
+    }
+
+    fn to_sql_checked(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        pg_types::__to_sql_checked(self, ty, out)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<T: UnsignedInt> PgFromSql for Unsigned<T>
+    where T::Signed: PgFromSql,
+{
+    fn from_sql(ty: &pg_types::Type, raw: &[u8]) -> Result<Self, Box<Error + Sync + Send>> {
+        T::from_signed(T::Signed::from_sql(ty, raw)?)
+            .map(Unsigned)
+            .ok_or_else(|| OUT_OF_RANGE.into())
+    }
+
+    fn accepts(ty: &pg_types::Type) -> bool {
+        T::Signed::accepts(ty)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<T: UnsignedInt> PgFromSql for Unsigned<Option<T>>
+    where T::Signed: PgFromSql,
+{
+    fn from_sql(ty: &pg_types::Type, raw: &[u8]) -> Result<Self, Box<Error + Sync + Send>> {
+        let Unsigned(value) = Unsigned::<T>::from_sql(ty, raw)?;
+        Ok(Unsigned(Some(value)))
+    }
+
+    fn from_sql_null(_ty: &pg_types::Type) -> Result<Self, Box<Error + Sync + Send>> {
+        Ok(Unsigned(None))
+    }
+
+    fn accepts(ty: &pg_types::Type) -> bool {
+        T::Signed::accepts(ty)
+    }
+}
+
+// NOTE: SQLite stores every integer in a 64-bit integer, so the values are sent as i64.
+#[cfg(feature = "rusqlite")]
+impl<T: UnsignedInt> ToSql for Unsigned<T>
+    where T::Signed: Into<i64>,
+{
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput> {
+        match self.0.to_signed() {
+            Some(value) => Ok(ToSqlOutput::from(value.into())),
+            None => Err(rusqlite::Error::ToSqlConversionFailure(OUT_OF_RANGE.into())),
+        }
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl<T: UnsignedInt> ToSql for Unsigned<Option<T>>
+    where T::Signed: Into<i64>,
+{
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput> {
+        match self.0 {
+            Some(value) => match value.to_signed() {
+                Some(value) => Ok(ToSqlOutput::from(value.into())),
+                None => Err(rusqlite::Error::ToSqlConversionFailure(OUT_OF_RANGE.into())),
+            },
+            None => Ok(ToSqlOutput::from(Null)),
+        }
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl<T: UnsignedInt> FromSql for Unsigned<T>
+    where T::Signed: FromSql,
+{
+    fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+        T::from_signed(T::Signed::column_result(value)?)
+            .map(Unsigned)
+            .ok_or_else(|| FromSqlError::Other(OUT_OF_RANGE.into()))
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl<T: UnsignedInt> FromSql for Unsigned<Option<T>>
+    where T::Signed: FromSql,
+{
+    fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Null => Ok(Unsigned(None)),
+            value => Unsigned::<T>::column_result(value).map(|Unsigned(value)| Unsigned(Some(value))),
+        }
+    }
+}
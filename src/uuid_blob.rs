@@ -0,0 +1,74 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Conversion of the `Uuid` values from and to the blobs storing them in SQLite.
+//!
+//! SQLite has no UUID type, so a value is stored as its 16 bytes in a `BLOB` column.
+
+use rusqlite::{self, types::{FromSql, FromSqlError, FromSqlResult, Null, ToSql, ToSqlOutput, ValueRef}};
+use uuid::Uuid;
+
+/// A wrapper converting a `Uuid` from and to the blob stored in the database.
+#[doc(hidden)]
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct UuidBlob<T>(pub T);
+
+impl<T> UuidBlob<T> {
+    /// Wrap a reference to a `Uuid` to send it as a parameter.
+    pub fn from_ref(value: &T) -> &UuidBlob<T> {
+        // NOTE: this is safe because UuidBlob has the same representation as T.
+        unsafe { &*(value as *const T as *const UuidBlob<T>) }
+    }
+}
+
+impl ToSql for UuidBlob<Uuid> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Blob(self.0.as_bytes())))
+    }
+}
+
+impl ToSql for UuidBlob<Option<Uuid>> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput> {
+        match self.0 {
+            Some(ref value) => Ok(ToSqlOutput::Borrowed(ValueRef::Blob(value.as_bytes()))),
+            None => Ok(ToSqlOutput::from(Null)),
+        }
+    }
+}
+
+impl FromSql for UuidBlob<Uuid> {
+    fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+        // NOTE: a blob which is not 16-byte long does not hold a Uuid.
+        Uuid::from_bytes(value.as_blob()?)
+            .map(UuidBlob)
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+impl FromSql for UuidBlob<Option<Uuid>> {
+    fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Null => Ok(UuidBlob(None)),
+            value => UuidBlob::<Uuid>::column_result(value).map(|UuidBlob(value)| UuidBlob(Some(value))),
+        }
+    }
+}
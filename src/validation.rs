@@ -0,0 +1,153 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Validation of the fields of a table struct with the max_length, range and regex attributes.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error;
+#[cfg(feature = "postgres")]
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "postgres")]
+use postgres::types::{self as pg_types, IsNull, ToSql as PgToSql};
+use regex::Regex;
+#[cfg(feature = "rusqlite")]
+use rusqlite::{self, types::{ToSql, ToSqlOutput}};
+
+/// Error returned by the validate() method of a table struct for a field whose value does not
+/// respect one of its validation attributes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    /// The name of the invalid field.
+    pub field: &'static str,
+    pub kind: ValidationErrorKind,
+}
+
+/// The validation attribute which is not respected.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationErrorKind {
+    /// The string has more characters than allowed by the `#[tql(max_length)]` attribute.
+    TooLong {
+        max_length: usize,
+    },
+    /// The number is outside the bounds of the `#[tql(range)]` attribute.
+    OutOfRange {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    /// The string does not match the `#[tql(regex)]` attribute.
+    NoMatch {
+        regex: &'static str,
+    },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self.kind {
+            ValidationErrorKind::TooLong { max_length } =>
+                write!(formatter, "{} has more than {} characters", self.field, max_length),
+            ValidationErrorKind::OutOfRange { min, max } =>
+                match (min, max) {
+                    (Some(min), Some(max)) => write!(formatter, "{} is not between {} and {}", self.field, min, max),
+                    (Some(min), None) => write!(formatter, "{} is less than {}", self.field, min),
+                    (None, Some(max)) => write!(formatter, "{} is greater than {}", self.field, max),
+                    (None, None) => write!(formatter, "{} is out of range", self.field),
+                },
+            ValidationErrorKind::NoMatch { regex } =>
+                write!(formatter, "{} does not match the regex `{}`", self.field, regex),
+        }
+    }
+}
+
+impl error::Error for ValidationError {
+    fn description(&self) -> &str {
+        match self.kind {
+            ValidationErrorKind::TooLong { .. } => "string too long",
+            ValidationErrorKind::OutOfRange { .. } => "number out of range",
+            ValidationErrorKind::NoMatch { .. } => "string not matching regex",
+        }
+    }
+}
+
+thread_local! {
+    static REGEXES: RefCell<HashMap<&'static str, Regex>> = RefCell::new(HashMap::new());
+}
+
+/// Check if the value matches the regex of a `#[tql(regex)]` attribute.
+/// The regex is compiled on the first call only.
+#[doc(hidden)]
+pub fn regex_is_match(regex: &'static str, value: &str) -> bool {
+    REGEXES.with(|regexes| {
+        regexes.borrow_mut()
+            .entry(regex)
+            // NOTE: the regex is checked by #[derive(SqlTable)].
+            .or_insert_with(|| Regex::new(regex).expect("valid regex"))
+            .is_match(value)
+    })
+}
+
+/// A query parameter whose conversion fails with the first validation error of the value assigned
+/// to a field by the `sql!()` macro.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Validated<'a, P: 'a> {
+    errors: Vec<ValidationError>,
+    parameter: &'a P,
+}
+
+impl<'a, P> Validated<'a, P> {
+    pub fn new(parameter: &'a P, errors: Vec<ValidationError>) -> Self {
+        Validated {
+            errors,
+            parameter,
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl<'a, P: PgToSql> PgToSql for Validated<'a, P> {
+    fn to_sql(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        match self.errors.first() {
+            Some(error) => Err(Box::new(error.clone())),
+            None => self.parameter.to_sql(ty, out),
+        }
+    }
+
+    fn accepts(ty: &pg_types::Type) -> bool {
+        P::accepts(ty)
+    }
+
+    fn to_sql_checked(&self, ty: &pg_types::Type, out: &mut Vec<u8>) -> Result<IsNull, Box<Error + Sync + Send>> {
+        pg_types::__to_sql_checked(self, ty, out)
+    }
+}
+
+#[cfg(feature = "rusqlite")]
+impl<'a, P: ToSql> ToSql for Validated<'a, P> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput> {
+        match self.errors.first() {
+            Some(error) => Err(rusqlite::Error::ToSqlConversionFailure(Box::new(error.clone()))),
+            None => self.parameter.to_sql(),
+        }
+    }
+}
@@ -62,7 +62,7 @@ fn test_aggregate() {
     sql!(TableAggregateExpr.insert(field1 = "test", field2 = new_field1)).unwrap();
 
     let aggregate = sql!(TableAggregateExpr.aggregate(avg(field2))).unwrap();
-    assert_eq!((55.0 + 12.0 + 42.0) / 3.0, aggregate.field2_avg);
+    assert_eq!(Some((55.0 + 12.0 + 42.0) / 3.0), aggregate.field2_avg);
 
     let mut aggregates = sql!(TableAggregateExpr
           .values(field1)
@@ -70,21 +70,21 @@ fn test_aggregate() {
         .unwrap();
     assert_eq!(2, aggregates.len());
     aggregates.sort_by(|x, y| x.field2_avg.partial_cmp(&y.field2_avg).expect("aggregate value"));
-    assert_eq!(12.0, aggregates[0].field2_avg); // NOTE: round(12 / 1) = 12.
-    assert_eq!(48.5, aggregates[1].field2_avg); // NOTE: round((55 + 42) / 3) = 49.
+    assert_eq!(Some(12.0), aggregates[0].field2_avg); // NOTE: round(12 / 1) = 12.
+    assert_eq!(Some(48.5), aggregates[1].field2_avg); // NOTE: round((55 + 42) / 3) = 49.
 
     let aggregate = sql!(TableAggregateExpr.aggregate(average = avg(field2))).unwrap();
-    assert_eq!((55.0 + 12.0 + 42.0) / 3.0, aggregate.average);
+    assert_eq!(Some((55.0 + 12.0 + 42.0) / 3.0), aggregate.average);
 
     let aggregates = sql!(TableAggregateExpr.values(field1).aggregate(average = avg(field2)).filter(average < 20.0))
         .unwrap();
     assert_eq!(1, aggregates.len());
-    assert_eq!(12.0, aggregates[0].average); // NOTE: round(12 / 1) = 12.
+    assert_eq!(Some(12.0), aggregates[0].average); // NOTE: round(12 / 1) = 12.
 
     let aggregates = sql!(TableAggregateExpr.values(field1).aggregate(avg(field2)).filter(field2_avg < 20.0))
         .unwrap();
     assert_eq!(1, aggregates.len());
-    assert_eq!(12.0, aggregates[0].field2_avg); // NOTE: round(12 / 1) = 12.
+    assert_eq!(Some(12.0), aggregates[0].field2_avg); // NOTE: round(12 / 1) = 12.
 
     let aggregates = sql!(TableAggregateExpr
         .filter(field2 > 10)
@@ -92,13 +92,13 @@ fn test_aggregate() {
         .aggregate(avg(field2)).filter(field2_avg < 20.0))
         .unwrap();
     assert_eq!(1, aggregates.len());
-    assert_eq!(12.0, aggregates[0].field2_avg); // NOTE: round(12 / 1) = 12.
+    assert_eq!(Some(12.0), aggregates[0].field2_avg); // NOTE: round(12 / 1) = 12.
 
     let aggregates = sql!(TableAggregateExpr.filter(field2 > 10).values(field1)
                           .aggregate(average = avg(field2)).filter(average < 20.0))
         .unwrap();
     assert_eq!(1, aggregates.len());
-    assert_eq!(12.0, aggregates[0].average); // NOTE: round(12 / 1) = 12.
+    assert_eq!(Some(12.0), aggregates[0].average); // NOTE: round(12 / 1) = 12.
 
     let value1 = 10;
     let aggregates = sql!(TableAggregateExpr
@@ -108,7 +108,7 @@ fn test_aggregate() {
         .filter(average < 20.0))
         .unwrap();
     assert_eq!(1, aggregates.len());
-    assert_eq!(12.0, aggregates[0].average); // NOTE: round(12 / 1) = 12.
+    assert_eq!(Some(12.0), aggregates[0].average); // NOTE: round(12 / 1) = 12.
 
     let value2 = 20.0;
     let aggregates = sql!(TableAggregateExpr
@@ -118,5 +118,51 @@ fn test_aggregate() {
         .filter(average < value2))
         .unwrap();
     assert_eq!(1, aggregates.len());
-    assert_eq!(12.0, aggregates[0].average); // NOTE: round(12 / 1) = 12.
+    assert_eq!(Some(12.0), aggregates[0].average); // NOTE: round(12 / 1) = 12.
+
+    let aggregate = sql!(TableAggregateExpr.aggregate(count(primary_key), count_distinct(field1), total = sum(field2),
+                                                      min(field2), max(field2)))
+        .unwrap();
+    let count: i64 = aggregate.primary_key_count;
+    assert_eq!(3, count);
+    assert_eq!(2, aggregate.field1_count_distinct);
+    let total: Option<i64> = aggregate.total;
+    assert_eq!(Some(55 + 12 + 42), total);
+    let min: Option<i32> = aggregate.field2_min;
+    assert_eq!(Some(12), min);
+    assert_eq!(Some(55), aggregate.field2_max);
+
+    let aggregates = sql!(TableAggregateExpr
+        .filter(field2 < 20)
+        .values(field2)
+        .aggregate(names = string_agg(field1)))
+        .unwrap();
+    assert_eq!(1, aggregates.len());
+    let names: Option<&str> = aggregates[0].names.as_ref().map(String::as_str);
+    assert_eq!(Some("testing"), names);
+
+    let aggregate = sql!(TableAggregateExpr.aggregate(total = sum(field2 * 2 + 1))).unwrap();
+    assert_eq!(Some((55 + 12 + 42) * 2 + 3), aggregate.total);
+
+    let aggregates = sql!(TableAggregateExpr.values(field1).aggregate(total = sum(field2)).sort(-total)).unwrap();
+    assert_eq!(2, aggregates.len());
+    assert_eq!(Some(55 + 42), aggregates[0].total);
+    assert_eq!(Some(12), aggregates[1].total);
+
+    let aggregates = sql!(TableAggregateExpr.values(field1).aggregate(total = sum(field2)).sort(-total)[..1]).unwrap();
+    assert_eq!(1, aggregates.len());
+    assert_eq!(Some(55 + 42), aggregates[0].total);
+
+    let aggregate = sql!(TableAggregateExpr.values(field1).aggregate(total = sum(field2)).sort(total)[0]).unwrap();
+    assert_eq!(Some(12), aggregate.total);
+
+    let aggregate = sql!(TableAggregateExpr
+        .filter(field2 > 100)
+        .aggregate(count(primary_key), avg(field2), min(field2), max(field2), sum(field2)))
+        .unwrap();
+    assert_eq!(0, aggregate.primary_key_count);
+    assert_eq!(None, aggregate.field2_avg);
+    assert_eq!(None, aggregate.field2_min);
+    assert_eq!(None, aggregate.field2_max);
+    assert_eq!(None, aggregate.field2_sum);
 }
@@ -0,0 +1,109 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![cfg(feature = "postgres")]
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use tql::PrimaryKey;
+use tql_macros::sql;
+
+use connection::get_connection;
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+struct TableArray {
+    id: PrimaryKey,
+    numbers: Vec<i32>,
+    tags: Vec<String>,
+    scores: Option<Vec<f64>>,
+    title: String,
+}
+
+#[test]
+fn test_array() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableArray.drop());
+    });
+
+    let _ = sql!(TableArray.create());
+
+    let numbers = vec![1, 2, 3];
+    let tags = vec!["rust".to_string(), "sql".to_string()];
+    let id1 = sql!(TableArray.insert(numbers = numbers, tags = tags, scores = None, title = "first")).unwrap();
+    let numbers = vec![];
+    let tags = vec!["python".to_string()];
+    let scores = Some(vec![1.5, 2.5]);
+    let id2 = sql!(TableArray.insert(numbers = numbers, tags = tags, scores = scores, title = "second")).unwrap();
+
+    let table = sql!(TableArray.get(id1)).unwrap();
+    assert_eq!(vec![1, 2, 3], table.numbers);
+    assert_eq!(vec!["rust".to_string(), "sql".to_string()], table.tags);
+    assert_eq!(None, table.scores);
+
+    let table = sql!(TableArray.get(id2)).unwrap();
+    assert!(table.numbers.is_empty());
+    assert_eq!(Some(vec![1.5, 2.5]), table.scores);
+
+    let tables = sql!(TableArray.filter(tags.contains("rust"))).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let number = 2;
+    let tables = sql!(TableArray.filter(numbers.contains(&number))).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let tables = sql!(TableArray.filter(numbers.len() == 0)).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id2, tables[0].id);
+
+    let tables = sql!(TableArray.filter(tags.len() >= 1 && title.len() > 5)).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id2, tables[0].id);
+
+    let wanted = vec!["sql".to_string(), "python".to_string()];
+    let tables = sql!(TableArray.filter(tags.overlaps(&wanted)).sort(id)).unwrap();
+    assert_eq!(2, tables.len());
+    assert_eq!(id1, tables[0].id);
+    assert_eq!(id2, tables[1].id);
+
+    let wanted = vec!["go".to_string()];
+    let tables = sql!(TableArray.filter(tags.overlaps(&wanted))).unwrap();
+    assert!(tables.is_empty());
+
+    let tags = vec!["go".to_string()];
+    let _ = sql!(TableArray.get(id2).update(tags = tags));
+    let tables = sql!(TableArray.filter(tags.overlaps(&wanted))).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id2, tables[0].id);
+}
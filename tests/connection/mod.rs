@@ -36,7 +36,7 @@ macro_rules! backend_extern_crate {
 #[cfg(feature = "postgres")]
 use postgres::TlsMode;
 #[cfg(feature = "postgres")]
-pub use postgres::{Connection, Result};
+pub use postgres::{Connection, GenericConnection, Result};
 
 #[cfg(feature = "postgres")]
 #[allow(dead_code)]
@@ -47,6 +47,10 @@ pub fn get_connection() -> Connection {
 #[cfg(feature = "sqlite")]
 pub use rusqlite::{Connection, Error, Result};
 
+/// The type of the connection received by the hooks.
+#[cfg(feature = "sqlite")]
+pub type GenericConnection = Connection;
+
 #[cfg(feature = "sqlite")]
 #[allow(dead_code)]
 pub fn get_connection() -> Connection {
@@ -0,0 +1,141 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![cfg(all(feature = "postgres", feature = "decimal"))]
+#![feature(proc_macro)]
+
+extern crate rust_decimal;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+use tql::PrimaryKey;
+use tql_macros::sql;
+
+use connection::get_connection;
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+struct TableDecimal {
+    id: PrimaryKey,
+    #[tql(numeric(precision = 12, scale = 2))]
+    price: Decimal,
+    discount: Option<Decimal>,
+    quantity: i32,
+}
+
+fn decimal(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+#[test]
+fn test_decimal() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableDecimal.drop());
+    });
+
+    let _ = sql!(TableDecimal.create());
+
+    let price = decimal("10.10");
+    let id1 = sql!(TableDecimal.insert(price = price, discount = None, quantity = 1)).unwrap();
+    let price = decimal("20.20");
+    let discount = Some(decimal("0.5"));
+    let id2 = sql!(TableDecimal.insert(price = price, discount = discount, quantity = 2)).unwrap();
+    let price = decimal("0.30");
+    let _ = sql!(TableDecimal.insert(price = price, discount = None, quantity = 2)).unwrap();
+
+    let table = sql!(TableDecimal.get(id2)).unwrap();
+    assert_eq!(decimal("20.20"), table.price);
+    assert_eq!(Some(decimal("0.5")), table.discount);
+
+    let minimum = decimal("10.10");
+    let tables = sql!(TableDecimal.filter(price >= minimum).sort(id)).unwrap();
+    assert_eq!(2, tables.len());
+    assert_eq!(id1, tables[0].id);
+    assert_eq!(id2, tables[1].id);
+
+    let tables = sql!(TableDecimal.filter(discount.is_some())).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id2, tables[0].id);
+
+    let new_price = decimal("19.99");
+    let _ = sql!(TableDecimal.get(id2).update(price = new_price));
+    let table = sql!(TableDecimal.get(id2)).unwrap();
+    assert_eq!(decimal("19.99"), table.price);
+}
+
+#[test]
+fn test_decimal_aggregate() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableDecimalAggregate.drop());
+    });
+
+    let _ = sql!(TableDecimalAggregate.create());
+
+    let amount = decimal("0.10");
+    let _ = sql!(TableDecimalAggregate.insert(amount = amount, category = 1)).unwrap();
+    let amount = decimal("0.20");
+    let _ = sql!(TableDecimalAggregate.insert(amount = amount, category = 1)).unwrap();
+    let amount = decimal("0.60");
+    let _ = sql!(TableDecimalAggregate.insert(amount = amount, category = 2)).unwrap();
+
+    // NOTE: the sum of 0.1 and 0.2 is exact with decimals.
+    let aggregate = sql!(TableDecimalAggregate.aggregate(sum(amount))).unwrap();
+    assert_eq!(Some(decimal("0.90")), aggregate.amount_sum);
+
+    let aggregate = sql!(TableDecimalAggregate.aggregate(total = sum(amount), average = avg(amount))).unwrap();
+    let total: Option<Decimal> = aggregate.total;
+    let average: Option<Decimal> = aggregate.average;
+    assert_eq!(Some(decimal("0.90")), total);
+    assert_eq!(Some(decimal("0.30")), average);
+
+    let aggregates = sql!(TableDecimalAggregate.values(category).aggregate(total = sum(amount)).sort(category)).unwrap();
+    assert_eq!(2, aggregates.len());
+    assert_eq!(Some(decimal("0.30")), aggregates[0].total);
+    assert_eq!(Some(decimal("0.60")), aggregates[1].total);
+
+    let minimum = decimal("0.50");
+    let aggregates = sql!(TableDecimalAggregate.values(category).aggregate(total = sum(amount)).filter(total > minimum))
+        .unwrap();
+    assert_eq!(1, aggregates.len());
+    assert_eq!(Some(decimal("0.60")), aggregates[0].total);
+}
+
+#[derive(SqlTable)]
+struct TableDecimalAggregate {
+    id: PrimaryKey,
+    #[tql(numeric(precision = 10, scale = 2))]
+    amount: Decimal,
+    category: i32,
+}
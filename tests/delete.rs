@@ -57,3 +57,15 @@ fn test_delete() {
         to_sql!(Table.get(id).delete())
     );
 }
+
+#[test]
+fn test_delete_returning() {
+    assert_eq!(
+        "DELETE FROM Table WHERE Table.field1 = 'test' RETURNING id, field1, field2",
+        to_sql!(Table.filter(field1 == "test").delete().returning())
+    );
+    assert_eq!(
+        "DELETE FROM Table WHERE Table.id = $1 RETURNING field2",
+        to_sql!(Table.get(id).delete().returning(field2))
+    );
+}
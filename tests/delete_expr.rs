@@ -21,6 +21,7 @@
 
 #![feature(proc_macro)]
 
+extern crate chrono;
 extern crate tql;
 #[macro_use]
 extern crate tql_macros;
@@ -31,6 +32,7 @@ mod teardown;
 
 backend_extern_crate!();
 
+use chrono::NaiveDateTime;
 use tql::PrimaryKey;
 use tql_macros::sql;
 
@@ -111,3 +113,157 @@ fn test_delete() {
     let table = sql!(TableDeleteExpr.get(id));
     assert!(is_not_found(table));
 }
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableDeleteReturningExpr {
+    id: PrimaryKey,
+    field1: String,
+    field2: i32,
+}
+
+#[test]
+fn test_delete_returning() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableDeleteReturningExpr.drop());
+    });
+
+    let _ = sql!(TableDeleteReturningExpr.create());
+
+    let id1 = sql!(TableDeleteReturningExpr.insert(field1 = "value1", field2 = 1)).unwrap();
+    let id2 = sql!(TableDeleteReturningExpr.insert(field1 = "value2", field2 = 2)).unwrap();
+
+    let tables = sql!(TableDeleteReturningExpr.get(id1).delete().returning()).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+    assert_eq!("value1", tables[0].field1);
+    assert_eq!(1, tables[0].field2);
+
+    let values = sql!(TableDeleteReturningExpr.filter(field2 > 0).delete().returning(id, field1)).unwrap();
+    assert_eq!(vec![(id2, "value2".to_string())], values);
+
+    let values = sql!(TableDeleteReturningExpr.filter(field2 > 0).delete().returning(id)).unwrap();
+    assert!(values.is_empty());
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableDeleteInstanceExpr {
+    id: PrimaryKey,
+    field1: String,
+    field2: i32,
+}
+
+#[test]
+fn test_delete_instance() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableDeleteInstanceExpr.drop());
+    });
+
+    let _ = sql!(TableDeleteInstanceExpr.create());
+
+    let id1 = sql!(TableDeleteInstanceExpr.insert(field1 = "value1", field2 = 1)).unwrap();
+    let id2 = sql!(TableDeleteInstanceExpr.insert(field1 = "value2", field2 = 2)).unwrap();
+
+    let table = sql!(TableDeleteInstanceExpr.get(id1)).unwrap();
+    let num_deleted = table.delete(&connection).unwrap();
+    assert_eq!(1, num_deleted);
+
+    let table = sql!(TableDeleteInstanceExpr.get(id1));
+    assert!(is_not_found(table));
+
+    let table = sql!(TableDeleteInstanceExpr.get(id2));
+    assert!(table.is_ok());
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableDeleteSoftExpr {
+    id: PrimaryKey,
+    field1: String,
+    field2: i32,
+    #[tql(soft_delete)]
+    deleted_at: Option<NaiveDateTime>,
+}
+
+#[test]
+fn test_delete_soft() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableDeleteSoftExpr.drop());
+    });
+
+    let _ = sql!(TableDeleteSoftExpr.create());
+
+    let id1 = sql!(TableDeleteSoftExpr.insert(field1 = "value1", field2 = 1)).unwrap();
+    let id2 = sql!(TableDeleteSoftExpr.insert(field1 = "value2", field2 = 2)).unwrap();
+    let id3 = sql!(TableDeleteSoftExpr.insert(field1 = "value3", field2 = 3)).unwrap();
+
+    let num_deleted = sql!(TableDeleteSoftExpr.get(id1).delete()).unwrap();
+    assert_eq!(1, num_deleted);
+
+    let table = sql!(TableDeleteSoftExpr.get(id1));
+    assert!(is_not_found(table));
+
+    let table = sql!(TableDeleteSoftExpr.with_deleted().get(id1)).unwrap();
+    assert_eq!("value1", table.field1);
+    assert!(table.deleted_at.is_some());
+
+    // The rows already deleted keep their deletion date.
+    let num_deleted = sql!(TableDeleteSoftExpr.filter(field2 <= 2).delete()).unwrap();
+    assert_eq!(1, num_deleted);
+    let deleted_table = sql!(TableDeleteSoftExpr.with_deleted().get(id1)).unwrap();
+    assert_eq!(table.deleted_at, deleted_table.deleted_at);
+    // The deleted rows are only updated with with_deleted().
+    let num_updated = sql!(TableDeleteSoftExpr.get(id2).update(deleted_at = None)).unwrap();
+    assert_eq!(0, num_updated);
+    let num_updated = sql!(TableDeleteSoftExpr.with_deleted().get(id2).update(deleted_at = None)).unwrap();
+    assert_eq!(1, num_updated);
+
+    let tables = sql!(TableDeleteSoftExpr.sort(id)).unwrap();
+    assert_eq!(vec![id2, id3], tables.iter().map(|table| table.id).collect::<Vec<_>>());
+
+    // The condition on the deleted rows must apply to the whole filter.
+    let tables = sql!(TableDeleteSoftExpr.filter(field2 == 1 || field2 == 2)).unwrap();
+    assert_eq!(vec![id2], tables.iter().map(|table| table.id).collect::<Vec<_>>());
+
+    let tables = sql!(TableDeleteSoftExpr.with_deleted().sort(id)).unwrap();
+    assert_eq!(3, tables.len());
+
+    let tables = sql!(TableDeleteSoftExpr.only_deleted()).unwrap();
+    assert_eq!(vec![id1], tables.iter().map(|table| table.id).collect::<Vec<_>>());
+
+    let count = sql!(TableDeleteSoftExpr.count()).unwrap();
+    assert_eq!(2, count);
+
+    let count = sql!(TableDeleteSoftExpr.with_deleted().count()).unwrap();
+    assert_eq!(3, count);
+
+    let exists = sql!(TableDeleteSoftExpr.filter(field2 == 1).exists()).unwrap();
+    assert!(!exists);
+
+    let exists = sql!(TableDeleteSoftExpr.filter(field2 == 1).only_deleted().exists()).unwrap();
+    assert!(exists);
+
+    let table = sql!(TableDeleteSoftExpr.get(id2)).unwrap();
+    table.delete(&connection).unwrap();
+
+    let count = sql!(TableDeleteSoftExpr.only_deleted().count()).unwrap();
+    assert_eq!(2, count);
+
+    // The rows are really deleted with with_deleted() or only_deleted().
+    let num_deleted = sql!(TableDeleteSoftExpr.with_deleted().get(id3).delete()).unwrap();
+    assert_eq!(1, num_deleted);
+    let count = sql!(TableDeleteSoftExpr.with_deleted().count()).unwrap();
+    assert_eq!(2, count);
+
+    let num_deleted = sql!(TableDeleteSoftExpr.only_deleted().filter(field2 <= 2).delete()).unwrap();
+    assert_eq!(2, num_deleted);
+    let count = sql!(TableDeleteSoftExpr.with_deleted().count()).unwrap();
+    assert_eq!(0, count);
+}
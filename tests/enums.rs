@@ -0,0 +1,86 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use tql::PrimaryKey;
+use tql_macros::sql;
+
+use connection::get_connection;
+use teardown::TearDown;
+
+#[derive(Clone, Debug, PartialEq, SqlEnum)]
+enum Status {
+    Active,
+    Inactive,
+    Banned,
+}
+
+#[derive(SqlTable)]
+struct TableEnum {
+    id: PrimaryKey,
+    status: Status,
+    previous_status: Option<Status>,
+}
+
+#[test]
+fn test_enum() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableEnum.drop());
+    });
+
+    let _ = sql!(TableEnum.create());
+
+    let id1 = sql!(TableEnum.insert(status = Status::Active, previous_status = None)).unwrap();
+    let id2 = sql!(TableEnum.insert(status = Status::Banned, previous_status = Some(Status::Active))).unwrap();
+
+    let table = sql!(TableEnum.get(id2)).unwrap();
+    assert_eq!(Status::Banned, table.status);
+    assert_eq!(Some(Status::Active), table.previous_status);
+
+    let tables = sql!(TableEnum.filter(status == Status::Active)).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let banned = Status::Banned;
+    let tables = sql!(TableEnum.filter(status != banned)).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let _ = sql!(TableEnum.get(id1).update(status = Status::Inactive, previous_status = Some(Status::Active)));
+    let table = sql!(TableEnum.get(id1)).unwrap();
+    assert_eq!(Status::Inactive, table.status);
+    assert_eq!(Some(Status::Active), table.previous_status);
+
+    assert_eq!(0, sql!(TableEnum.filter(status == Status::Active).count()).unwrap());
+}
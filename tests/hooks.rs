@@ -0,0 +1,121 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use tql::{Hooks, PrimaryKey};
+use tql_macros::sql;
+
+use connection::{GenericConnection, Result, get_connection};
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+#[tql(hooks)]
+struct TableHooks {
+    id: PrimaryKey,
+    email: String,
+    inserted: bool,
+}
+
+#[derive(SqlTable)]
+struct TableHooksAudit {
+    id: PrimaryKey,
+    deleted_id: i32,
+}
+
+impl Hooks for TableHooks {
+    fn before_insert(&mut self, _connection: &GenericConnection) -> Result<()> {
+        self.email = self.email.to_lowercase();
+        Ok(())
+    }
+
+    fn after_insert(&mut self, _connection: &GenericConnection) -> Result<()> {
+        self.inserted = true;
+        Ok(())
+    }
+
+    fn before_update(&mut self, _connection: &GenericConnection) -> Result<()> {
+        self.email = self.email.to_lowercase();
+        Ok(())
+    }
+
+    fn after_delete(&self, connection: &GenericConnection) -> Result<()> {
+        let id = self.id;
+        sql!(TableHooksAudit.insert(deleted_id = id))?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_hooks() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableHooks.drop());
+        let _ = sql!(TableHooksAudit.drop());
+    });
+
+    let _ = sql!(TableHooks.create());
+    let _ = sql!(TableHooksAudit.create());
+
+    let mut table = TableHooks {
+        id: 0,
+        email: "Me@Example.com".to_string(),
+        inserted: false,
+    };
+    let id = table.insert(&connection).unwrap();
+    assert_eq!("me@example.com", table.email);
+    assert!(table.inserted);
+
+    let table = sql!(TableHooks.get(id)).unwrap();
+    assert_eq!("me@example.com", table.email);
+    assert!(!table.inserted);
+
+    let mut table = table;
+    table.email = "New@Example.com".to_string();
+    table.save(&connection).unwrap();
+    let table = sql!(TableHooks.get(id)).unwrap();
+    assert_eq!("new@example.com", table.email);
+
+    // The queries of sql!() bypass the hooks.
+    let _ = sql!(TableHooks.get(id).update(email = "Other@Example.com"));
+    let table = sql!(TableHooks.get(id)).unwrap();
+    assert_eq!("Other@Example.com", table.email);
+
+    table.delete(&connection).unwrap();
+    let audits = sql!(TableHooksAudit.all()).unwrap();
+    assert_eq!(vec![id], audits.iter().map(|audit| audit.deleted_id).collect::<Vec<_>>());
+
+    let id = sql!(TableHooks.insert(email = "Third@Example.com", inserted = false)).unwrap();
+    let _ = sql!(TableHooks.get(id).delete());
+    let count = sql!(TableHooksAudit.count()).unwrap();
+    assert_eq!(1, count);
+}
@@ -21,10 +21,12 @@
 
 #![feature(proc_macro)]
 
+extern crate chrono;
 extern crate tql;
 #[macro_use]
 extern crate tql_macros;
 
+use chrono::NaiveDateTime;
 #[cfg(feature = "postgres")]
 use postgres::error::UNDEFINED_TABLE;
 use tql::{ForeignKey, PrimaryKey};
@@ -186,3 +188,246 @@ fn test_insert() {
     )).unwrap();
     assert_eq!(4, id);
 }
+
+#[derive(SqlTable)]
+struct TableUpsertExpr {
+    primary_key: PrimaryKey,
+    field1: String,
+    field2: i32,
+}
+
+#[test]
+fn test_insert_on_conflict() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUpsertExpr.drop());
+    });
+
+    let _ = sql!(TableUpsertExpr.create());
+
+    let id = sql!(TableUpsertExpr.insert(field1 = "value1", field2 = 55).on_conflict_ignore()).unwrap();
+    assert_eq!(Some(1), id);
+
+    let id = sql!(TableUpsertExpr.insert(primary_key = 1, field1 = "value2", field2 = 42).on_conflict_ignore())
+        .unwrap();
+    assert_eq!(None, id);
+
+    let table = sql!(TableUpsertExpr.get(1)).unwrap();
+    assert_eq!("value1", table.field1);
+    assert_eq!(55, table.field2);
+
+    let new_field1 = "value3";
+    let id = sql!(TableUpsertExpr.insert(primary_key = 1, field1 = new_field1, field2 = 42)
+                  .on_conflict(primary_key).update(field1 = new_field1, field2 += 1)).unwrap();
+    assert_eq!(Some(1), id);
+
+    let table = sql!(TableUpsertExpr.get(1)).unwrap();
+    assert_eq!("value3", table.field1);
+    assert_eq!(56, table.field2);
+
+    let id = sql!(TableUpsertExpr.insert(primary_key = 2, field1 = new_field1, field2 = 42)
+                  .on_conflict(primary_key).update(field2 += 1)).unwrap();
+    assert_eq!(Some(2), id);
+
+    let table = sql!(TableUpsertExpr.get(2)).unwrap();
+    assert_eq!("value3", table.field1);
+    assert_eq!(42, table.field2);
+}
+
+#[derive(SqlTable)]
+struct TableInsertReturningExpr {
+    primary_key: PrimaryKey,
+    field1: String,
+    field2: i32,
+}
+
+#[derive(SqlTable)]
+struct TableInsertInstanceExpr {
+    primary_key: PrimaryKey,
+    field1: String,
+    field2: i32,
+    optional_field: Option<i32>,
+}
+
+#[derive(SqlTable)]
+struct TableInsertAutomaticExpr {
+    primary_key: PrimaryKey,
+    field1: String,
+    #[tql(auto_now_add)]
+    date_added: NaiveDateTime,
+    #[tql(auto_now)]
+    date_modified: NaiveDateTime,
+}
+
+#[derive(SqlTable)]
+struct TableInsertManyExpr {
+    primary_key: PrimaryKey,
+    field1: String,
+    field2: i32,
+    optional_field: Option<i32>,
+}
+
+#[test]
+fn test_insert_many() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableInsertManyExpr.drop());
+    });
+
+    let _ = sql!(TableInsertManyExpr.create());
+
+    let rows = vec![
+        TableInsertManyExpr {
+            primary_key: 0,
+            field1: "value1".to_string(),
+            field2: 55,
+            optional_field: None,
+        },
+        TableInsertManyExpr {
+            primary_key: 0,
+            field1: "value2".to_string(),
+            field2: 42,
+            optional_field: Some(12),
+        },
+    ];
+    let ids = sql!(TableInsertManyExpr.insert_many(rows)).unwrap();
+    assert_eq!(vec![1, 2], ids);
+
+    let tables = sql!(TableInsertManyExpr.all().sort(primary_key)).unwrap();
+    assert_eq!(2, tables.len());
+    assert_eq!("value1", tables[0].field1);
+    assert_eq!(55, tables[0].field2);
+    assert!(tables[0].optional_field.is_none());
+    assert_eq!("value2", tables[1].field1);
+    assert_eq!(42, tables[1].field2);
+    assert_eq!(Some(12), tables[1].optional_field);
+
+    let rows = (0..2000).map(|index| (format!("value{}", index), index, None));
+    let ids = sql!(TableInsertManyExpr.insert_many(rows)).unwrap();
+    assert_eq!((3..2003).collect::<Vec<_>>(), ids);
+
+    let count = sql!(TableInsertManyExpr.filter(field2 < 2000).count()).unwrap();
+    assert_eq!(2002, count);
+
+    let rows: Vec<(String, i32, Option<i32>)> = vec![];
+    let ids = sql!(TableInsertManyExpr.insert_many(rows)).unwrap();
+    assert!(ids.is_empty());
+}
+
+#[test]
+fn test_insert_returning() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableInsertReturningExpr.drop());
+    });
+
+    let _ = sql!(TableInsertReturningExpr.create());
+
+    let table = sql!(TableInsertReturningExpr.insert(field1 = "value1", field2 = 55).returning()).unwrap();
+    assert_eq!(1, table.primary_key);
+    assert_eq!("value1", table.field1);
+    assert_eq!(55, table.field2);
+
+    let (id, field2) = sql!(TableInsertReturningExpr.insert(field1 = "value2", field2 = 42).returning(primary_key, field2))
+        .unwrap();
+    assert_eq!(2, id);
+    assert_eq!(42, field2);
+
+    let field1 = sql!(TableInsertReturningExpr.insert(primary_key = 2, field1 = "value3", field2 = 24)
+                      .on_conflict_ignore().returning(field1)).unwrap();
+    assert_eq!(None, field1);
+
+    let table = sql!(TableInsertReturningExpr.insert(primary_key = 2, field1 = "value3", field2 = 24)
+                     .on_conflict(primary_key).update(field2 += 1).returning()).unwrap().unwrap();
+    assert_eq!(2, table.primary_key);
+    assert_eq!("value2", table.field1);
+    assert_eq!(43, table.field2);
+}
+
+#[test]
+fn test_insert_instance() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableInsertInstanceExpr.drop());
+    });
+
+    let _ = sql!(TableInsertInstanceExpr.create());
+
+    let mut table = TableInsertInstanceExpr {
+        primary_key: 0,
+        field1: "value1".to_string(),
+        field2: 55,
+        optional_field: None,
+    };
+    let id = table.insert(&connection).unwrap();
+    assert_eq!(1, id);
+    assert_eq!(1, table.primary_key);
+
+    let mut table2 = TableInsertInstanceExpr {
+        primary_key: 0,
+        field1: "value2".to_string(),
+        field2: 42,
+        optional_field: Some(12),
+    };
+    table2.insert(&connection).unwrap();
+    assert_eq!(2, table2.primary_key);
+
+    let table = sql!(TableInsertInstanceExpr.get(2)).unwrap();
+    assert_eq!("value2", table.field1);
+    assert_eq!(42, table.field2);
+    assert_eq!(Some(12), table.optional_field);
+}
+
+#[test]
+fn test_insert_automatic() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableInsertAutomaticExpr.drop());
+    });
+
+    let _ = sql!(TableInsertAutomaticExpr.create());
+
+    let id = sql!(TableInsertAutomaticExpr.insert(field1 = "value1")).unwrap();
+    let table = sql!(TableInsertAutomaticExpr.get(id)).unwrap();
+    assert_eq!("value1", table.field1);
+    assert_eq!(table.date_added, table.date_modified);
+
+    let date = NaiveDateTime::from_timestamp(0, 0);
+    let mut table = TableInsertAutomaticExpr {
+        primary_key: 0,
+        field1: "value2".to_string(),
+        date_added: date,
+        date_modified: date,
+    };
+    let id = table.insert(&connection).unwrap();
+    let table = sql!(TableInsertAutomaticExpr.get(id)).unwrap();
+    assert_eq!("value2", table.field1);
+    assert!(table.date_added > date);
+    assert_eq!(table.date_added, table.date_modified);
+
+    let rows = vec![
+        TableInsertAutomaticExpr {
+            primary_key: 0,
+            field1: "value3".to_string(),
+            date_added: date,
+            date_modified: date,
+        },
+    ];
+    let ids = sql!(TableInsertAutomaticExpr.insert_many(rows)).unwrap();
+    let table = sql!(TableInsertAutomaticExpr.get(ids[0])).unwrap();
+    assert_eq!("value3", table.field1);
+    assert!(table.date_added > date);
+    assert_eq!(table.date_added, table.date_modified);
+
+    let rows = vec![("value4".to_string(),)];
+    let ids = sql!(TableInsertAutomaticExpr.insert_many(rows)).unwrap();
+    let table = sql!(TableInsertAutomaticExpr.get(ids[0])).unwrap();
+    assert_eq!("value4", table.field1);
+    assert!(table.date_added > date);
+    assert_eq!(table.date_added, table.date_modified);
+}
@@ -0,0 +1,139 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![cfg(feature = "json")]
+#![feature(proc_macro)]
+
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use serde_json::Value;
+use tql::{Json, PrimaryKey};
+use tql_macros::sql;
+
+use connection::{get_connection, is_not_found};
+use teardown::TearDown;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Payload {
+    kind: String,
+    count: i32,
+}
+
+#[derive(SqlTable)]
+struct TableJson {
+    id: PrimaryKey,
+    payload: Json<Payload>,
+    extra: Json<Value>,
+}
+
+#[test]
+fn test_json() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableJson.drop());
+    });
+
+    let _ = sql!(TableJson.create());
+
+    let payload = Json(Payload {
+        kind: "x".to_string(),
+        count: 1,
+    });
+    let extra = Json(json!({ "tags": ["a", "b"], "level": 3 }));
+    let id1 = sql!(TableJson.insert(payload = payload, extra = extra)).unwrap();
+
+    let payload = Json(Payload {
+        kind: "y".to_string(),
+        count: 2,
+    });
+    let extra = Json(json!({ "tags": ["c"] }));
+    let id2 = sql!(TableJson.insert(payload = payload, extra = extra)).unwrap();
+
+    let table = sql!(TableJson.get(id1)).unwrap();
+    assert_eq!("x", table.payload.kind);
+    assert_eq!(1, table.payload.count);
+    assert_eq!(3, table.extra["level"]);
+
+    let tables = sql!(TableJson.filter(payload.get("kind") == "x")).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let tables = sql!(TableJson.filter(payload.get("kind") == "z")).unwrap();
+    assert_eq!(0, tables.len());
+
+    let payload = Json(Payload {
+        kind: "z".to_string(),
+        count: 3,
+    });
+    let _ = sql!(TableJson.get(id2).update(payload = payload));
+    let table = sql!(TableJson.filter(payload.get("kind") == "z").get()).unwrap();
+    assert_eq!(id2, table.id);
+    assert_eq!(3, table.payload.count);
+
+    let result = sql!(TableJson.filter(payload.get("kind") == "y").get());
+    assert!(is_not_found(result));
+}
+
+#[test]
+fn test_json_contains() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableJsonContains.drop());
+    });
+
+    let _ = sql!(TableJsonContains.create());
+
+    let document = Json(json!({ "tags": ["a", "b"], "level": 3 }));
+    let id1 = sql!(TableJsonContains.insert(document = document)).unwrap();
+    let document = Json(json!({ "tags": ["c"], "level": 3 }));
+    let id2 = sql!(TableJsonContains.insert(document = document)).unwrap();
+
+    let needle = Json(json!({ "tags": ["a"] }));
+    let tables = sql!(TableJsonContains.filter(document.contains(&needle))).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let needle = Json(json!({ "level": 3 }));
+    let tables = sql!(TableJsonContains.filter(document.contains(&needle)).sort(id)).unwrap();
+    assert_eq!(2, tables.len());
+    assert_eq!(id1, tables[0].id);
+    assert_eq!(id2, tables[1].id);
+}
+
+#[derive(SqlTable)]
+struct TableJsonContains {
+    id: PrimaryKey,
+    document: Json<Value>,
+}
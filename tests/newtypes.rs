@@ -0,0 +1,86 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use tql::PrimaryKey;
+use tql_macros::sql;
+
+use connection::get_connection;
+use teardown::TearDown;
+
+#[derive(Clone, Debug, PartialEq, SqlType)]
+struct Email(String);
+
+#[derive(Clone, Debug, PartialEq, SqlType)]
+struct Cents(i64);
+
+#[derive(SqlTable)]
+struct TableNewtype {
+    id: PrimaryKey,
+    email: Email,
+    price: Cents,
+    discount: Option<Cents>,
+}
+
+#[test]
+fn test_newtype() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableNewtype.drop());
+    });
+
+    let _ = sql!(TableNewtype.create());
+
+    let id1 = sql!(TableNewtype.insert(email = Email("me@example.com".to_string()), price = Cents(1000),
+                                       discount = None)).unwrap();
+    let email = Email("you@example.com".to_string());
+    let id2 = sql!(TableNewtype.insert(email = email, price = Cents(250), discount = Some(Cents(50)))).unwrap();
+
+    let table = sql!(TableNewtype.get(id2)).unwrap();
+    assert_eq!(Email("you@example.com".to_string()), table.email);
+    assert_eq!(Cents(250), table.price);
+    assert_eq!(Some(Cents(50)), table.discount);
+
+    let me = Email("me@example.com".to_string());
+    let table = sql!(TableNewtype.get(email == me)).unwrap();
+    assert_eq!(id1, table.id);
+    assert_eq!(None, table.discount);
+
+    let tables = sql!(TableNewtype.filter(price > Cents(500))).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let _ = sql!(TableNewtype.get(id1).update(discount = Some(Cents(100))));
+    let table = sql!(TableNewtype.get(id1)).unwrap();
+    assert_eq!(Some(Cents(100)), table.discount);
+}
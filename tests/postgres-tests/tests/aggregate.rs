@@ -26,7 +26,7 @@ extern crate tql;
 #[macro_use]
 extern crate tql_macros;
 
-use tql::PrimaryKey;
+use tql::{ForeignKey, PrimaryKey};
 use tql_macros::to_sql;
 
 #[derive(SqlTable)]
@@ -35,36 +35,101 @@ struct Table {
     id: PrimaryKey,
     field1: String,
     field2: i32,
+    field3: i32,
+    related_field: ForeignKey<RelatedTable>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct RelatedTable {
+    id: PrimaryKey,
+    field1: i32,
 }
 
 #[test]
 fn test_aggregate() {
     assert_eq!(
-        "SELECT CAST(AVG(field2) AS DOUBLE PRECISION) FROM Table",
+        "SELECT CAST(AVG(Table.field2) AS DOUBLE PRECISION) FROM Table",
         to_sql!(Table.aggregate(avg(field2)))
     );
     assert_eq!(
-        "SELECT CAST(AVG(field2) AS DOUBLE PRECISION) FROM Table GROUP BY field1",
+        "SELECT CAST(AVG(Table.field2) AS DOUBLE PRECISION) FROM Table GROUP BY Table.field1",
         to_sql!(Table.values(field1).aggregate(avg(field2)))
     );
     assert_eq!(
-        "SELECT CAST(AVG(field2) AS DOUBLE PRECISION) FROM Table",
+        "SELECT CAST(AVG(Table.field2) AS DOUBLE PRECISION) FROM Table",
         to_sql!(Table.aggregate(average = avg(field2)))
     );
     assert_eq!(
-        "SELECT CAST(AVG(field2) AS DOUBLE PRECISION) FROM Table GROUP BY field1 HAVING CAST(AVG(field2) AS DOUBLE PRECISION) < 20",
+        "SELECT CAST(AVG(Table.field2) AS DOUBLE PRECISION) FROM Table GROUP BY Table.field1 HAVING CAST(AVG(Table.field2) AS DOUBLE PRECISION) < 20",
         to_sql!(Table.values(field1).aggregate(average = avg(field2)).filter(average < 20))
     );
     assert_eq!(
-        "SELECT CAST(AVG(field2) AS DOUBLE PRECISION) FROM Table GROUP BY field1 HAVING CAST(AVG(field2) AS DOUBLE PRECISION) < 20",
+        "SELECT CAST(AVG(Table.field2) AS DOUBLE PRECISION) FROM Table GROUP BY Table.field1 HAVING CAST(AVG(Table.field2) AS DOUBLE PRECISION) < 20",
         to_sql!(Table.values(field1).aggregate(avg(field2)).filter(field2_avg < 20))
     );
     assert_eq!(
-        "SELECT CAST(AVG(field2) AS DOUBLE PRECISION) FROM Table WHERE Table.field2 > 10 GROUP BY field1 HAVING CAST(AVG(field2) AS DOUBLE PRECISION) < 20",
+        "SELECT CAST(AVG(Table.field2) AS DOUBLE PRECISION) FROM Table WHERE Table.field2 > 10 GROUP BY Table.field1 HAVING CAST(AVG(Table.field2) AS DOUBLE PRECISION) < 20",
         to_sql!(Table.filter(field2 > 10).values(field1).aggregate(avg(field2)).filter(field2_avg < 20))
     );
     assert_eq!(
-        "SELECT CAST(AVG(field2) AS DOUBLE PRECISION) FROM Table WHERE Table.field2 > 10 GROUP BY field1 HAVING CAST(AVG(field2) AS DOUBLE PRECISION) < 20",
+        "SELECT CAST(AVG(Table.field2) AS DOUBLE PRECISION) FROM Table WHERE Table.field2 > 10 GROUP BY Table.field1 HAVING CAST(AVG(Table.field2) AS DOUBLE PRECISION) < 20",
         to_sql!(Table.filter(field2 > 10).values(field1).aggregate(average = avg(field2)).filter(average < 20))
     );
 }
+
+#[test]
+fn test_aggregate_functions() {
+    assert_eq!(
+        "SELECT COUNT(Table.id), COUNT(DISTINCT Table.field1) FROM Table",
+        to_sql!(Table.aggregate(count(id), count_distinct(field1)))
+    );
+    assert_eq!(
+        "SELECT MIN(Table.field2), MAX(Table.field2) FROM Table GROUP BY Table.field1",
+        to_sql!(Table.values(field1).aggregate(min(field2), max(field2)))
+    );
+    assert_eq!(
+        "SELECT CAST(SUM(Table.field2) AS BIGINT) FROM Table GROUP BY Table.field1 HAVING SUM(Table.field2) > 100",
+        to_sql!(Table.values(field1).aggregate(total = sum(field2)).filter(total > 100))
+    );
+    assert_eq!(
+        "SELECT STRING_AGG(Table.field1, ',') FROM Table GROUP BY Table.field2",
+        to_sql!(Table.values(field2).aggregate(string_agg(field1)))
+    );
+}
+
+#[test]
+fn test_aggregate_expression() {
+    assert_eq!(
+        "SELECT CAST(SUM(Table.field2 * Table.field3) AS BIGINT) FROM Table",
+        to_sql!(Table.aggregate(total = sum(field2 * field3)))
+    );
+    assert_eq!(
+        "SELECT CAST(AVG((Table.field2 + Table.field3) / 2) AS DOUBLE PRECISION) FROM Table GROUP BY Table.field1",
+        to_sql!(Table.values(field1).aggregate(average = avg((field2 + field3) / 2)))
+    );
+    assert_eq!(
+        "SELECT CAST(AVG(RelatedTable.field1) AS DOUBLE PRECISION) FROM Table INNER JOIN RelatedTable ON Table.related_field = RelatedTable.id",
+        to_sql!(Table.aggregate(avg(related_field.field1)).join(related_field))
+    );
+    assert_eq!(
+        "SELECT CAST(SUM(RelatedTable.field1) AS BIGINT) FROM Table INNER JOIN RelatedTable ON Table.related_field = RelatedTable.id GROUP BY Table.field1",
+        to_sql!(Table.values(field1).aggregate(sum(related_field.field1)).join(related_field))
+    );
+}
+
+#[test]
+fn test_aggregate_sort_limit() {
+    assert_eq!(
+        "SELECT CAST(SUM(Table.field2) AS BIGINT) FROM Table GROUP BY Table.field1 ORDER BY SUM(Table.field2) DESC LIMIT 10",
+        to_sql!(Table.values(field1).aggregate(total = sum(field2)).sort(-total)[..10])
+    );
+    assert_eq!(
+        "SELECT CAST(SUM(Table.field2) AS BIGINT) FROM Table GROUP BY Table.field1 ORDER BY Table.field1, SUM(Table.field2) DESC",
+        to_sql!(Table.values(field1).aggregate(total = sum(field2)).sort(field1, -total))
+    );
+    assert_eq!(
+        "SELECT CAST(SUM(Table.field2) AS BIGINT) FROM Table GROUP BY Table.field1 HAVING SUM(Table.field2) > 100 ORDER BY SUM(Table.field2) LIMIT 3 OFFSET 2",
+        to_sql!(Table.values(field1).aggregate(total = sum(field2)).filter(total > 100).sort(total)[2..5])
+    );
+}
@@ -65,3 +65,46 @@ fn test_insert() {
         to_sql!(Table.insert(field1 = "value1", field2 = 55, related_field = related_object, optional_field = 42))
     );
 }
+
+#[test]
+fn test_insert_on_conflict() {
+    assert_eq!(
+        "INSERT INTO Table(field1, field2, related_field) VALUES('value1', 55, $1) ON CONFLICT DO NOTHING RETURNING id",
+        to_sql!(Table.insert(field1 = "value1", field2 = 55, related_field = related_object).on_conflict_ignore())
+    );
+    assert_eq!(
+        "INSERT INTO Table(field1, field2, related_field) VALUES('value1', 55, $1) ON CONFLICT (field1) DO UPDATE SET field2 = $2 RETURNING id",
+        to_sql!(Table.insert(field1 = "value1", field2 = 55, related_field = related_object)
+                .on_conflict(field1).update(field2 = new_field2))
+    );
+    assert_eq!(
+        "INSERT INTO Table(field1, field2, related_field) VALUES('value1', 55, $1) ON CONFLICT (field1, field2) DO UPDATE SET field2 = Table.field2 + 1, optional_field = 42 RETURNING id",
+        to_sql!(Table.insert(field1 = "value1", field2 = 55, related_field = related_object)
+                .on_conflict(field1, field2).update(field2 += 1, optional_field = 42))
+    );
+}
+
+#[test]
+fn test_insert_many() {
+    assert_eq!(
+        "INSERT INTO Table(field1, field2, related_field, optional_field) VALUES {} RETURNING id",
+        to_sql!(Table.insert_many(rows))
+    );
+}
+
+#[test]
+fn test_insert_returning() {
+    assert_eq!(
+        "INSERT INTO RelatedTable(field1) VALUES('test') RETURNING id, field1",
+        to_sql!(RelatedTable.insert(field1 = "test").returning())
+    );
+    assert_eq!(
+        "INSERT INTO Table(field1, field2, related_field) VALUES('value1', $1, $2) RETURNING id, field2",
+        to_sql!(Table.insert(field1 = "value1", field2 = new_field2, related_field = related_object)
+                .returning(id, field2))
+    );
+    assert_eq!(
+        "INSERT INTO RelatedTable(field1) VALUES('test') ON CONFLICT DO NOTHING RETURNING field1",
+        to_sql!(RelatedTable.insert(field1 = "test").on_conflict_ignore().returning(field1))
+    );
+}
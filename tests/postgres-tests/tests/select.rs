@@ -60,6 +60,58 @@ fn test_all() {
     );
 }
 
+#[test]
+fn test_count() {
+    assert_eq!(
+        "SELECT COUNT(*) FROM Table",
+        to_sql!(Table.count())
+    );
+    assert_eq!(
+        "SELECT COUNT(*) FROM Table WHERE Table.field2 > $1",
+        to_sql!(Table.filter(field2 > value).count())
+    );
+    assert_eq!(
+        "SELECT COUNT(*) FROM Table INNER JOIN RelatedTable ON Table.related_field = RelatedTable.id WHERE Table.field1 = 'test'",
+        to_sql!(Table.filter(field1 == "test").join(related_field).count())
+    );
+}
+
+#[test]
+fn test_exists() {
+    assert_eq!(
+        "SELECT EXISTS(SELECT 1 FROM Table)",
+        to_sql!(Table.exists())
+    );
+    assert_eq!(
+        "SELECT EXISTS(SELECT 1 FROM Table WHERE Table.field1 = $1)",
+        to_sql!(Table.filter(field1 == value).exists())
+    );
+}
+
+#[test]
+fn test_distinct() {
+    assert_eq!(
+        "SELECT DISTINCT Table.id, Table.field1, Table.field2, Table.field3, Table.date FROM Table",
+        to_sql!(Table.distinct())
+    );
+    assert_eq!(
+        "SELECT DISTINCT Table.id, Table.field1, Table.field2, Table.field3, Table.date FROM Table WHERE Table.field2 > 10 ORDER BY field1 LIMIT 5",
+        to_sql!(Table.filter(field2 > 10).distinct().sort(field1)[..5])
+    );
+}
+
+#[test]
+fn test_distinct_on() {
+    assert_eq!(
+        "SELECT DISTINCT ON (Table.field1) Table.id, Table.field1, Table.field2, Table.field3, Table.date FROM Table ORDER BY field1, date DESC",
+        to_sql!(Table.distinct_on(field1).sort(field1, -date))
+    );
+    assert_eq!(
+        "SELECT DISTINCT ON (Table.field1, Table.field2) Table.id, Table.field1, Table.field2, Table.field3, Table.date FROM Table ORDER BY field2, field1",
+        to_sql!(Table.distinct_on(field1, field2).sort(field2, field1))
+    );
+}
+
 #[test]
 fn test_filter() {
     assert_eq!(
@@ -172,6 +224,42 @@ fn test_filter_get() {
     );
 }
 
+#[test]
+fn test_filter_nullable() {
+    assert_eq!(
+        format!("{} FROM Table WHERE Table.field3 IS NULL", SELECT),
+        to_sql!(Table.filter(field3 == None))
+    );
+    assert_eq!(
+        format!("{} FROM Table WHERE Table.field3 IS NOT NULL AND Table.field1 = $1", SELECT),
+        to_sql!(Table.filter(field3 != None && field1 == value))
+    );
+    assert_eq!(
+        format!("{} FROM Table WHERE Table.field3 = 42", SELECT),
+        to_sql!(Table.filter(field3 == Some(42)))
+    );
+    assert_eq!(
+        format!("{} FROM Table WHERE Table.field3 IS NULL", SELECT),
+        to_sql!(Table.filter(None == field3))
+    );
+    assert_eq!(
+        format!("{} FROM Table WHERE Table.field3 IS NOT NULL AND Table.field3 > $1", SELECT),
+        to_sql!(Table.filter(None != field3 && Some(value) < field3))
+    );
+    assert_eq!(
+        format!("{} FROM Table WHERE Table.field1 = $1 AND Table.field3 = $2", SELECT),
+        to_sql!(Table.filter(field1 == value1 && field3 == Some(value2)))
+    );
+    assert_eq!(
+        format!("{} FROM Table WHERE Table.field1 = $1 AND field3 IS DISTINCT FROM $2", SELECT),
+        to_sql!(Table.filter(field1 == value1 && field3.is_distinct_from(value2)))
+    );
+    assert_eq!(
+        format!("{} FROM Table WHERE field3 IS NOT DISTINCT FROM 42", SELECT),
+        to_sql!(Table.filter(field3.is_not_distinct_from(Some(42))))
+    );
+}
+
 #[test]
 fn test_filter_sort() {
     assert_eq!(
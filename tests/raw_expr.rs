@@ -0,0 +1,78 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+use tql::PrimaryKey;
+use tql_macros::{sql, sql_raw};
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use connection::get_connection;
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+struct TableRawExpr {
+    id: PrimaryKey,
+    field1: String,
+    field2: i32,
+}
+
+#[test]
+fn test_raw() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableRawExpr.drop());
+    });
+
+    let _ = sql!(TableRawExpr.create());
+
+    let id1 = sql!(TableRawExpr.insert(field1 = "test", field2 = 55)).unwrap();
+    let id2 = sql!(TableRawExpr.insert(field1 = "testing", field2 = 12)).unwrap();
+    sql!(TableRawExpr.insert(field1 = "other", field2 = 42)).unwrap();
+
+    let tables = sql_raw!(connection, TableRawExpr,
+        concat!("SELECT ", tql_TableRawExpr_field_list!(), " FROM TableRawExpr ORDER BY field2 DESC")).unwrap();
+    assert_eq!(3, tables.len());
+    assert_eq!(id1, tables[0].id);
+    assert_eq!("test", tables[0].field1);
+    assert_eq!(55, tables[0].field2);
+    assert_eq!(12, tables[2].field2);
+
+    let pattern = "test%".to_string();
+    let value = 50;
+    let tables = sql_raw!(TableRawExpr,
+        concat!("SELECT ", tql_TableRawExpr_field_list!(),
+                " FROM TableRawExpr WHERE field1 LIKE $1 AND field2 < $2 ORDER BY id"),
+        pattern, value).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id2, tables[0].id);
+    assert_eq!("testing", tables[0].field1);
+}
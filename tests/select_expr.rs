@@ -284,6 +284,72 @@ fn test_select() {
     let_vec!(table1 = tables);
     assert_eq!(id4, table1.id);
 
+    let mut tables = sql!(TableSelectExpr.filter(optional_field == None)).unwrap();
+    assert_eq!(4, tables.len());
+    let_vec!(table1, table2, table3, table4 = tables);
+    assert_eq!(id1, table1.id);
+    assert_eq!(id2, table2.id);
+    assert_eq!(id3, table3.id);
+    assert_eq!(id5, table4.id);
+
+    let mut tables = sql!(TableSelectExpr.filter(optional_field != None)).unwrap();
+    assert_eq!(1, tables.len());
+    let_vec!(table1 = tables);
+    assert_eq!(id4, table1.id);
+
+    let value = 42;
+    let mut tables = sql!(TableSelectExpr.filter(optional_field == Some(value))).unwrap();
+    assert_eq!(1, tables.len());
+    let_vec!(table1 = tables);
+    assert_eq!(id4, table1.id);
+
+    let tables = sql!(TableSelectExpr.filter(optional_field == Some(24))).unwrap();
+    assert_eq!(0, tables.len());
+
+    let tables = sql!(TableSelectExpr.filter(None == optional_field)).unwrap();
+    assert_eq!(4, tables.len());
+
+    let mut tables = sql!(TableSelectExpr.filter(Some(value) == optional_field)).unwrap();
+    assert_eq!(1, tables.len());
+    let_vec!(table1 = tables);
+    assert_eq!(id4, table1.id);
+
+    let value: Option<i32> = None;
+    let tables = sql!(TableSelectExpr.filter(optional_field.is_distinct_from(value))).unwrap();
+    assert_eq!(1, tables.len());
+
+    let tables = sql!(TableSelectExpr.filter(optional_field.is_not_distinct_from(value))).unwrap();
+    assert_eq!(4, tables.len());
+
+    let count = sql!(TableSelectExpr.count()).unwrap();
+    assert_eq!(5, count);
+
+    let value = 42;
+    let count = sql!(TableSelectExpr.filter(field2 > value).count()).unwrap();
+    assert_eq!(2, count);
+
+    let count = sql!(TableSelectExpr.filter(field2 < 100).join(related_field).count()).unwrap();
+    assert_eq!(4, count);
+
+    let exists = sql!(TableSelectExpr.filter(field1 == "value3").exists()).unwrap();
+    assert!(exists);
+
+    let exists = sql!(TableSelectExpr.filter(field1 == "value6").exists()).unwrap();
+    assert!(!exists);
+
+    let tables = sql!(TableSelectExpr.filter(field2 < 100).distinct()).unwrap();
+    assert_eq!(4, tables.len());
+
+    #[cfg(feature = "postgres")]
+    {
+        // Get the row with the biggest field2 for each related_field.
+        let mut tables = sql!(TableSelectExpr.distinct_on(related_field).sort(related_field, -field2)).unwrap();
+        assert_eq!(2, tables.len());
+        let_vec!(table1, table2 = tables);
+        assert_eq!(id1, table1.id);
+        assert_eq!(id5, table2.id);
+    }
+
     let mut tables = sql!(TableSelectExpr.filter(datetime.year() == 2015)).unwrap();
     assert_eq!(1, tables.len());
     let_vec!(table1 = tables);
@@ -60,6 +60,18 @@ fn test_all() {
     );
 }
 
+#[test]
+fn test_distinct() {
+    assert_eq!(
+        "SELECT DISTINCT Table.id, Table.field1, Table.field2, Table.field3, Table.date FROM Table",
+        to_sql!(Table.distinct())
+    );
+    assert_eq!(
+        "SELECT DISTINCT Table.id, Table.field1, Table.field2, Table.field3, Table.date FROM Table WHERE Table.field2 > 10 ORDER BY field1 LIMIT 5",
+        to_sql!(Table.filter(field2 > 10).distinct().sort(field1)[..5])
+    );
+}
+
 #[test]
 fn test_filter() {
     assert_eq!(
@@ -23,11 +23,11 @@ extern crate compiletest_rs as compiletest;
 
 use std::path::PathBuf;
 
-fn run_mode(mode: &'static str) {
+fn run_mode(mode: &'static str, directory: &'static str) {
     let mut config = compiletest::Config::default();
 
     config.mode = mode.parse().expect("Invalid mode");
-    config.src_base = PathBuf::from(format!("tests/{}", mode));
+    config.src_base = PathBuf::from(format!("tests/{}", directory));
     #[cfg(feature = "sqlite")]
     {
         config.target_rustcflags = Some("--cfg feature=\"sqlite\"".to_string());
@@ -43,5 +43,8 @@ fn run_mode(mode: &'static str) {
 
 #[test]
 fn compile_test() {
-    run_mode("ui");
+    run_mode("ui", "ui");
+    // NOTE: the errors for the types not supported by SQLite are only reported with this backend.
+    #[cfg(feature = "sqlite")]
+    run_mode("ui", "ui-sqlite");
 }
@@ -45,13 +45,13 @@ struct Table {
 
 fn main() {
     let connection = get_connection();
-    if let Ok(aggregate) = sql!(Table.aggregate(average = avg(field2))) {
+    if let Ok(aggregate) = sql!(Table.aggregate(average = avg(i32_field))) {
         println!("{}", aggregate.averag);
-        //~^ ERROR no field `averag` on type `main::Aggregate`
+        //~^ ERROR no field `averag` on type `main::Aggregate<f64>`
         //~| did you mean `average`?
     }
 
-    if let Ok(aggregate) = sql!(Table.aggregate(average = avg(field2))) {
+    if let Ok(aggregate) = sql!(Table.aggregate(average = avg(i32_field))) {
         println!("{}", aggregate.average);
     }
 }
@@ -54,6 +54,17 @@ fn main() {
     //sql!(Table.values(i32_field).aggregate(average = avg(i32_field)).filter(avrage < 20));
     // TODO: propose similar names.
 
+    sql!(Table.aggregate(sum(i32_field * 2)));
+    //~^ ERROR an aggregate on an expression must be named
+    //~| HELP use a name like `result = sum(...)`
+
+    sql!(Table.aggregate(total = sum(i32_field.len())));
+    //~^ ERROR Expected field, related field or arithmetic expression
+
+    sql!(Table.values(field1).aggregate(total = sum(i32_field)).sort(-i32_field));
+    //~^ ERROR cannot sort on `i32_field` in an aggregate query
+    //~| HELP sort on a field from values() or on an aggregate result name
+
     if let Some(aggregate) = sql!(Table.aggregate(average = avg(field2))) {
         println!("{}", aggregate.averag);
     }
@@ -0,0 +1,62 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Tests of the sql_raw!() macro.
+
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+backend_extern_crate!();
+
+use tql::PrimaryKey;
+use tql_macros::sql_raw;
+
+use connection::{Connection, get_connection};
+
+#[derive(SqlTable)]
+struct Table {
+    id: PrimaryKey,
+    field1: String,
+    i32_field: i32,
+}
+
+fn main() {
+    let connection = get_connection();
+    let value = 42;
+
+    sql_raw!(Table, "SELECT * FROM Table WHERE i32_field = $1");
+    //~^ ERROR this query has 1 placeholder but 0 arguments were supplied
+
+    sql_raw!(Table, "SELECT * FROM Table WHERE i32_field = $1 AND id = $2", value);
+    //~^ ERROR this query has 2 placeholders but 1 argument was supplied
+
+    sql_raw!(Table, concat!("SELECT ", tql_Table_field_list!(), " FROM Table WHERE i32_field = $1"), value, value);
+    //~^ ERROR this query has 1 placeholder but 2 arguments were supplied
+
+    let query = "SELECT * FROM Table";
+    sql_raw!(Table, query);
+    //~^ ERROR Expected string literal or concat!()
+}
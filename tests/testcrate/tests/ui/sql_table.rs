@@ -35,10 +35,6 @@ struct Table<'a> {
     //~^ WARNING No primary key found
     string: &'a str,
     //~^ ERROR use of unsupported type name `& 'a str`
-    connection: Connection,
-    //~^ ERROR use of unsupported type name `Connection`
-    connection2: Option<Connection>,
-    //~^ ERROR use of unsupported type name `Connection`
     nested_options: Option<Option<String>>,
     //~^ ERROR use of unsupported type name `Option<String>`
     datetime: DateTime,
@@ -51,6 +47,6 @@ struct Table<'a> {
     //~^ ERROR use of unsupported type name `Option`
     vector: Vec,
     //~^ ERROR use of unsupported type name `Vec`
-    vector_i32: Vec<i32>,
-    //~^ ERROR use of unsupported type name `Vec<i32>`
+    vector_connection: Vec<Connection>,
+    //~^ ERROR use of unsupported type name `Vec<Connection>`
 }
@@ -0,0 +1,76 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Tests of the `#[tql]` attributes of the fields.
+
+#![feature(proc_macro)]
+
+#[macro_use]
+extern crate tql_macros;
+
+#[derive(SqlTable)]
+struct Table {
+    //~^ WARNING More than one primary key is currently not supported
+    //~| ERROR More than one version field found
+    id: PrimaryKey,
+    #[tql(version)]
+    version: String,
+    //~^ ERROR the version field must be an integer (i16, i32 or i64)
+    #[tql(version)]
+    version2: i64,
+    #[tql(unknown)]
+    //~^ ERROR unknown tql attribute
+    field: i32,
+    #[tql(auto_now)]
+    date: i32,
+    //~^ ERROR the auto_now and auto_now_add fields must have a date or time type
+    #[tql(soft_delete)]
+    deleted: i32,
+    //~^ ERROR the soft_delete field must have an optional date or time type
+    #[tql(max_length = 10)]
+    length: i32,
+    //~^ ERROR the max_length and regex fields must have a string type
+    #[tql(regex = "(")]
+    //~^ ERROR invalid regex
+    name: String,
+    #[tql(range(min = 10, max = 1))]
+    //~^ ERROR the minimum of the range is greater than its maximum
+    number: i32,
+    #[tql(range(min = 0))]
+    text: String,
+    //~^ ERROR the range field must have a numeric type
+    #[tql(primary_key)]
+    key: i32,
+    //~^ ERROR the primary_key field must have the type Uuid (use PrimaryKey for an integer)
+    #[tql(gen_random_uuid)]
+    //~^ ERROR the gen_random_uuid attribute requires the primary_key attribute
+    token: String,
+    #[tql(numeric(precision = 10, scale = 2))]
+    amount: f64,
+    //~^ ERROR the numeric field must have the type Decimal
+    #[tql(numeric(precision = 2, scale = 4))]
+    //~^ ERROR the scale of the numeric field is greater than its precision
+    total: f64,
+    //~^ ERROR the numeric field must have the type Decimal
+    #[tql(regex = "\\p{L}+")]
+    //~^ ERROR unsupported syntax in regex
+    word: String,
+}
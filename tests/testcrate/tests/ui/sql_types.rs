@@ -0,0 +1,59 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Tests of the `#[derive(SqlEnum)]` and `#[derive(SqlType)]` attributes.
+
+#![feature(proc_macro)]
+
+#[macro_use]
+extern crate tql_macros;
+
+#[derive(SqlEnum)]
+enum Empty {
+    //~^ ERROR SqlEnum cannot be derived for an enum without variants
+}
+
+#[derive(SqlEnum)]
+enum Generic<T> {
+    //~^ ERROR SqlEnum cannot be derived for a generic enum
+    Value,
+    Field(T),
+    //~^ ERROR the variants of a SqlEnum must not have fields
+    Struct { field: i32 },
+    //~^ ERROR the variants of a SqlEnum must not have fields
+}
+
+#[derive(SqlType)]
+struct Named {
+    //~^ ERROR SqlType can only be derived for a struct with one unnamed field
+    field: String,
+}
+
+#[derive(SqlType)]
+struct Pair(i32, i32);
+//~^ ERROR SqlType can only be derived for a struct with one unnamed field
+
+#[derive(SqlType)]
+struct Nullable(Option<String>);
+//~^ ERROR the field of a SqlType must have a type supported by tql
+
+fn main() {
+}
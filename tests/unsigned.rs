@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use tql::PrimaryKey;
+use tql_macros::sql;
+
+use connection::get_connection;
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+struct TableUnsigned {
+    id: PrimaryKey,
+    small: u8,
+    medium: u16,
+    hits: u32,
+    big: Option<u64>,
+}
+
+#[test]
+fn test_unsigned() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUnsigned.drop());
+    });
+
+    let _ = sql!(TableUnsigned.create());
+
+    let id1 = sql!(TableUnsigned.insert(small = 255, medium = 65535u16, hits = 4_000_000_000u32, big = None))
+        .unwrap();
+    let big = 9_000_000_000_000_000_000u64;
+    let hits = 42;
+    let id2 = sql!(TableUnsigned.insert(small = 1, medium = 2, hits = hits, big = Some(big))).unwrap();
+
+    let table = sql!(TableUnsigned.get(id1)).unwrap();
+    assert_eq!(255, table.small);
+    assert_eq!(65535, table.medium);
+    assert_eq!(4_000_000_000, table.hits);
+    assert_eq!(None, table.big);
+
+    let table = sql!(TableUnsigned.get(id2)).unwrap();
+    assert_eq!(42, table.hits);
+    assert_eq!(Some(big), table.big);
+
+    let minimum = 3_000_000_000u32;
+    let tables = sql!(TableUnsigned.filter(hits > minimum)).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let tables = sql!(TableUnsigned.filter(small == 255u8)).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id1, tables[0].id);
+
+    let hits = 3_500_000_000u32;
+    let _ = sql!(TableUnsigned.get(id2).update(hits = hits));
+    let table = sql!(TableUnsigned.get(id2)).unwrap();
+    assert_eq!(3_500_000_000, table.hits);
+
+    let values = sql!(TableUnsigned.get(id2).update(medium = 3).returning(hits, big)).unwrap();
+    assert_eq!(vec![(3_500_000_000, Some(big))], values);
+
+    let aggregate = sql!(TableUnsigned.aggregate(max(hits), min(hits), max(big), min(small))).unwrap();
+    assert_eq!(Some(4_000_000_000), aggregate.hits_max);
+    assert_eq!(Some(3_500_000_000), aggregate.hits_min);
+    assert_eq!(Some(big), aggregate.big_max);
+    assert_eq!(Some(1), aggregate.small_min);
+
+    let aggregate = sql!(TableUnsigned.aggregate(sum(hits))).unwrap();
+    let total: Option<i64> = aggregate.hits_sum;
+    assert_eq!(Some(7_500_000_000), total);
+
+    let mut table = TableUnsigned {
+        id: 0,
+        small: 3,
+        medium: 4,
+        hits: 5,
+        big: Some(6),
+    };
+    let id3 = table.insert(&connection).unwrap();
+    table.hits = 3_000_000_001;
+    table.save(&connection).unwrap();
+    let table = sql!(TableUnsigned.get(id3)).unwrap();
+    assert_eq!(3_000_000_001, table.hits);
+    assert_eq!(Some(6), table.big);
+
+    let aggregate = sql!(TableUnsigned.aggregate(sum(big))).unwrap();
+    let total: Option<i64> = aggregate.big_sum;
+    assert_eq!(Some(9_000_000_000_000_000_006), total);
+
+    let values = sql!(TableUnsigned.filter(small == 255u8).delete().returning(hits)).unwrap();
+    assert_eq!(vec![4_000_000_000], values);
+
+    // NOTE: a u64 is stored in a BIGINT, so the values greater than i64::MAX are rejected.
+    let big = u64::max_value();
+    assert!(sql!(TableUnsigned.insert(small = 1, medium = 2, hits = 3, big = Some(big))).is_err());
+
+    // NOTE: the sum of a u64 field is an i64, so the query fails when it overflows.
+    let big = 1_000_000_000_000_000_000u64;
+    let _ = sql!(TableUnsigned.insert(small = 1, medium = 2, hits = 3, big = Some(big))).unwrap();
+    assert!(sql!(TableUnsigned.aggregate(sum(big))).is_err());
+}
@@ -89,3 +89,15 @@ fn test_update_operation() {
         to_sql!(Table.get(1).update(field2 += 10, field3 /= 3))
     );
 }
+
+#[test]
+fn test_update_returning() {
+    assert_eq!(
+        "UPDATE Table SET field1 = 'value1' WHERE Table.id = 1 RETURNING id, field1, field2, field3",
+        to_sql!(Table.get(1).update(field1 = "value1").returning())
+    );
+    assert_eq!(
+        "UPDATE Table SET field2 = field2 + 1 WHERE Table.field1 = 'value1' RETURNING id, field2",
+        to_sql!(Table.filter(field1 == "value1").update(field2 += 1).returning(id, field2))
+    );
+}
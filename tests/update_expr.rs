@@ -21,8 +21,10 @@
 
 #![feature(proc_macro)]
 
+extern crate chrono;
 #[macro_use]
 extern crate lazy_static;
+#[macro_use(set)]
 extern crate tql;
 #[macro_use]
 extern crate tql_macros;
@@ -35,7 +37,8 @@ backend_extern_crate!();
 
 use std::sync::Mutex;
 
-use tql::{ForeignKey, PrimaryKey};
+use chrono::NaiveDateTime;
+use tql::{ForeignKey, PrimaryKey, SaveError, Tracked};
 use tql_macros::sql;
 
 use connection::{Connection, get_connection};
@@ -58,6 +61,44 @@ struct RelatedTable {
     field1: String,
 }
 
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableUpdateInstanceExpr {
+    id: PrimaryKey,
+    field1: String,
+    field2: i32,
+    optional_field: Option<i32>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableUpdateAutomaticExpr {
+    id: PrimaryKey,
+    field1: String,
+    #[tql(auto_now_add)]
+    date_added: NaiveDateTime,
+    #[tql(auto_now)]
+    date_modified: Option<NaiveDateTime>,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableUpdateTrackedExpr {
+    id: PrimaryKey,
+    field1: String,
+    field2: i32,
+    field3: i32,
+}
+
+#[derive(SqlTable)]
+#[allow(dead_code)]
+struct TableUpdateVersionExpr {
+    id: PrimaryKey,
+    field1: String,
+    #[tql(version)]
+    version: i32,
+}
+
 lazy_static! {
     static ref LOCK: Mutex<Connection> = Mutex::new(get_connection());
 }
@@ -193,3 +234,249 @@ fn test_update_operation() {
     assert_eq!(0, table.field2);
     assert_eq!(3, table.field3);
 }
+
+#[test]
+fn test_update_returning() {
+    let connection = LOCK.lock().unwrap();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUpdateExpr.drop());
+        let _ = sql!(RelatedTable.drop());
+    });
+
+    let _ = sql!(RelatedTable.create());
+    let _ = sql!(TableUpdateExpr.create());
+
+    let id = sql!(RelatedTable.insert(field1 = "")).unwrap();
+    let related_field = sql!(RelatedTable.get(id)).unwrap();
+
+    let id = sql!(TableUpdateExpr.insert(field1 = "", field2 = 0, field3 = 1, related_field = related_field)).unwrap();
+
+    let tables = sql!(TableUpdateExpr.get(id).update(field1 = "value1", field2 += 10).returning()).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id, tables[0].id);
+    assert_eq!("value1", tables[0].field1);
+    assert_eq!(10, tables[0].field2);
+    assert_eq!(1, tables[0].field3);
+    assert!(tables[0].related_field.is_none());
+
+    let values = sql!(TableUpdateExpr.filter(field1 == "value1").update(field3 *= 3).returning(id, field3)).unwrap();
+    assert_eq!(vec![(id, 3)], values);
+
+    let values = sql!(TableUpdateExpr.filter(field1 == "other").update(field3 = 0).returning(field3)).unwrap();
+    assert!(values.is_empty());
+}
+
+#[test]
+fn test_update_instance() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUpdateInstanceExpr.drop());
+    });
+
+    let _ = sql!(TableUpdateInstanceExpr.create());
+
+    let id1 = sql!(TableUpdateInstanceExpr.insert(field1 = "value1", field2 = 1)).unwrap();
+    let id2 = sql!(TableUpdateInstanceExpr.insert(field1 = "value2", field2 = 2)).unwrap();
+
+    let mut table = sql!(TableUpdateInstanceExpr.get(id1)).unwrap();
+    table.field1 = "new value".to_string();
+    table.optional_field = Some(42);
+    let num_updated = table.save(&connection).unwrap();
+    assert_eq!(1, num_updated);
+
+    let table = sql!(TableUpdateInstanceExpr.get(id1)).unwrap();
+    assert_eq!("new value", table.field1);
+    assert_eq!(1, table.field2);
+    assert_eq!(Some(42), table.optional_field);
+
+    let table = sql!(TableUpdateInstanceExpr.get(id2)).unwrap();
+    assert_eq!("value2", table.field1);
+
+    let mut table2 = sql!(TableUpdateInstanceExpr.get(id1)).unwrap();
+    let _ = sql!(TableUpdateInstanceExpr.get(id1).update(field2 = 10, optional_field = None));
+    table2.reload(&connection).unwrap();
+    assert_eq!(id1, table2.id);
+    assert_eq!("new value", table2.field1);
+    assert_eq!(10, table2.field2);
+    assert_eq!(None, table2.optional_field);
+}
+
+#[test]
+fn test_update_instance_foreign_key() {
+    let connection = LOCK.lock().unwrap();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUpdateExpr.drop());
+        let _ = sql!(RelatedTable.drop());
+    });
+
+    let _ = sql!(RelatedTable.create());
+    let _ = sql!(TableUpdateExpr.create());
+
+    let related_id1 = sql!(RelatedTable.insert(field1 = "related1")).unwrap();
+    let related_field = sql!(RelatedTable.get(related_id1)).unwrap();
+    let id = sql!(TableUpdateExpr.insert(field1 = "value1", field2 = 1, field3 = 2, related_field = related_field))
+        .unwrap();
+
+    // NOTE: the foreign key is None without a join, so it is not updated.
+    let mut table = sql!(TableUpdateExpr.get(id)).unwrap();
+    assert!(table.related_field.is_none());
+    table.field1 = "value2".to_string();
+    assert_eq!(1, table.save(&*connection).unwrap());
+
+    let table = sql!(TableUpdateExpr.get(id).join(related_field)).unwrap();
+    assert_eq!("value2", table.field1);
+    assert_eq!(related_id1, table.related_field.expect("related field").id);
+
+    let related_id2 = sql!(RelatedTable.insert(field1 = "related2")).unwrap();
+    let mut table = sql!(TableUpdateExpr.get(id)).unwrap();
+    table.related_field = Some(sql!(RelatedTable.get(related_id2)).unwrap());
+    assert_eq!(1, table.save(&*connection).unwrap());
+
+    let _ = sql!(TableUpdateExpr.get(id).update(field2 = 10));
+    table.reload(&*connection).unwrap();
+    assert_eq!(10, table.field2);
+    assert_eq!(related_id2, table.related_field.as_ref().expect("related field").id);
+
+    let table = sql!(TableUpdateExpr.get(id).join(related_field)).unwrap();
+    assert_eq!("value2", table.field1);
+    assert_eq!(related_id2, table.related_field.expect("related field").id);
+}
+
+#[test]
+fn test_update_automatic() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUpdateAutomaticExpr.drop());
+    });
+
+    let _ = sql!(TableUpdateAutomaticExpr.create());
+
+    let id = sql!(TableUpdateAutomaticExpr.insert(field1 = "value1")).unwrap();
+    let table = sql!(TableUpdateAutomaticExpr.get(id)).unwrap();
+    let date_added = table.date_added;
+    assert_eq!(Some(date_added), table.date_modified);
+
+    let _ = sql!(TableUpdateAutomaticExpr.get(id).update(field1 = "value2"));
+    let mut table = sql!(TableUpdateAutomaticExpr.get(id)).unwrap();
+    assert_eq!("value2", table.field1);
+    assert_eq!(date_added, table.date_added);
+    assert!(table.date_modified >= Some(date_added));
+
+    let date = NaiveDateTime::from_timestamp(0, 0);
+    table.field1 = "value3".to_string();
+    table.date_added = date;
+    table.date_modified = None;
+    table.save(&connection).unwrap();
+    let table = sql!(TableUpdateAutomaticExpr.get(id)).unwrap();
+    assert_eq!("value3", table.field1);
+    assert_eq!(date_added, table.date_added);
+    assert!(table.date_modified >= Some(date_added));
+
+    let mut tracked = Tracked::new(table);
+    set!(tracked.field1 = "value4".to_string());
+    set!(tracked.date_modified = None);
+    tracked.save(&connection).unwrap();
+    let table = sql!(TableUpdateAutomaticExpr.get(id)).unwrap();
+    assert_eq!("value4", table.field1);
+    assert!(table.date_modified >= Some(date_added));
+}
+
+#[test]
+fn test_update_tracked() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUpdateTrackedExpr.drop());
+    });
+
+    let _ = sql!(TableUpdateTrackedExpr.create());
+
+    let id = sql!(TableUpdateTrackedExpr.insert(field1 = "value1", field2 = 1, field3 = 2)).unwrap();
+
+    let mut tracked = Tracked::new(sql!(TableUpdateTrackedExpr.get(id)).unwrap());
+    assert_eq!(0, tracked.save(&connection).unwrap());
+
+    // Another update of a column which is not changed by the tracked instance.
+    let _ = sql!(TableUpdateTrackedExpr.get(id).update(field3 = 30));
+
+    set!(tracked.field1 = "new value".to_string());
+    set!(tracked.field2 = tracked.field2 + 10);
+    assert_eq!(&["field1", "field2"], tracked.changed_fields());
+    assert_eq!("new value", tracked.field1);
+    assert_eq!(1, tracked.save(&connection).unwrap());
+    assert!(tracked.changed_fields().is_empty());
+
+    let table = sql!(TableUpdateTrackedExpr.get(id)).unwrap();
+    assert_eq!("new value", table.field1);
+    assert_eq!(11, table.field2);
+    assert_eq!(30, table.field3);
+}
+
+#[test]
+fn test_update_version() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUpdateVersionExpr.drop());
+    });
+
+    let _ = sql!(TableUpdateVersionExpr.create());
+
+    let id = sql!(TableUpdateVersionExpr.insert(field1 = "value1", version = 1)).unwrap();
+
+    let mut table1 = sql!(TableUpdateVersionExpr.get(id)).unwrap();
+    let mut table2 = sql!(TableUpdateVersionExpr.get(id)).unwrap();
+
+    table1.field1 = "value2".to_string();
+    table1.save(&connection).unwrap();
+    assert_eq!(2, table1.version);
+
+    table2.field1 = "value3".to_string();
+    match table2.save(&connection) {
+        Err(SaveError::StaleObject) => (),
+        result => panic!("Expected StaleObject error, but got {:?}", result),
+    }
+    assert_eq!(1, table2.version);
+
+    let table = sql!(TableUpdateVersionExpr.get(id)).unwrap();
+    assert_eq!("value2", table.field1);
+    assert_eq!(2, table.version);
+
+    let mut tracked = Tracked::new(table);
+    set!(tracked.field1 = "value4".to_string());
+    assert_eq!(1, tracked.save(&connection).unwrap());
+    assert_eq!(3, tracked.version);
+
+    let mut tracked2 = Tracked::new(table2);
+    set!(tracked2.field1 = "value5".to_string());
+    match tracked2.save(&connection) {
+        Err(SaveError::StaleObject) => (),
+        result => panic!("Expected StaleObject error, but got {:?}", result),
+    }
+
+    let mut table = sql!(TableUpdateVersionExpr.get(id)).unwrap();
+    assert_eq!("value4", table.field1);
+    assert_eq!(3, table.version);
+
+    // The update queries also increment the version.
+    assert_eq!(1, sql!(TableUpdateVersionExpr.get(id).update(field1 = "value6")).unwrap());
+    table.field1 = "value7".to_string();
+    match table.save(&connection) {
+        Err(SaveError::StaleObject) => (),
+        result => panic!("Expected StaleObject error, but got {:?}", result),
+    }
+
+    let row_id = id;
+    let old_version = 3;
+    let count = sql!(TableUpdateVersionExpr.filter(id == row_id && version == old_version).update(field1 = "value8"))
+        .unwrap();
+    assert_eq!(0, count);
+
+    let table = sql!(TableUpdateVersionExpr.get(id)).unwrap();
+    assert_eq!("value6", table.field1);
+    assert_eq!(4, table.version);
+}
@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![cfg(all(any(feature = "postgres", feature = "rusqlite"), feature = "uuid"))]
+#![feature(proc_macro)]
+
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+extern crate uuid;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use tql::ForeignKey;
+use tql_macros::sql;
+use uuid::Uuid;
+
+use connection::{get_connection, is_not_found};
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+struct TableUuid {
+    #[tql(primary_key, gen_random_uuid)]
+    id: Uuid,
+    field1: String,
+}
+
+#[derive(SqlTable)]
+struct TableUuidAssigned {
+    #[tql(primary_key)]
+    id: Uuid,
+    token: Option<Uuid>,
+    related: ForeignKey<TableUuid>,
+}
+
+#[test]
+fn test_uuid() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableUuidAssigned.drop());
+        let _ = sql!(TableUuid.drop());
+    });
+
+    let _ = sql!(TableUuid.create());
+    let _ = sql!(TableUuidAssigned.create());
+
+    let id1: Uuid = sql!(TableUuid.insert(field1 = "value1")).unwrap();
+    let id2: Uuid = sql!(TableUuid.insert(field1 = "value2")).unwrap();
+    assert_ne!(id1, id2);
+    // The generated primary keys are version 4 UUIDs.
+    for id in &[id1, id2] {
+        assert_eq!(4, id.get_version_num());
+        assert_eq!(0x80, id.as_bytes()[8] & 0xC0);
+    }
+
+    let table = sql!(TableUuid.get(id1)).unwrap();
+    assert_eq!(id1, table.id);
+    assert_eq!("value1", table.field1);
+
+    let mut table = TableUuid {
+        id: Uuid::nil(),
+        field1: "value3".to_string(),
+    };
+    let id3 = table.insert(&connection).unwrap();
+    assert_eq!(id3, table.id);
+    assert_ne!(Uuid::nil(), table.id);
+
+    let id = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+    let related = sql!(TableUuid.get(id2)).unwrap();
+    let inserted_id = sql!(TableUuidAssigned.insert(id = id, token = None, related = related)).unwrap();
+    assert_eq!(id, inserted_id);
+
+    let token = Some(id1);
+    let _ = sql!(TableUuidAssigned.get(id).update(token = token));
+    let table = sql!(TableUuidAssigned.get(id).join(related)).unwrap();
+    assert_eq!(Some(id1), table.token);
+    assert_eq!(id2, table.related.unwrap().id);
+
+    let tables = sql!(TableUuidAssigned.filter(token.is_some())).unwrap();
+    assert_eq!(1, tables.len());
+    assert_eq!(id, tables[0].id);
+
+    let mut table = sql!(TableUuid.get(id3)).unwrap();
+    table.field1 = "value4".to_string();
+    table.save(&connection).unwrap();
+    table.field1 = String::new();
+    table.reload(&connection).unwrap();
+    assert_eq!("value4", table.field1);
+
+    let rows = vec![
+        TableUuid { id: Uuid::nil(), field1: "value5".to_string() },
+        TableUuid { id: Uuid::nil(), field1: "value6".to_string() },
+    ];
+    let ids = sql!(TableUuid.insert_many(rows)).unwrap();
+    assert_eq!(2, ids.len());
+    let table = sql!(TableUuid.get(ids[1])).unwrap();
+    assert_eq!("value6", table.field1);
+
+    let num_deleted = table.delete(&connection).unwrap();
+    assert_eq!(1, num_deleted);
+    assert!(is_not_found(sql!(TableUuid.get(ids[1]))));
+}
@@ -0,0 +1,147 @@
+/*
+ * Copyright (c) 2017-2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+#![feature(proc_macro)]
+
+#[macro_use(set)]
+extern crate tql;
+#[macro_use]
+extern crate tql_macros;
+
+#[macro_use]
+mod connection;
+mod teardown;
+
+backend_extern_crate!();
+
+use tql::{PrimaryKey, SaveError, Tracked, ValidationError, ValidationErrorKind};
+use tql_macros::sql;
+
+use connection::get_connection;
+use teardown::TearDown;
+
+#[derive(SqlTable)]
+struct TableValidation {
+    id: PrimaryKey,
+    #[tql(max_length = 5, regex = "^[a-z]+$")]
+    name: String,
+    #[tql(range(min = 0, max = 100))]
+    age: i32,
+    #[tql(range(min = -1.5))]
+    ratio: Option<f64>,
+}
+
+#[test]
+fn test_validation() {
+    let connection = get_connection();
+
+    let _teardown = TearDown::new(|| {
+        let _ = sql!(TableValidation.drop());
+    });
+
+    let _ = sql!(TableValidation.create());
+
+    let mut table = TableValidation {
+        id: 0,
+        name: "value".to_string(),
+        age: 42,
+        ratio: None,
+    };
+    assert_eq!(Ok(()), table.validate());
+    let id = table.insert(&connection).unwrap();
+
+    let mut table = TableValidation {
+        id: 0,
+        name: "Long value".to_string(),
+        age: 142,
+        ratio: Some(-2.5),
+    };
+    match table.insert(&connection) {
+        Err(SaveError::Validation(errors)) => assert_eq!(vec![
+            ValidationError {
+                field: "name",
+                kind: ValidationErrorKind::TooLong { max_length: 5 },
+            },
+            ValidationError {
+                field: "name",
+                kind: ValidationErrorKind::NoMatch { regex: "^[a-z]+$" },
+            },
+            ValidationError {
+                field: "age",
+                kind: ValidationErrorKind::OutOfRange { min: Some(0.0), max: Some(100.0) },
+            },
+            ValidationError {
+                field: "ratio",
+                kind: ValidationErrorKind::OutOfRange { min: Some(-1.5), max: None },
+            },
+        ], errors),
+        result => panic!("Expected Validation error, but got {:?}", result),
+    }
+    assert_eq!(1, sql!(TableValidation.count()).unwrap());
+
+    let mut table = sql!(TableValidation.get(id)).unwrap();
+    table.age = -1;
+    match table.save(&connection) {
+        Err(SaveError::Validation(ref errors)) if errors.len() == 1 => assert_eq!("age", errors[0].field),
+        result => panic!("Expected Validation error, but got {:?}", result),
+    }
+
+    let mut tracked = Tracked::new(sql!(TableValidation.get(id)).unwrap());
+    set!(tracked.name = "other".to_string());
+    set!(tracked.ratio = Some(-3.0));
+    match tracked.save(&connection) {
+        Err(SaveError::Validation(ref errors)) if errors.len() == 1 => assert_eq!("ratio", errors[0].field),
+        result => panic!("Expected Validation error, but got {:?}", result),
+    }
+
+    // The values which are not literals are checked at runtime.
+    let name = "long value".to_string();
+    let error = sql!(TableValidation.insert(name = name, age = 42)).unwrap_err();
+    assert!(error.to_string().contains("name has more than 5 characters"));
+    let name = "Value".to_string();
+    let error = sql!(TableValidation.insert(name = name, age = 42)).unwrap_err();
+    assert!(error.to_string().contains("name does not match the regex `^[a-z]+$`"));
+    let age = 142;
+    let error = sql!(TableValidation.get(id).update(age = age)).unwrap_err();
+    assert!(error.to_string().contains("age is not between 0 and 100"));
+    let ratio = -2.5;
+    let error = sql!(TableValidation.get(id).update(ratio = Some(ratio))).unwrap_err();
+    assert!(error.to_string().contains("ratio is less than -1.5"));
+    let ratio = Some(-2.5);
+    let error = sql!(TableValidation.get(id).update(ratio = ratio)).unwrap_err();
+    assert!(error.to_string().contains("ratio is less than -1.5"));
+
+    let rows = vec![
+        ("other".to_string(), 10, None),
+        ("invalid value".to_string(), 10, Some(1.0)),
+    ];
+    let error = sql!(TableValidation.insert_many(rows)).unwrap_err();
+    assert!(error.to_string().contains("name has more than 5 characters"));
+    assert_eq!(1, sql!(TableValidation.count()).unwrap());
+
+    let ratio = Some(0.5);
+    assert_eq!(1, sql!(TableValidation.get(id).update(ratio = ratio)).unwrap());
+
+    let table = sql!(TableValidation.get(id)).unwrap();
+    assert_eq!("value", table.name);
+    assert_eq!(42, table.age);
+    assert_eq!(Some(0.5), table.ratio);
+}
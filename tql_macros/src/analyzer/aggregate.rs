@@ -25,8 +25,12 @@ use proc_macro2::Span;
 use syn::{
     BinOp,
     Expr,
+    ExprField,
+    ExprLit,
     ExprUnary,
     Ident,
+    Lit,
+    Member,
     UnOp,
 };
 use syn::spanned::Spanned;
@@ -37,8 +41,10 @@ use ast::{
     AggregateFilterExpression,
     AggregateFilters,
     Expression,
+    Order,
     Query,
     WithSpan,
+    aggregate_fields,
     first_token_span,
 };
 use error::{Error, Result, res};
@@ -50,7 +56,13 @@ use super::{
     path_expr_to_string,
     propose_similar_name,
 };
-use super::filter::{binop_to_logical_operator, binop_to_relational_operator, is_logical_operator, is_relational_operator};
+use super::filter::{
+    binop_to_logical_operator,
+    binop_to_relational_operator,
+    is_arithmetic_operator,
+    is_logical_operator,
+    is_relational_operator,
+};
 
 /// Convert an `Expression` to an `Aggregate`.
 pub fn argument_to_aggregate(arg: &Expression) -> Result<Aggregate> {
@@ -63,8 +75,8 @@ pub fn argument_to_aggregate(arg: &Expression) -> Result<Aggregate> {
     if let Expr::Call(ref call) = *call {
         if let Some(identifier) = path_expr_to_string(&call.func, &mut errors) {
             aggregate.function = identifier.to_string();
-            if let Some(sql_function) = aggregates.get(&identifier) {
-                aggregate.sql_function = sql_function.clone();
+            if let Some(template) = aggregates.get(&identifier) {
+                aggregate.template = template.clone();
             }
             else {
                 let mut error = Error::new_with_code(
@@ -80,26 +92,32 @@ pub fn argument_to_aggregate(arg: &Expression) -> Result<Aggregate> {
         }
 
         if check_argument_count(&call.args, 1, arg.span(), &mut errors) {
-            if let Expr::Path(ref path) = **call.args.first().expect("first argument").value() {
-                let path_ident = path.path.segments.first().unwrap().into_value().ident;
-                aggregate.field = Some(path_ident);
+            let argument = *call.args.first().expect("first argument").value();
+            let error_count = errors.len();
+            check_aggregate_argument(argument, &mut errors);
+            aggregate.argument = Some(argument.clone());
+            let valid_argument = errors.len() == error_count;
 
-                if aggregate.result_name.is_none() {
-                    let result_name = aggregate.field.expect("Aggregate identifier").to_string() + "_" +
-                        &aggregate.sql_function.to_lowercase();
-                    let mut ident = new_ident(&result_name);
+            if aggregate.result_name.is_some() {
+                aggregate.has_name_in_query = true;
+            }
+            else if valid_argument {
+                if let Some((name, span)) = argument_name(argument) {
+                    let mut ident = new_ident(&(name + "_" + &aggregate.function));
                     // NOTE: violate the hygiene by assigning a known context to this new
                     // identifier.
-                    ident.set_span(path_ident.span());
+                    ident.set_span(span);
                     aggregate.result_name = Some(ident);
                 }
                 else {
-                    aggregate.has_name_in_query = true;
+                    let mut error = Error::new(
+                        "an aggregate on an expression must be named",
+                        argument.span(),
+                    );
+                    error.add_help(&format!("use a name like `result = {}(...)`", aggregate.function));
+                    errors.push(error);
                 }
             }
-            else {
-                // TODO: allow expressions (field2 / field1).
-            }
         }
     }
     else {
@@ -112,6 +130,95 @@ pub fn argument_to_aggregate(arg: &Expression) -> Result<Aggregate> {
     res(aggregate, errors)
 }
 
+/// Get the default name of an aggregate `argument`: `field` or `related_field_field`.
+fn argument_name(argument: &Expression) -> Option<(String, Span)> {
+    match *argument {
+        Expr::Field(ExprField { ref base, member: Member::Named(member), .. }) =>
+            field_base_ident(base).map(|base| (format!("{}_{}", base, member), member.span())),
+        Expr::Path(ref path) => {
+            let path_ident = path.path.segments.first().unwrap().into_value().ident;
+            Some((path_ident.to_string(), path_ident.span()))
+        },
+        _ => None,
+    }
+}
+
+/// Check that the aggregate `argument` only contains fields, fields of joined tables, number
+/// literals and arithmetic operators.
+fn check_aggregate_argument(argument: &Expression, errors: &mut Vec<Error>) {
+    match *argument {
+        Expr::Binary(ref bin) if is_arithmetic_operator(&bin.op) => {
+            check_aggregate_argument(&bin.left, errors);
+            check_aggregate_argument(&bin.right, errors);
+        },
+        Expr::Field(ExprField { ref base, member: Member::Named(_), .. }) if field_base_ident(base).is_some() => (),
+        Expr::Lit(ExprLit { lit: Lit::Float(_), .. }) | Expr::Lit(ExprLit { lit: Lit::Int(_), .. }) => (),
+        Expr::Paren(ref paren) => check_aggregate_argument(&paren.expr, errors),
+        Expr::Path(ref path) if path.qself.is_none() && path.path.segments.len() == 1 => (),
+        _ => errors.push(Error::new(
+            "Expected field, related field or arithmetic expression", // TODO: improve this message.
+            argument.span(),
+        )),
+    }
+}
+
+/// Check that the fields of the joined tables used in the aggregates are joined.
+pub fn check_aggregate_joins(query: &Query, errors: &mut Vec<Error>) {
+    if let Query::Aggregate { ref aggregates, ref joins, .. } = *query {
+        let arguments = aggregates.iter()
+            .filter_map(|aggregate| aggregate.argument.as_ref());
+        for argument in arguments {
+            for field in aggregate_fields(argument) {
+                if let Expr::Field(ExprField { ref base, .. }) = *field {
+                    if let Some(base) = field_base_ident(base) {
+                        if !joins.iter().any(|join| join.base_field == base) {
+                            let mut error = Error::new(
+                                &format!("cannot aggregate on a field of `{}` without joining it", base),
+                                field.span(),
+                            );
+                            error.add_help(&format!("add `.join({})` to the query", base));
+                            errors.push(error);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Check that the fields used in the sort() of an aggregate query are either groups or aggregate
+/// result names.
+pub fn check_aggregate_order(query: &Query, errors: &mut Vec<Error>) {
+    if let Query::Aggregate { ref aggregates, ref groups, ref order, .. } = *query {
+        for order in order {
+            let identifier =
+                match *order {
+                    Order::Ascending(ref identifier) | Order::Descending(ref identifier) => identifier,
+                    Order::NoOrder => continue,
+                };
+            let is_aggregate = aggregates.iter().any(|aggregate| aggregate.result_name.as_ref() == Some(identifier));
+            if !is_aggregate && !groups.contains(identifier) {
+                let mut error = Error::new(
+                    &format!("cannot sort on `{}` in an aggregate query", identifier),
+                    identifier.span(),
+                );
+                error.add_help("sort on a field from values() or on an aggregate result name");
+                errors.push(error);
+            }
+        }
+    }
+}
+
+/// Get the identifier of the related field in `related_field.field`.
+fn field_base_ident(base: &Expression) -> Option<Ident> {
+    if let Expr::Path(ref path) = *base {
+        if path.qself.is_none() && path.path.segments.len() == 1 {
+            return Some(path.path.segments.first().unwrap().into_value().ident);
+        }
+    }
+    None
+}
+
 /// Convert an `Expression` to a group `Ident`.
 pub fn argument_to_group(arg: &Expression) -> Result<Ident> {
     let mut errors = vec![];
@@ -247,18 +354,18 @@ pub fn get_values_idents(query: &Query) -> Vec<Ident> {
 }
 
 /// Get the aggregate function calls to typecheck the query.
-pub fn get_aggregate_calls(query: &Query) -> Vec<(String, Expr)> {
+pub fn get_aggregate_calls(query: &Query) -> Vec<(Aggregate, Expr)> {
     if let Query::Aggregate { ref aggregate_filter, ..} = *query {
         return get_calls_from_aggregate_filter(aggregate_filter);
     }
     vec![]
 }
 
-fn get_calls_from_aggregate_filter(filter: &AggregateFilterExpression) -> Vec<(String, Expr)> {
+fn get_calls_from_aggregate_filter(filter: &AggregateFilterExpression) -> Vec<(Aggregate, Expr)> {
     let mut calls = vec![];
     match *filter {
         AggregateFilterExpression::Filter(ref filter) =>
-            calls.push((filter.operand1.function.clone(), filter.operand2.clone())),
+            calls.push((filter.operand1.clone(), filter.operand2.clone())),
         AggregateFilterExpression::Filters(ref filters) => {
             calls.extend(get_calls_from_aggregate_filter(&filters.operand1));
             calls.extend(get_calls_from_aggregate_filter(&filters.operand2));
@@ -25,6 +25,8 @@ use proc_macro2::Span;
 use syn::{
     BinOp,
     Expr,
+    Ident,
+    UnOp,
 };
 use syn::spanned::Spanned;
 
@@ -32,7 +34,9 @@ use ast::{
     Assignment,
     AssignmentOperator,
     Expression,
+    Query,
     WithSpan,
+    some_inner_expr,
 };
 use error::{Error, Result, res};
 use plugin::number_literal;
@@ -78,6 +82,37 @@ pub fn argument_to_assignment(arg: &Expression) -> Result<Assignment> {
     res(assignment, errors)
 }
 
+/// Get the fields assigned to a literal value by an insert or an update, to check the validation
+/// attributes of these fields at compile time.
+pub fn get_literal_assignments(query: &Query) -> Vec<(Ident, Expression)> {
+    fn is_literal(expr: &Expression) -> bool {
+        match *expr {
+            Expr::Lit(_) => true,
+            Expr::Unary(ref unary) =>
+                if let UnOp::Neg(_) = unary.op {
+                    is_literal(&unary.expr)
+                }
+                else {
+                    false
+                },
+            _ => some_inner_expr(expr).map(is_literal).unwrap_or(false),
+        }
+    }
+
+    match *query {
+        Query::Insert { ref assignments, .. } | Query::Update { ref assignments, .. } =>
+            assignments.iter()
+                .filter(|assignment| assignment.operator.node == AssignmentOperator::Equal &&
+                        is_literal(&assignment.value))
+                .filter_map(|assignment| {
+                    let identifier = assignment.identifier?;
+                    Some((identifier, assignment.value.clone()))
+                })
+                .collect(),
+        _ => vec![],
+    }
+}
+
 /// Convert a `BinOp` to an SQL `AssignmentOperator`.
 fn binop_to_assignment_operator(binop: &BinOp) -> (AssignmentOperator, Span) {
     match *binop {
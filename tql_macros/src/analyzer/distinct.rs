@@ -0,0 +1,77 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/// Analyzer for the distinct() and distinct_on() methods.
+
+use syn::Ident;
+
+use ast::{
+    Distinct,
+    Expression,
+    Order,
+    Query,
+};
+use error::{Error, Result, res};
+use super::path_expr_to_identifier;
+
+/// Convert an `Expression` to a field of a distinct_on() clause.
+pub fn argument_to_distinct_field(arg: &Expression) -> Result<Ident> {
+    let mut errors = vec![];
+    if let Some(identifier) = path_expr_to_identifier(arg, &mut errors) {
+        res(identifier, errors)
+    }
+    else {
+        Err(errors)
+    }
+}
+
+/// Check that the first fields of the sort() clause are the fields of the distinct_on() clause.
+/// This is required by PostgreSQL.
+pub fn check_distinct_on_order(query: &Query, errors: &mut Vec<Error>) {
+    if let Query::Select { distinct: Distinct::DistinctOn(ref fields), ref order, .. } = *query {
+        for order in order.iter().take(fields.len()) {
+            let identifier =
+                match *order {
+                    Order::Ascending(ref identifier) | Order::Descending(ref identifier) => identifier,
+                    Order::NoOrder => continue,
+                };
+            if !fields.contains(identifier) {
+                let mut error = Error::new(
+                    &format!("cannot sort on `{}` before the distinct_on() fields", identifier),
+                    identifier.span(),
+                );
+                let fields: Vec<_> = fields.iter().map(|field| format!("`{}`", field)).collect();
+                error.add_help(&format!("sort on {} first", fields.join(", ")));
+                errors.push(error);
+            }
+        }
+    }
+}
+
+/// Get the identifiers in the distinct_on() clause to be able to check that they exist.
+pub fn get_distinct_idents(query: &Query) -> Vec<Ident> {
+    if let Query::Select { distinct: Distinct::DistinctOn(ref fields), .. } = *query {
+        fields.clone()
+    }
+    else {
+        vec![]
+    }
+}
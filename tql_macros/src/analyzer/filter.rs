@@ -45,6 +45,9 @@ use ast::{
     Query,
     RelationalOperator,
     WithSpan,
+    is_none_literal,
+    mirror_operator,
+    some_inner_expr,
 };
 use error::{Error, Result, res};
 
@@ -73,13 +76,13 @@ pub fn analyze_filter_types(filter: &FilterExpression, table_name: &str, errors:
 fn binary_expression_to_filter_expression(expr1: &Expression, op: &BinOp, expr2: &Expression, table_name: &str) ->
     Result<FilterExpression>
 {
-    // TODO: accumulate the errors instead of stopping when the first one is encountered.
-    let filter1 = expression_to_filter_expression(expr1, table_name)?;
     // TODO: return errors instead of dummy.
     let dummy = FilterExpression::NoFilters;
 
     let filter =
         if is_logical_operator(op) {
+            // TODO: accumulate the errors instead of stopping when the first one is encountered.
+            let filter1 = expression_to_filter_expression(expr1, table_name)?;
             let filter2 = expression_to_filter_expression(expr2, table_name)?;
             FilterExpression::Filters(Filters {
                 operand1: Box::new(filter1),
@@ -88,11 +91,28 @@ fn binary_expression_to_filter_expression(expr1: &Expression, op: &BinOp, expr2:
             })
         }
         else if is_relational_operator(op) {
-            if let FilterExpression::FilterValue(filter1) = filter1 {
+            // NOTE: `None == field` and `Some(value) < field` are converted to `field == None` and
+            // `field > Some(value)`.
+            let mirrored = is_none_literal(expr1) || some_inner_expr(expr1).is_some();
+            let (field, value, operator) =
+                if mirrored {
+                    (expr2, expr1, mirror_operator(binop_to_relational_operator(op)))
+                }
+                else {
+                    (expr1, expr2, binop_to_relational_operator(op))
+                };
+            if is_none_literal(value) && !is_equality_operator(op) {
+                return Err(vec![Error::new(
+                    "None can only be compared with == or !=",
+                    value.span(),
+                )]);
+            }
+            if let FilterExpression::FilterValue(filter1) = expression_to_filter_expression(field, table_name)? {
                 FilterExpression::Filter(Filter {
                     operand1: filter1.node,
-                    operator: binop_to_relational_operator(op),
-                    operand2: expr2.clone(),
+                    operator,
+                    operand2: value.clone(),
+                    mirrored,
                 })
             }
             else {
@@ -100,6 +120,7 @@ fn binary_expression_to_filter_expression(expr1: &Expression, op: &BinOp, expr2:
             }
         }
         else {
+            let _ = expression_to_filter_expression(expr1, table_name)?;
             dummy
         };
     Ok(filter)
@@ -148,7 +169,7 @@ pub fn expression_to_filter_expression(arg: &Expression, table_name: &str) -> Re
             Expr::MethodCall(ref call) => {
                 FilterExpression::FilterValue(WithSpan {
                     node: method_call_expression_to_filter_expression(call.method, &call.receiver, &call.args,
-                                                                      call.span(), &mut errors),
+                                                                      call.span(), table_name, &mut errors),
                     span: arg.span(),
                 })
             },
@@ -179,6 +200,22 @@ pub fn expression_to_filter_expression(arg: &Expression, table_name: &str) -> Re
     res(filter, errors)
 }
 
+/// Check if a `BinOp` is == or !=.
+fn is_equality_operator(binop: &BinOp) -> bool {
+    match *binop {
+        BinOp::Eq(_) | BinOp::Ne(_) => true,
+        _ => false,
+    }
+}
+
+/// Check if a `BinOp` is an arithmetic operator.
+pub fn is_arithmetic_operator(binop: &BinOp) -> bool {
+    match *binop {
+        BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_) => true,
+        _ => false,
+    }
+}
+
 /// Check if a `BinOp` is a `LogicalOperator`.
 pub fn is_logical_operator(binop: &BinOp) -> bool {
     match *binop {
@@ -197,12 +234,12 @@ pub fn is_relational_operator(binop: &BinOp) -> bool {
 
 /// Convert a method call expression to a filter expression.
 fn method_call_expression_to_filter_expression(identifier: Ident, expr: &Expression, args: &Punctuated<Expr, Comma>,
-    position: Span, errors: &mut Vec<Error>) -> FilterValue
+    position: Span, table_name: &str, errors: &mut Vec<Error>) -> FilterValue
 {
     let dummy = FilterValue::None;
     match *expr {
         Expr::Path(ref path) => {
-            path_method_call_to_filter(&path.path, identifier, args, position)
+            path_method_call_to_filter(&path.path, identifier, args, position, table_name)
         },
         _ => {
             errors.push(Error::new(
@@ -215,7 +252,8 @@ fn method_call_expression_to_filter_expression(identifier: Ident, expr: &Express
 }
 
 /// Convert a method call where the object is an identifier to a filter expression.
-fn path_method_call_to_filter(path: &Path, identifier: Ident, args: &Punctuated<Expr, Comma>, position: Span) -> FilterValue
+fn path_method_call_to_filter(path: &Path, identifier: Ident, args: &Punctuated<Expr, Comma>, position: Span,
+    table_name: &str) -> FilterValue
 {
     let object_name = path.segments.first().unwrap().into_value().ident;
     let arguments: Vec<Expression> = args.iter()
@@ -227,15 +265,16 @@ fn path_method_call_to_filter(path: &Path, identifier: Ident, args: &Punctuated<
         method_name: identifier,
         object_name,
         position,
+        table: table_name.to_string(),
     })
 }
 
 pub fn get_method_calls(query: &Query) -> Vec<(ast::MethodCall, Option<Expression>)> {
     match *query {
-        Query::Aggregate { ref filter, .. } | Query::Delete { ref filter, .. } | Query::Select { ref filter, .. } |
-            Query::Update { ref filter, .. } =>
+        Query::Aggregate { ref filter, .. } | Query::Count { ref filter, .. } | Query::Delete { ref filter, .. } |
+            Query::Exists { ref filter, .. } | Query::Select { ref filter, .. } | Query::Update { ref filter, .. } =>
             get_methods_from_filter(filter),
-        Query::CreateTable { .. } | Query::Drop { .. } | Query::Insert { .. } =>
+        Query::CreateTable { .. } | Query::Drop { .. } | Query::Insert { .. } | Query::InsertMany { .. } =>
             vec![],
     }
 }
@@ -45,6 +45,7 @@ pub fn get_expression_to_filter_expression(arg: &Expression, table_name: &str) -
                 operand1: FilterValue::PrimaryKey(table_name.to_string()),
                 operator: RelationalOperator::Equal,
                 operand2: arg.clone(),
+                mirrored: false,
             });
             res((filter, true, Limit::NoLimit), vec![])
         },
@@ -23,18 +23,31 @@
 
 use std::collections::HashSet;
 
-#[cfg(feature = "unstable")]
 use proc_macro2::Span;
 use syn::Ident;
 
 use ast::{
     Assignment,
     AssignmentOperator,
+    Expression,
+    OnConflict,
     Query,
 };
-use error::Error;
+use error::{Error, Result, res};
 #[cfg(feature = "unstable")]
 use parser::MethodCalls;
+use super::path_expr_to_identifier;
+
+/// Convert an `Expression` to a field of an on_conflict() clause.
+pub fn argument_to_conflict_field(arg: &Expression) -> Result<Ident> {
+    let mut errors = vec![];
+    if let Some(identifier) = path_expr_to_identifier(arg, &mut errors) {
+        res(identifier, errors)
+    }
+    else {
+        Err(errors)
+    }
+}
 
 /// Check that the method call contains all the fields from the `table` and that all assignments
 /// does not use an operation (e.g. +=).
@@ -53,6 +66,45 @@ pub fn check_insert_arguments(assignments: &[Assignment], errors: &mut Vec<Error
     // TODO: check if the primary key is not in the inserted field?
 }
 
+/// Check that on_conflict() has fields and is followed by update() and that update() is only
+/// called after on_conflict() in an insert query.
+pub fn check_on_conflict(on_conflict: &OnConflict, on_conflict_position: Option<Span>, update_position: Option<Span>,
+                         errors: &mut Vec<Error>)
+{
+    match *on_conflict {
+        OnConflict::Update(ref fields, ref assignments) => {
+            let position = on_conflict_position.expect("on_conflict() position");
+            if fields.is_empty() {
+                errors.push(Error::new_with_code("this method takes at least 1 parameter but 0 parameters were supplied",
+                    position, "E0061"));
+            }
+            if assignments.is_empty() && update_position.is_none() {
+                let mut error = Error::new("on_conflict() must be followed by update()", position);
+                error.add_help("use on_conflict_ignore() to ignore the conflicting rows");
+                errors.push(error);
+            }
+        },
+        OnConflict::Ignore | OnConflict::NoConflict => {
+            if let Some(position) = update_position {
+                let mut error = Error::new("cannot call the update() method with the insert() method without on_conflict()",
+                    position);
+                error.add_help("add `.on_conflict(field)` before update()");
+                errors.push(error);
+            }
+        },
+    }
+}
+
+/// Get the identifiers in the on_conflict() clause to be able to check that they exist.
+pub fn get_conflict_idents(query: &Query) -> Vec<Ident> {
+    if let Query::Insert { on_conflict: OnConflict::Update(ref fields, _), .. } = *query {
+        fields.clone()
+    }
+    else {
+        vec![]
+    }
+}
+
 pub fn get_insert_idents(query: &Query) -> Option<Vec<Ident>> {
     let mut idents = vec![];
     if let Query::Insert { ref assignments, ..} = *query {
@@ -89,16 +89,19 @@ pub fn argument_to_limit(expression: &Expression) -> Result<Limit> {
 /// Get the expressions used in the limit clause to be able to check their type.
 pub fn get_limit_args(query: &Query) -> Vec<Expr> {
     let mut exprs = vec![];
-    if let Query::Select { ref limit, ..} = *query {
-        match *limit {
-            Limit::EndRange(ref expr) | Limit::Index(ref expr) | Limit::StartRange(ref expr) =>
-                exprs.push(expr.clone()),
-            Limit::LimitOffset(ref expr1, ref expr2) | Limit::Range(ref expr1, ref expr2) => {
-                exprs.push(expr1.clone());
-                exprs.push(expr2.clone());
-            },
-            Limit::NoLimit => (),
-        }
+    let limit =
+        match *query {
+            Query::Aggregate { ref limit, .. } | Query::Select { ref limit, .. } => limit,
+            _ => return exprs,
+        };
+    match *limit {
+        Limit::EndRange(ref expr) | Limit::Index(ref expr) | Limit::StartRange(ref expr) =>
+            exprs.push(expr.clone()),
+        Limit::LimitOffset(ref expr1, ref expr2) | Limit::Range(ref expr1, ref expr2) => {
+            exprs.push(expr1.clone());
+            exprs.push(expr2.clone());
+        },
+        Limit::NoLimit => (),
     }
     exprs
 }
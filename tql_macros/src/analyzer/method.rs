@@ -30,7 +30,9 @@ pub fn analyze_methods(query: &Query) -> Result<()> {
     let mut errors = vec![];
     for (call, _) in calls {
         let name = call.method_name.as_ref();
-        if let Some(method) = methods.get(name) {
+        // NOTE: the availability of a method defined on many types is checked by the
+        // tql_{table}_method macro since the type of the field is unknown here.
+        if let Some(&[ref method]) = methods.get(name).map(Vec::as_slice) {
             if method.template.is_none() {
                 errors.push(Error::new(&format!("The method {} is not available on this backend", name),
                     call.method_name.span()))
@@ -23,12 +23,14 @@
 
 mod aggregate;
 mod assignment;
+mod distinct;
 mod filter;
 mod get;
 mod insert;
 mod method;
 mod join;
 mod limit;
+mod returning;
 mod sort;
 
 use std::fmt::Display;
@@ -50,31 +52,46 @@ use ast::{
     Aggregate,
     AggregateFilterExpression,
     Assignment,
+    Distinct,
     Expression,
     FilterExpression,
     Groups,
     Join,
     Limit,
+    OnConflict,
     Order,
     Query,
+    Returning,
+    SoftDelete,
 };
 use error::{Error, Result, res};
 use parser::{MethodCall, MethodCalls};
 use plugin::number_literal;
-use self::aggregate::{argument_to_aggregate, argument_to_group, expression_to_aggregate_filter_expression};
+use self::aggregate::{
+    argument_to_aggregate,
+    argument_to_group,
+    check_aggregate_joins,
+    check_aggregate_order,
+    expression_to_aggregate_filter_expression,
+};
 pub use self::aggregate::{get_aggregate_calls, get_values_idents};
 use self::assignment::argument_to_assignment;
+pub use self::assignment::get_literal_assignments;
+use self::distinct::{argument_to_distinct_field, check_distinct_on_order};
+pub use self::distinct::get_distinct_idents;
 use self::filter::{analyze_filter_types, expression_to_filter_expression};
 pub use self::filter::get_method_calls;
 use self::get::get_expression_to_filter_expression;
-use self::insert::check_insert_arguments;
-pub use self::insert::get_insert_idents;
+use self::insert::{argument_to_conflict_field, check_insert_arguments, check_on_conflict};
+pub use self::insert::{get_conflict_idents, get_insert_idents};
 #[cfg(feature = "unstable")]
 pub use self::insert::get_insert_position;
 use self::join::argument_to_join;
 use self::limit::{analyze_limit_types, argument_to_limit};
 pub use self::limit::get_limit_args;
 pub use self::method::analyze_methods;
+use self::returning::argument_to_returning_field;
+pub use self::returning::get_returning_idents;
 use self::sort::argument_to_order;
 pub use self::sort::get_sort_idents;
 use string::{find_near, plural_verb};
@@ -84,10 +101,13 @@ use types::Type;
 #[derive(PartialEq)]
 enum SqlQueryType {
     Aggregate,
+    Count,
     CreateTable,
     Delete,
     Drop,
+    Exists,
     Insert,
+    InsertMany,
     Select,
     SelectOne,
     Update,
@@ -109,15 +129,25 @@ struct QueryData {
     aggregate_filter: AggregateFilterExpression,
     aggregates: Vec<Aggregate>,
     groups: Groups,
-    // Aggregate, Delete, Select, Update
+    // Aggregate, Count, Delete, Exists, Select, Update
     filter: FilterExpression,
-    // Aggregate / Select
+    // Aggregate, Count, Exists, Select
     joins: Vec<Join>,
     // Insert / Update
     assignments: Vec<Assignment>,
-    // Select
+    // Insert
+    on_conflict: OnConflict,
+    // InsertMany
+    rows: Option<Expression>,
+    // Delete, Insert, Update
+    returning: Returning,
+    // Aggregate, Select
     limit: Limit,
     order: Vec<Order>,
+    // Aggregate, Count, Delete, Exists, Select, Update
+    soft_delete: SoftDelete,
+    // Select
+    distinct: Distinct,
     use_pk: bool,
     // All
     query_type: SqlQueryType,
@@ -141,6 +171,9 @@ pub fn analyze(method_calls: &MethodCalls) -> Result<Query> {
     let query = new_query(query_data, table_name);
 
     check_delete_without_filters(&query, delete_position, &mut errors);
+    check_aggregate_joins(&query, &mut errors);
+    check_aggregate_order(&query, &mut errors);
+    check_distinct_on_order(&query, &mut errors);
 
     res(query, errors)
 }
@@ -149,15 +182,18 @@ pub fn analyze(method_calls: &MethodCalls) -> Result<Query> {
 pub fn analyze_types(query: &Query) -> Result<()> {
     let mut errors = vec![];
     match *query {
-        Query::Aggregate { ref filter, ref table, .. } => {
+        Query::Aggregate { ref filter, ref limit, ref table, .. } => {
             analyze_filter_types(filter, &table, &mut errors);
+            analyze_limit_types(limit, &mut errors);
         },
-        Query::CreateTable { .. } => (), // Nothing to analyze.
-        Query::Delete { ref filter, ref table, .. } => {
+        Query::Count { ref filter, ref table, .. } | Query::Delete { ref filter, ref table, .. } |
+            Query::Exists { ref filter, ref table, .. } => {
             analyze_filter_types(filter, &table, &mut errors);
         },
+        Query::CreateTable { .. } => (), // Nothing to analyze.
         Query::Drop { .. } => (), // Nothing to analyze.
         Query::Insert { .. } => (),
+        Query::InsertMany { .. } => (),
         Query::Select { ref filter, ref limit, ref table, .. } => {
             analyze_filter_types(filter, &table, &mut errors);
             analyze_limit_types(limit, &mut errors);
@@ -203,13 +239,17 @@ fn check_delete_without_filters(query: &Query, delete_position: Option<Span>, er
 fn check_method_calls_validity(method_calls: &MethodCalls, errors: &mut Vec<Error>) {
     let method_map =
         hashmap!{
-            "aggregate" => vec!["filter", "join", "values"],
-            "all" => vec!["filter", "get", "join", "limit", "sort"],
+            "aggregate" => vec!["filter", "join", "limit", "only_deleted", "sort", "values", "with_deleted"],
+            "all" => vec!["distinct", "distinct_on", "filter", "get", "join", "limit", "only_deleted", "sort",
+                          "with_deleted"],
+            "count" => vec!["filter", "join", "only_deleted", "with_deleted"],
             "create" => vec![],
-            "delete" => vec!["filter", "get"],
+            "delete" => vec!["filter", "get", "only_deleted", "returning", "with_deleted"],
             "drop" => vec![],
-            "insert" => vec![],
-            "update" => vec!["filter", "get"],
+            "exists" => vec!["filter", "join", "only_deleted", "with_deleted"],
+            "insert" => vec!["on_conflict", "on_conflict_ignore", "returning", "update"],
+            "insert_many" => vec![],
+            "update" => vec!["filter", "get", "only_deleted", "returning", "with_deleted"],
         };
 
     let main_method = method_calls.calls.iter()
@@ -308,17 +348,27 @@ fn get_methods() -> Vec<String> {
     vec![
         "aggregate".to_string(),
         "all".to_string(),
+        "count".to_string(),
         "create".to_string(),
         "delete".to_string(),
+        "distinct".to_string(),
+        "distinct_on".to_string(),
         "drop".to_string(),
+        "exists".to_string(),
         "filter".to_string(),
         "get".to_string(),
         "insert".to_string(),
+        "insert_many".to_string(),
         "join".to_string(),
         "limit".to_string(),
+        "on_conflict".to_string(),
+        "on_conflict_ignore".to_string(),
+        "only_deleted".to_string(),
+        "returning".to_string(),
         "sort".to_string(),
         "update".to_string(),
         "values".to_string(),
+        "with_deleted".to_string(),
     ]
 }
 
@@ -379,7 +429,8 @@ fn mismatched_types<S: Display, T: Display>(expected_type: S, actual_type: &T, p
 
 /// Create a new query from all the data gathered by the method calls.
 fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, groups,
-    aggregate_filter, query_type, use_pk }: QueryData, table_name: String) -> Query
+    aggregate_filter, distinct, on_conflict, query_type, returning, rows, soft_delete, use_pk }: QueryData,
+    table_name: String) -> Query
 {
     match query_type {
         SqlQueryType::Aggregate =>
@@ -389,6 +440,16 @@ fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, g
                 filter,
                 groups,
                 joins,
+                limit,
+                order,
+                soft_delete,
+                table: table_name,
+            },
+        SqlQueryType::Count =>
+            Query::Count {
+                filter,
+                joins,
+                soft_delete,
                 table: table_name,
             },
         SqlQueryType::CreateTable =>
@@ -398,6 +459,8 @@ fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, g
         SqlQueryType::Delete =>
             Query::Delete {
                 filter,
+                returning,
+                soft_delete,
                 table: table_name,
                 use_pk,
             },
@@ -405,18 +468,34 @@ fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, g
             Query::Drop {
                 table: table_name,
             },
+        SqlQueryType::Exists =>
+            Query::Exists {
+                filter,
+                joins,
+                soft_delete,
+                table: table_name,
+            },
         SqlQueryType::Insert =>
             Query::Insert {
                 assignments,
+                on_conflict,
+                returning,
+                table: table_name,
+            },
+        SqlQueryType::InsertMany =>
+            Query::InsertMany {
+                rows: rows.expect("insert_many() rows"),
                 table: table_name,
             },
         SqlQueryType::Select | SqlQueryType::SelectOne => {
             Query::Select {
+                distinct,
                 filter,
                 get: query_type == SqlQueryType::SelectOne,
                 joins,
                 limit,
                 order,
+                soft_delete,
                 table: table_name,
                 use_pk,
             }
@@ -425,6 +504,8 @@ fn new_query(QueryData { filter, joins, limit, order, assignments, aggregates, g
             Query::Update {
                 assignments,
                 filter,
+                returning,
+                soft_delete,
                 table: table_name,
                 use_pk,
             },
@@ -459,6 +540,8 @@ fn path_expr_to_string(expression: &Expression, errors: &mut Vec<Error>) -> Opti
 fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut Option<Span>) -> Result<QueryData> {
     let mut errors = vec![];
     let mut query_data = QueryData::default();
+    let mut on_conflict_position = None;
+    let mut update_position = None;
 
     for method_call in calls {
         match method_call.name.as_ref() {
@@ -471,6 +554,10 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
             "all" => {
                 check_no_arguments(&method_call, &mut errors);
             },
+            "count" => {
+                check_no_arguments(&method_call, &mut errors);
+                query_data.query_type = SqlQueryType::Count;
+            },
             "create" => {
                 check_no_arguments(&method_call, &mut errors);
                 query_data.query_type = SqlQueryType::CreateTable;
@@ -484,6 +571,25 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
                 check_no_arguments(&method_call, &mut errors);
                 query_data.query_type = SqlQueryType::Drop;
             },
+            "distinct" => {
+                check_no_arguments(&method_call, &mut errors);
+                query_data.distinct = Distinct::Distinct;
+            },
+            "distinct_on" => {
+                if cfg!(feature = "postgres") {
+                    try(convert_arguments(&method_call.args, argument_to_distinct_field), &mut errors, |fields| {
+                        query_data.distinct = Distinct::DistinctOn(fields);
+                    });
+                }
+                else {
+                    errors.push(Error::new("The method distinct_on is not available on this backend",
+                        method_call.name.span()));
+                }
+            },
+            "exists" => {
+                check_no_arguments(&method_call, &mut errors);
+                query_data.query_type = SqlQueryType::Exists;
+            },
             "filter" => {
                 if query_data.aggregates.is_empty() {
                     // If the aggregate() method was not called, filter() filters on the values
@@ -524,6 +630,20 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
                 }
                 query_data.query_type = SqlQueryType::Insert;
             },
+            "insert_many" => {
+                if method_call.args.len() == 1 {
+                    query_data.rows = Some(method_call.args[0].clone());
+                }
+                else {
+                    let length = method_call.args.len();
+                    errors.push(Error::new_with_code(
+                        &format!("this method takes 1 parameter but {} parameter{} supplied", length,
+                                 plural_verb(length)),
+                        method_call.name.span(), "E0061"
+                    ));
+                }
+                query_data.query_type = SqlQueryType::InsertMany;
+            },
             "join" => {
                 try(convert_arguments(&method_call.args, |expr| argument_to_join(expr, table_name)), &mut errors,
                     |result| {
@@ -537,25 +657,67 @@ fn process_methods(calls: &[MethodCall], table_name: &str, delete_position: &mut
                     query_data.limit = new_limit;
                 });
             },
+            "on_conflict" => {
+                try(convert_arguments(&method_call.args, argument_to_conflict_field), &mut errors, |fields| {
+                    query_data.on_conflict = OnConflict::Update(fields, vec![]);
+                });
+                on_conflict_position = Some(method_call.name.span());
+            },
+            "on_conflict_ignore" => {
+                check_no_arguments(&method_call, &mut errors);
+                query_data.on_conflict = OnConflict::Ignore;
+            },
+            "only_deleted" => {
+                check_no_arguments(&method_call, &mut errors);
+                query_data.soft_delete = SoftDelete::OnlyDeleted;
+            },
+            "returning" => {
+                try(convert_arguments(&method_call.args, argument_to_returning_field), &mut errors, |fields| {
+                    query_data.returning =
+                        if fields.is_empty() {
+                            Returning::Row
+                        }
+                        else {
+                            Returning::Fields(fields)
+                        };
+                });
+            },
             "sort" => {
                 try(convert_arguments(&method_call.args, argument_to_order), &mut errors, |new_order| {
                     query_data.order = new_order;
                 });
             },
             "update" => {
-                try(convert_arguments(&method_call.args, argument_to_assignment), &mut errors, |assigns| {
-                    query_data.assignments = assigns;
-                });
-                query_data.query_type = SqlQueryType::Update;
+                if query_data.query_type == SqlQueryType::Insert {
+                    // If the insert() method was called, update() gives the assignments to do when
+                    // there is a conflict (ON CONFLICT DO UPDATE).
+                    try(convert_arguments(&method_call.args, argument_to_assignment), &mut errors, |assigns| {
+                        if let OnConflict::Update(_, ref mut assignments) = query_data.on_conflict {
+                            *assignments = assigns;
+                        }
+                    });
+                    update_position = Some(method_call.name.span());
+                }
+                else {
+                    try(convert_arguments(&method_call.args, argument_to_assignment), &mut errors, |assigns| {
+                        query_data.assignments = assigns;
+                    });
+                    query_data.query_type = SqlQueryType::Update;
+                }
             },
             "values" => {
                 try(convert_arguments(&method_call.args, argument_to_group), &mut errors, |new_groups| {
                     query_data.groups = new_groups;
                 });
             },
+            "with_deleted" => {
+                check_no_arguments(&method_call, &mut errors);
+                query_data.soft_delete = SoftDelete::WithDeleted;
+            },
             _ => (), // NOTE: Nothing to do since check_methods() check for unknown method.
         }
     }
+    check_on_conflict(&query_data.on_conflict, on_conflict_position, update_position, &mut errors);
     res(query_data, errors)
 }
 
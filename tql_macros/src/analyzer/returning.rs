@@ -0,0 +1,53 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+/// Analyzer for the returning() method.
+
+use syn::Ident;
+
+use ast::{
+    Expression,
+    Query,
+    Returning,
+};
+use error::{Result, res};
+use super::path_expr_to_identifier;
+
+/// Convert an `Expression` to a field of a returning() clause.
+pub fn argument_to_returning_field(arg: &Expression) -> Result<Ident> {
+    let mut errors = vec![];
+    if let Some(identifier) = path_expr_to_identifier(arg, &mut errors) {
+        res(identifier, errors)
+    }
+    else {
+        Err(errors)
+    }
+}
+
+/// Get the identifiers in the returning() clause to be able to check that they exist.
+pub fn get_returning_idents(query: &Query) -> Vec<Ident> {
+    match *query {
+        Query::Delete { returning: Returning::Fields(ref fields), .. } |
+            Query::Insert { returning: Returning::Fields(ref fields), .. } |
+            Query::Update { returning: Returning::Fields(ref fields), .. } => fields.clone(),
+        _ => vec![],
+    }
+}
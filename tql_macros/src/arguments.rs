@@ -31,12 +31,16 @@ use ast::{
     Aggregate,
     AggregateFilterExpression,
     Assignment,
+    AssignmentOperator,
     Expression,
     FilterExpression,
     FilterValue,
     Limit,
     MethodCall,
+    OnConflict,
     Query,
+    is_none_literal,
+    some_inner_expr,
 };
 
 /// A Rust expression to be send as a parameter to the SQL query function.
@@ -45,6 +49,13 @@ pub struct Arg {
     pub expression: Expression,
     pub field_name: Option<Ident>,
     pub field_name_prefix: Option<String>,
+    /// The expression was wrapped in `Some()` in the query.
+    pub nullable: bool,
+    /// The expression is compared to the primary key of the table `field_name_prefix`.
+    pub primary_key: bool,
+    /// The expression is assigned to the field `field_name`, so its value is checked against the
+    /// validation attributes of the field.
+    pub validated: bool,
 }
 
 /// A collection of `Arg`s.
@@ -58,6 +69,9 @@ fn add(arguments: &mut Args, literals: &mut Args, field_name: Option<Ident>, fie
         expression: expr,
         field_name_prefix,
         field_name,
+        nullable: false,
+        primary_key: false,
+        validated: false,
     });
 }
 
@@ -66,17 +80,34 @@ fn add_assignments(assignments: Vec<Assignment>, arguments: &mut Args, literals:
     for assign in assignments {
         let field_name = assign.identifier.expect("Assignment identifier");
         // NOTE: At this stage (code generation), the field exists, hence unwrap().
-        add(arguments, literals, Some(field_name), None, assign.value);
+        add_expr(arguments, literals, Arg {
+            expression: assign.value,
+            field_name: Some(field_name),
+            field_name_prefix: None,
+            nullable: false,
+            primary_key: false,
+            // NOTE: the value of an operation like `field += value` is not the value of the field.
+            validated: assign.operator.node == AssignmentOperator::Equal,
+        });
     }
 }
 
 /// Add an argument to `arguments`.
-fn add_expr(arguments: &mut Args, literals: &mut Args, arg: Arg) {
-    // Do not add literal.
+fn add_expr(arguments: &mut Args, literals: &mut Args, mut arg: Arg) {
+    // Only send the value of `Some(value)` as the argument.
+    if let Some(expr) = some_inner_expr(&arg.expression).cloned() {
+        arg.expression = expr;
+        arg.nullable = true;
+    }
+    // Do not add literal (None is converted to NULL in the query).
     if let Expr::Lit(_) = arg.expression {
         literals.push(arg);
         return;
     }
+    if is_none_literal(&arg.expression) {
+        literals.push(arg);
+        return;
+    }
     arguments.push(arg);
 }
 
@@ -141,6 +172,9 @@ fn add_limit_arguments(limit: Limit, arguments: &mut Args, literals: &mut Args)
                 expression,
                 field_name: None,
                 field_name_prefix: None,
+                nullable: false,
+                primary_key: false,
+                validated: false,
             });
             add(arguments, literals, None, None, expression1);
         },
@@ -155,6 +189,9 @@ fn add_with_method(args: &mut Args, literals: &mut Args, expr: Expression)
         expression: expr,
         field_name: None,
         field_name_prefix: None,
+        nullable: false,
+        primary_key: false,
+        validated: false,
     });
 }
 
@@ -185,7 +222,14 @@ fn add_filter_value_arguments(filter_value: &FilterValue, args: &mut Args, liter
         FilterValue::None => unreachable!("FilterValue::None in add_filter_value_arguments()"),
         FilterValue::PrimaryKey(ref table) => {
             if let Some(expr) = expression {
-                add(args, literals, None, Some(table.clone()), expr);
+                add_expr(args, literals, Arg {
+                    expression: expr,
+                    field_name: None,
+                    field_name_prefix: Some(table.clone()),
+                    nullable: false,
+                    primary_key: true,
+                    validated: false,
+                });
             }
         },
     }
@@ -197,17 +241,24 @@ pub fn arguments(query: Query) -> (Args, Args) {
     let mut literals = vec![];
 
     match query {
-        Query::Aggregate { aggregate_filter, filter, .. } => {
+        Query::Aggregate { aggregate_filter, filter, limit, .. } => {
             add_filter_arguments(filter, &mut arguments, &mut literals);
             add_aggregate_filter_arguments(aggregate_filter, &mut arguments, &mut literals);
+            add_limit_arguments(limit, &mut arguments, &mut literals);
         },
-        Query::CreateTable { .. } => (), // No arguments.
-        Query::Delete { filter, .. } => {
+        Query::Count { filter, .. } | Query::Delete { filter, .. } | Query::Exists { filter, .. } => {
             add_filter_arguments(filter, &mut arguments, &mut literals);
         },
+        Query::CreateTable { .. } => (), // No arguments.
         Query::Drop { .. } => (), // No arguments.
-        Query::Insert { assignments, .. } => {
+        Query::Insert { assignments, on_conflict, .. } => {
             add_assignments(assignments, &mut arguments, &mut literals);
+            if let OnConflict::Update(_, assignments) = on_conflict {
+                add_assignments(assignments, &mut arguments, &mut literals);
+            }
+        },
+        Query::InsertMany { rows, .. } => {
+            add(&mut arguments, &mut literals, None, None, rows);
         },
         Query::Select { filter, limit, ..} => {
             add_filter_arguments(filter, &mut arguments, &mut literals);
@@ -25,7 +25,7 @@ use std::fmt::{Display, Error, Formatter};
 
 use proc_macro2::{Span, TokenStream};
 use quote::{Tokens, ToTokens};
-use syn::{Expr, Ident};
+use syn::{Expr, ExprCall, Ident};
 
 pub type Expression = Expr;
 pub type Groups = Vec<Ident>;
@@ -33,11 +33,14 @@ pub type Groups = Vec<Ident>;
 /// `Aggregate` for use in SQL Aggregate `Query`.
 #[derive(Clone, Debug, Default)]
 pub struct Aggregate {
-    pub field: Option<Ident>,
+    /// The aggregated expression: a field, a field of a joined table (`author.age`) or an
+    /// arithmetic expression using them.
+    pub argument: Option<Expression>,
     pub function: String,
     pub has_name_in_query: bool,
     pub result_name: Option<Ident>,
-    pub sql_function: String,
+    /// The SQL template of the aggregate function, with `$0` as a placeholder for the field.
+    pub template: String,
 }
 
 /// `AggregateFilter` for SQL `Query` (HAVING clause).
@@ -123,6 +126,8 @@ pub struct Filter {
     pub operator: RelationalOperator,
     /// The expression to be compared to `operand1`.
     pub operand2: Expression,
+    /// The operands are swapped in the query (like `None == field`).
+    pub mirrored: bool,
 }
 
 /// Either a single `Filter`, `Filters`, `NegFilter`, `NoFilters`, `ParenFilter` or a `FilterValue`.
@@ -153,6 +158,23 @@ pub struct Filters {
     pub operand2: Box<FilterExpression>,
 }
 
+/// An SQL DISTINCT clause.
+#[derive(Debug)]
+pub enum Distinct {
+    /// Comes from `distinct()`.
+    Distinct,
+    /// Comes from `distinct_on(field1, field2)`.
+    DistinctOn(Vec<Ident>),
+    /// No distinct was specified.
+    NoDistinct,
+}
+
+impl Default for Distinct {
+    fn default() -> Distinct {
+        Distinct::NoDistinct
+    }
+}
+
 /// Either an identifier or a method call.
 #[derive(Debug)]
 pub enum FilterValue {
@@ -207,6 +229,24 @@ pub struct MethodCall {
     pub method_name: Ident,
     pub object_name: Ident,
     pub position: Span,
+    pub table: String,
+}
+
+/// An SQL ON CONFLICT clause of an INSERT query.
+#[derive(Debug)]
+pub enum OnConflict {
+    /// Comes from `on_conflict_ignore()`.
+    Ignore,
+    /// No conflict clause was specified.
+    NoConflict,
+    /// Comes from `on_conflict(field1, field2).update(field3 = value)`.
+    Update(Vec<Ident>, Vec<Assignment>),
+}
+
+impl Default for OnConflict {
+    fn default() -> OnConflict {
+        OnConflict::NoConflict
+    }
 }
 
 /// An SQL ORDER BY clause.
@@ -239,6 +279,15 @@ pub enum Query {
         filter: FilterExpression,
         groups: Groups,
         joins: Vec<Join>,
+        limit: Limit,
+        order: Vec<Order>,
+        soft_delete: SoftDelete,
+        table: String,
+    },
+    Count {
+        filter: FilterExpression,
+        joins: Vec<Join>,
+        soft_delete: SoftDelete,
         table: String,
     },
     CreateTable {
@@ -246,42 +295,99 @@ pub enum Query {
     },
     Delete {
         filter: FilterExpression,
+        returning: Returning,
+        soft_delete: SoftDelete,
         table: String,
         use_pk: bool,
     },
     Drop {
         table: String,
     },
+    Exists {
+        filter: FilterExpression,
+        joins: Vec<Join>,
+        soft_delete: SoftDelete,
+        table: String,
+    },
     Insert {
         assignments: Vec<Assignment>,
+        on_conflict: OnConflict,
+        returning: Returning,
+        table: String,
+    },
+    InsertMany {
+        rows: Expression,
         table: String,
     },
     Select {
+        distinct: Distinct,
         filter: FilterExpression,
         get: bool,
         joins: Vec<Join>,
         limit: Limit,
         order: Vec<Order>,
+        soft_delete: SoftDelete,
         table: String,
         use_pk: bool,
     },
     Update {
         assignments: Vec<Assignment>,
         filter: FilterExpression,
+        returning: Returning,
+        soft_delete: SoftDelete,
         table: String,
         use_pk: bool,
     },
 }
 
+/// An SQL RETURNING clause.
+#[derive(Clone, Debug)]
+pub enum Returning {
+    /// Comes from `returning(field1, field2)`.
+    Fields(Vec<Ident>),
+    /// No returning was specified.
+    NoReturning,
+    /// Comes from `returning()`.
+    Row,
+}
+
+impl Default for Returning {
+    fn default() -> Returning {
+        Returning::NoReturning
+    }
+}
+
+/// The rows to select in a table with a `#[tql(soft_delete)]` field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoftDelete {
+    /// No method was specified: the deleted rows are excluded.
+    NotDeleted,
+    /// Comes from `only_deleted()`.
+    OnlyDeleted,
+    /// Comes from `with_deleted()`.
+    WithDeleted,
+}
+
+impl Default for SoftDelete {
+    fn default() -> SoftDelete {
+        SoftDelete::NotDeleted
+    }
+}
+
 /// The type of the query.
 pub enum QueryType {
     AggregateMulti,
     AggregateOne,
+    Count,
     Create,
     Exec,
+    Exists,
+    InsertMany,
     InsertOne,
     SelectMulti,
     SelectOne,
+    SelectOptional,
+    Upsert,
 }
 
 /// An SQL field with its type.
@@ -294,15 +400,30 @@ pub struct TypedField {
 /// Get the query type.
 pub fn query_type(query: &Query) -> QueryType {
     match *query {
-        Query::Aggregate { ref groups, .. } => {
-            if !groups.is_empty() {
+        Query::Aggregate { ref groups, ref limit, .. } => {
+            if let Limit::Index(_) = *limit {
+                QueryType::AggregateOne
+            }
+            else if !groups.is_empty() {
                 QueryType::AggregateMulti
             }
             else {
                 QueryType::AggregateOne
             }
         },
-        Query::Insert { .. } => QueryType::InsertOne,
+        Query::Count { .. } => QueryType::Count,
+        Query::Exists { .. } => QueryType::Exists,
+        Query::Insert { ref on_conflict, ref returning, .. } => {
+            match (on_conflict, returning) {
+                (&OnConflict::NoConflict, &Returning::NoReturning) => QueryType::InsertOne,
+                (_, &Returning::NoReturning) => QueryType::Upsert,
+                // NOTE: the returned rows are decoded like the rows of a select query.
+                (&OnConflict::NoConflict, _) => QueryType::SelectOne,
+                // No row is returned when the conflicting row is ignored.
+                _ => QueryType::SelectOptional,
+            }
+        },
+        Query::InsertMany { .. } => QueryType::InsertMany,
         Query::Select { get, ref limit, .. } => {
             let mut typ = QueryType::SelectMulti;
             if get {
@@ -314,7 +435,10 @@ pub fn query_type(query: &Query) -> QueryType {
             typ
         },
         Query::CreateTable { .. } => QueryType::Create,
-        Query::Delete { .. } | Query::Drop { .. } | Query::Update { .. } => QueryType::Exec,
+        Query::Delete { returning: Returning::NoReturning, .. } |
+            Query::Update { returning: Returning::NoReturning, .. } => QueryType::Exec,
+        Query::Delete { .. } | Query::Update { .. } => QueryType::SelectMulti,
+        Query::Drop { .. } => QueryType::Exec,
     }
 }
 
@@ -324,6 +448,56 @@ pub struct WithSpan<T> {
     pub span: Span,
 }
 
+/// Check if the expression is the `None` literal.
+pub fn is_none_literal(expr: &Expr) -> bool {
+    if let Expr::Path(ref path) = *expr {
+        return path.qself.is_none() && path.path.segments.len() == 1 &&
+            path.path.segments.first().expect("first segment in path").value().ident == "None";
+    }
+    false
+}
+
+/// Get the inner expression of a `Some(expr)` expression.
+pub fn some_inner_expr(expr: &Expr) -> Option<&Expr> {
+    if let Expr::Call(ExprCall { ref func, ref args, .. }) = *expr {
+        if let Expr::Path(ref path) = **func {
+            let is_some = path.qself.is_none() && path.path.segments.len() == 1 &&
+                path.path.segments.first().expect("first segment in path").value().ident == "Some";
+            if is_some && args.len() == 1 {
+                return args.first().map(|arg| *arg.value());
+            }
+        }
+    }
+    None
+}
+
+/// Get the operator giving the same result when its operands are swapped.
+pub fn mirror_operator(operator: RelationalOperator) -> RelationalOperator {
+    // NOTE: GreaterThan is converted to >= and GreaterThanEqual to >.
+    match operator {
+        RelationalOperator::Equal => RelationalOperator::Equal,
+        RelationalOperator::LesserThan => RelationalOperator::GreaterThanEqual,
+        RelationalOperator::LesserThanEqual => RelationalOperator::GreaterThan,
+        RelationalOperator::NotEqual => RelationalOperator::NotEqual,
+        RelationalOperator::GreaterThan => RelationalOperator::LesserThanEqual,
+        RelationalOperator::GreaterThanEqual => RelationalOperator::LesserThan,
+    }
+}
+
+/// Get the fields (`field` or `related_field.field`) used in an aggregate argument, in order.
+pub fn aggregate_fields(expr: &Expr) -> Vec<&Expr> {
+    match *expr {
+        Expr::Binary(ref bin) => {
+            let mut fields = aggregate_fields(&bin.left);
+            fields.extend(aggregate_fields(&bin.right));
+            fields
+        },
+        Expr::Field(_) | Expr::Path(_) => vec![expr],
+        Expr::Paren(ref paren) => aggregate_fields(&paren.expr),
+        _ => vec![],
+    }
+}
+
 /// Get the position of the first token of the expression.
 pub fn first_token_span(expr: &Expr) -> Span {
     let tokens: TokenStream = expr.into_tokens().into();
@@ -24,19 +24,272 @@
 use std::collections::BTreeMap;
 use std::fmt::Write;
 
+use proc_macro2::{Group, Literal, TokenStream, TokenTree};
 use syn::{
     self,
     AngleBracketedGenericArguments,
+    Attribute,
     Field,
+    ItemStruct,
+    Lit,
+    Meta,
+    MetaList,
+    MetaNameValue,
+    NestedMeta,
     PathArguments,
     TypePath,
 };
 use syn::spanned::Spanned;
 
 use ast::WithSpan;
+use error::{Error, Result, res};
 use state::{BothTypes, SqlFields};
 use types::Type;
 
+/// An attribute of a field of the table struct, like `#[tql(version)]`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldAttribute {
+    /// The field is assigned the current date/time when the row is inserted or updated.
+    AutoNow,
+    /// The field is assigned the current date/time when the row is inserted.
+    AutoNowAdd,
+    /// The string field cannot have more than this number of characters.
+    MaxLength(u64),
+    /// The primary key is generated by the database with `gen_random_uuid()`.
+    GenRandomUuid,
+    /// The decimal field is saved with this precision (number of digits) and scale (number of
+    /// digits after the decimal point).
+    Numeric(u64, u64),
+    /// The numeric field must be between the optional minimum and maximum (inclusive).
+    Range(Option<f64>, Option<f64>),
+    /// The field is the primary key of the table (for the types other than `PrimaryKey`).
+    PrimaryKey,
+    /// The string field must match this regular expression.
+    Regex(String),
+    /// The field is assigned the current date/time instead of deleting the row.
+    SoftDelete,
+    /// The field is a version number incremented by the save() method.
+    Version,
+}
+
+/// An attribute of the table struct, like `#[tql(hooks)]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StructAttribute {
+    /// The struct implements `Hooks` itself instead of using the no-op implementation.
+    Hooks,
+}
+
+/// Get the attributes of a field of the table struct.
+pub fn field_attributes(field: &Field) -> Result<Vec<WithSpan<FieldAttribute>>> {
+    tql_attributes(&field.attrs, |meta| {
+        match *meta {
+            Meta::Word(ref word) =>
+                match word.as_ref() {
+                    "auto_now" => Some(FieldAttribute::AutoNow),
+                    "auto_now_add" => Some(FieldAttribute::AutoNowAdd),
+                    "gen_random_uuid" => Some(FieldAttribute::GenRandomUuid),
+                    "primary_key" => Some(FieldAttribute::PrimaryKey),
+                    "soft_delete" => Some(FieldAttribute::SoftDelete),
+                    "version" => Some(FieldAttribute::Version),
+                    _ => None,
+                },
+            Meta::NameValue(MetaNameValue { ref ident, ref lit, .. }) =>
+                match (ident.as_ref(), lit) {
+                    ("max_length", &Lit::Int(ref int)) => Some(FieldAttribute::MaxLength(int.value())),
+                    ("regex", &Lit::Str(ref string)) => Some(FieldAttribute::Regex(string.value())),
+                    _ => None,
+                },
+            Meta::List(MetaList { ref ident, ref nested, .. }) if ident == "numeric" => {
+                let mut precision = None;
+                let mut scale = 0;
+                for meta in nested {
+                    match *meta {
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref ident, lit: Lit::Int(ref int), .. })) =>
+                            match ident.as_ref() {
+                                "precision" => precision = Some(int.value()),
+                                "scale" => scale = int.value(),
+                                _ => return None,
+                            },
+                        _ => return None,
+                    }
+                }
+                precision.map(|precision| FieldAttribute::Numeric(precision, scale))
+            },
+            Meta::List(MetaList { ref ident, ref nested, .. }) if ident == "range" => {
+                let mut min = None;
+                let mut max = None;
+                for meta in nested {
+                    match *meta {
+                        NestedMeta::Meta(Meta::NameValue(MetaNameValue { ref ident, ref lit, .. })) => {
+                            let bound = Some(lit_to_f64(lit)?);
+                            match ident.as_ref() {
+                                "min" => min = bound,
+                                "max" => max = bound,
+                                _ => return None,
+                            }
+                        },
+                        _ => return None,
+                    }
+                }
+                if min.is_none() && max.is_none() {
+                    return None;
+                }
+                Some(FieldAttribute::Range(min, max))
+            },
+            _ => None,
+        }
+    })
+}
+
+/// Convert the literal bound of a range to a number.
+/// A negative bound, like `min = -10`, was converted to a string by negative_numbers_to_strings().
+fn lit_to_f64(lit: &Lit) -> Option<f64> {
+    match *lit {
+        Lit::Float(ref float) => Some(float.value()),
+        Lit::Int(ref int) => Some(int.value() as f64),
+        Lit::Str(ref string) => string.value().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Get the attributes of the table struct.
+pub fn struct_attributes(item_struct: &ItemStruct) -> Result<Vec<WithSpan<StructAttribute>>> {
+    tql_attributes(&item_struct.attrs, |meta| {
+        match *meta {
+            Meta::Word(ref word) if word == "hooks" => Some(StructAttribute::Hooks),
+            _ => None,
+        }
+    })
+}
+
+/// Get the items of the `#[tql(...)]` attributes converted by `convert`.
+fn tql_attributes<A, F>(attrs: &[Attribute], convert: F) -> Result<Vec<WithSpan<A>>>
+    where F: Fn(&Meta) -> Option<A>
+{
+    let mut attributes = vec![];
+    let mut errors = vec![];
+    for attribute in attrs {
+        let is_tql_attribute = attribute.path.segments.len() == 1 &&
+            attribute.path.segments.first().expect("first segment in path").value().ident == "tql";
+        if !is_tql_attribute {
+            continue;
+        }
+        let mut attribute = attribute.clone();
+        attribute.tts = negative_numbers_to_strings(attribute.tts);
+        if let Some(Meta::List(MetaList { ref nested, .. })) = attribute.interpret_meta() {
+            for meta in nested {
+                if let NestedMeta::Meta(ref meta) = *meta {
+                    if let Some(attribute) = convert(meta) {
+                        attributes.push(WithSpan {
+                            node: attribute,
+                            span: meta.span(),
+                        });
+                        continue;
+                    }
+                }
+                errors.push(Error::new("unknown tql attribute", meta.span()));
+            }
+        }
+        else {
+            errors.push(Error::new("cannot parse tql attribute", attribute.span()));
+        }
+    }
+    res(attributes, errors)
+}
+
+/// Convert the negative numbers, like `-10`, to string literals, like `"-10"`, because the value of
+/// an attribute item must be a literal.
+fn negative_numbers_to_strings(tokens: TokenStream) -> TokenStream {
+    let mut result = vec![];
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let token =
+            match token {
+                TokenTree::Group(group) => {
+                    let mut new_group = Group::new(group.delimiter(), negative_numbers_to_strings(group.stream()));
+                    new_group.set_span(group.span());
+                    TokenTree::Group(new_group)
+                },
+                TokenTree::Op(op) => {
+                    let is_number =
+                        match tokens.peek() {
+                            Some(&TokenTree::Literal(ref literal)) if op.op() == '-' =>
+                                literal.to_string().starts_with(|character: char| character.is_digit(10)),
+                            _ => false,
+                        };
+                    if is_number {
+                        let number = tokens.next().expect("number").to_string();
+                        let mut string = Literal::string(&format!("-{}", number));
+                        string.set_span(op.span());
+                        TokenTree::Literal(string)
+                    }
+                    else {
+                        TokenTree::Op(op)
+                    }
+                },
+                token => token,
+            };
+        result.push(token);
+    }
+    result.into_iter().collect()
+}
+
+/// Check if the field is assigned automatically by the database (auto_now or auto_now_add).
+pub fn is_automatic_field(field: &Field) -> bool {
+    has_attribute(field, FieldAttribute::AutoNow) || has_attribute(field, FieldAttribute::AutoNowAdd)
+}
+
+/// Check if the field is the primary key of the table (of type `PrimaryKey` or with the
+/// primary_key attribute).
+pub fn is_primary_key(field: &Field) -> bool {
+    field_ty_to_type(&field.ty).node == Type::Serial || has_attribute(field, FieldAttribute::PrimaryKey)
+}
+
+/// Check if the field is a primary key generated by the database (of type `PrimaryKey` or with the
+/// gen_random_uuid attribute), so that it is not inserted.
+pub fn is_generated_primary_key(field: &Field) -> bool {
+    field_ty_to_type(&field.ty).node == Type::Serial || has_attribute(field, FieldAttribute::GenRandomUuid)
+}
+
+/// Check if the field has the attribute.
+pub fn has_attribute(field: &Field, attribute: FieldAttribute) -> bool {
+    field_attributes(field)
+        .map(|attributes| attributes.iter().any(|attr| attr.node == attribute))
+        .unwrap_or(false)
+}
+
+/// Get the precision and scale of the numeric attribute of the field.
+pub fn numeric_attribute(field: &Field) -> Option<(u64, u64)> {
+    field_attributes(field)
+        .ok()
+        .and_then(|attributes| attributes.into_iter()
+            .filter_map(|attribute| match attribute.node {
+                FieldAttribute::Numeric(precision, scale) => Some((precision, scale)),
+                _ => None,
+            })
+            .next())
+}
+
+/// Get the validation attributes (max_length, range and regex) of the field.
+pub fn validation_attributes(field: &Field) -> Vec<FieldAttribute> {
+    field_attributes(field)
+        .map(|attributes| attributes.into_iter()
+            .map(|attribute| attribute.node)
+            .filter(|attribute| match *attribute {
+                FieldAttribute::MaxLength(_) | FieldAttribute::Range(_, _) | FieldAttribute::Regex(_) => true,
+                _ => false,
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+/// Check if the table struct has the attribute.
+pub fn has_struct_attribute(item_struct: &ItemStruct, attribute: StructAttribute) -> bool {
+    struct_attributes(item_struct)
+        .map(|attributes| attributes.iter().any(|attr| attr.node == attribute))
+        .unwrap_or(false)
+}
+
 /// Convert a type from the Rust AST to the SQL `Type`.
 //#[allow(cmp_owned)]
 pub fn field_ty_to_type(ty: &syn::Type) -> WithSpan<Type> {
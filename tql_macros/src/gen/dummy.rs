@@ -20,9 +20,10 @@
  */
 
 use quote::Tokens;
-use syn::{Expr, Ident};
+use syn::{self, Expr, Ident};
 
 use super::BackendGen;
+use types::Type;
 use SqlQueryWithArgs;
 
 pub struct DummyBackend {}
@@ -33,6 +34,10 @@ pub fn create_backend() -> DummyBackend {
 }
 
 impl BackendGen for DummyBackend {
+    fn connection_type(&self, _table_ident: &Ident) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
     fn convert_index(&self, _index: usize) -> Tokens {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
@@ -41,25 +46,55 @@ impl BackendGen for DummyBackend {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
+    fn enum_impls(&self, _enum_ident: &Ident, _to_label: Tokens, _from_label: Tokens) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn error_type(&self, _table_ident: &Ident) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
     fn gen_query_expr(&self, _connection_expr: Tokens, _args: &SqlQueryWithArgs, _args_expr: Tokens, _struct_expr: Tokens,
                       _aggregate_struct: Tokens, _aggregate_expr: Tokens) -> Tokens
     {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
+    fn gen_select_multi_expr(&self, _connection_expr: Tokens, _sql_query: &Tokens, _args_expr: Tokens,
+                             _struct_expr: Tokens) -> Tokens
+    {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
     fn int_literal(&self, _num: usize) -> Expr {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
+    fn is_to_sql_type(&self, _typ: &Type) -> bool {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn newtype_impls(&self, _type_ident: &Ident, _field_type: &syn::Type) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn row_count_type(&self) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
     fn row_type_ident(&self, _table_ident: &Ident) -> Tokens {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
-    fn to_sql(&self, _primary_key_ident: &Ident) -> Tokens {
+    fn to_sql(&self, _primary_key_value: Tokens) -> Tokens {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 
     fn to_sql_impl(&self, _table_ident: &Ident, _to_sql_code: Tokens) -> Tokens {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
+
+    fn to_sql_trait(&self, _table_ident: &Ident) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
 }
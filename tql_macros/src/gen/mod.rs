@@ -26,22 +26,33 @@ mod postgres;
 #[cfg(feature = "rusqlite")]
 mod sqlite;
 
+use std::iter;
+
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::Tokens;
 use rand::{self, Rng};
+use regex::Regex;
 use syn::{
     self,
     Expr,
+    ExprLit,
+    ExprUnary,
     Field,
     Fields,
     FieldsNamed,
+    FieldsUnnamed,
     Ident,
+    Index,
+    ItemEnum,
     ItemStruct,
+    Lit,
+    UnOp,
     parse,
 };
+use syn::{Path, TypePath};
 #[cfg(feature = "unstable")]
-use syn::{AngleBracketedGenericArguments, LitStr, Path, TypePath};
+use syn::{AngleBracketedGenericArguments, LitStr};
 #[cfg(feature = "unstable")]
 use syn::PathArguments::AngleBracketed;
 use syn::punctuated::Punctuated;
@@ -50,20 +61,53 @@ use syn::token::Comma;
 
 use ast::{
     Aggregate,
+    Assignment,
+    AssignmentOperator,
+    Distinct,
+    Expression,
+    Filter,
+    FilterExpression,
+    FilterValue,
+    Filters,
     Join,
+    Limit,
+    LogicalOperator,
+    OnConflict,
+    Query,
+    RelationalOperator,
+    Returning,
+    SoftDelete,
     TypedField,
+    WithSpan,
+    query_type,
+    some_inner_expr,
+};
+use attribute::{
+    FieldAttribute,
+    StructAttribute,
+    field_attributes,
+    field_ty_to_type,
+    fields_vec_to_hashmap,
+    has_attribute,
+    has_struct_attribute,
+    is_automatic_field,
+    is_generated_primary_key,
+    is_primary_key,
+    numeric_attribute,
+    struct_attributes,
+    validation_attributes,
 };
-use attribute::{field_ty_to_type, fields_vec_to_hashmap};
 use error::{Error, Result, res};
 use plugin::{new_ident, string_literal};
+use raw::RawQuery;
 #[cfg(feature = "postgres")]
 use self::postgres::create_backend;
 #[cfg(feature = "rusqlite")]
 use self::sqlite::create_backend;
 #[cfg(not(any(feature = "rusqlite", feature = "postgres")))]
 use self::dummy::create_backend;
-use sql::fields_to_sql;
-use state::SqlFields;
+use sql::{EnumSql, checks_to_sql, enum_to_sql, fields_to_sql};
+use state::{SqlFields, methods_singleton};
 use string::token_to_string;
 use types::{
     Type,
@@ -116,9 +160,20 @@ pub fn table_methods(item_struct: &ItemStruct) -> Tokens {
         let row_type_ident = backend.row_type_ident(&table_ident);
         let delta_type = backend.delta_type();
         let row_ident = Ident::new("__tql_item_row", Span::call_site());
+        let primary_key_type = named.iter()
+            .find(|field| is_primary_key(field))
+            .map(|field| {
+                let typ = &field.ty;
+                quote! { #typ }
+            })
+            .unwrap_or_else(|| quote_spanned! { table_ident.span() =>
+                ::tql::PrimaryKey
+            });
 
         quote! {
             unsafe impl #trait_ident for #table_ident {
+                type PrimaryKey = #primary_key_type;
+
                 const FIELD_COUNT: #delta_type = #field_count;
 
                 fn _tql_default() -> Self {
@@ -155,7 +210,19 @@ pub fn tosql_impl(item_struct: &ItemStruct, primary_key_field: Option<String>) -
     let to_sql_code =
         if let Some(pk) = primary_key_field {
             let primary_key_ident = Ident::new(&pk, Span::call_site());
-            backend.to_sql(&primary_key_ident)
+            let primary_key_type = match item_struct.fields {
+                Fields::Named(FieldsNamed { ref named, .. }) =>
+                    named.iter()
+                        .find(|field| field.ident == Some(primary_key_ident))
+                        .map(|field| field_ty_to_type(&field.ty).node),
+                _ => None,
+            };
+            let value =
+                match primary_key_type.as_ref().and_then(value_wrapper) {
+                    Some(wrapper) => quote! { #wrapper::from_ref(&self.#primary_key_ident) },
+                    None => quote! { self.#primary_key_ident },
+                };
+            backend.to_sql(value)
         }
         else {
             quote! {
@@ -177,6 +244,740 @@ pub fn tosql_impl(item_struct: &ItemStruct, primary_key_field: Option<String>) -
     }
 }
 
+/// Get the variants of the enum deriving SqlEnum.
+pub fn get_enum_variants(item_enum: &ItemEnum) -> Result<Vec<Ident>> {
+    let mut errors = vec![];
+    if !item_enum.generics.params.is_empty() {
+        errors.push(Error::new("SqlEnum cannot be derived for a generic enum", item_enum.generics.span()));
+    }
+    if item_enum.variants.is_empty() {
+        errors.push(Error::new("SqlEnum cannot be derived for an enum without variants", item_enum.ident.span()));
+    }
+    for variant in &item_enum.variants {
+        if let Fields::Named(_) | Fields::Unnamed(_) = variant.fields {
+            errors.push(Error::new("the variants of a SqlEnum must not have fields", variant.span()));
+        }
+    }
+    let variants = item_enum.variants.iter()
+        .map(|variant| variant.ident)
+        .collect();
+    res(variants, errors)
+}
+
+/// Add the ToSql and FromSql implementations for the enum, as well as the macros giving its SQL
+/// type.
+/// An enum is saved in the database as the name of its variant.
+pub fn sql_enum_impl(item_enum: &ItemEnum, variants: &[Ident]) -> Tokens {
+    let enum_ident = &item_enum.ident;
+    let labels: Vec<_> = variants.iter()
+        .map(|variant| variant.to_string())
+        .collect();
+    let enum_idents = iter::repeat(enum_ident);
+    let enum_idents2 = iter::repeat(enum_ident);
+    let labels1 = &labels;
+    let labels2 = &labels;
+    let to_label = quote! {
+        match *self {
+            #(#enum_idents::#variants => #labels1,)*
+        }
+    };
+    let from_label = quote! {
+        match label {
+            #(#labels2 => Some(#enum_idents2::#variants),)*
+            _ => None,
+        }
+    };
+    let backend = create_backend();
+    let impls = backend.enum_impls(enum_ident, to_label, from_label);
+
+    let EnumSql { column_type, create_query, values_check } = enum_to_sql(&enum_ident.to_string(), &labels);
+    let check =
+        match values_check {
+            Some(values_check) => quote! {
+                concat!(" CHECK (", $column, " ", #values_check, ")")
+            },
+            None => quote! { "" },
+        };
+    let sql_type_macros = sql_type_macros(enum_ident, quote! { #column_type }, check,
+                                          &create_query.unwrap_or_default());
+    quote! {
+        #impls
+        #sql_type_macros
+    }
+}
+
+/// Get the type of the field of the newtype deriving SqlType.
+pub fn get_newtype_field_type(item_struct: &ItemStruct) -> Result<syn::Type> {
+    let mut errors = vec![];
+    if !item_struct.generics.params.is_empty() {
+        errors.push(Error::new("SqlType cannot be derived for a generic struct", item_struct.generics.span()));
+    }
+    match item_struct.fields {
+        Fields::Unnamed(FieldsUnnamed { ref unnamed, .. }) if unnamed.len() == 1 => {
+            let field = unnamed.first().expect("first field").into_value();
+            let typ = field_ty_to_type(&field.ty);
+            let backend = create_backend();
+            match typ.node {
+                Type::Custom(_) | Type::Generic | Type::Nullable(_) | Type::Serial | Type::UnsupportedType(_) |
+                    Type::UserDefined(_) =>
+                    errors.push(Error::new("the field of a SqlType must have a type supported by tql", field.ty.span())),
+                ref typ if !backend.is_to_sql_type(typ) =>
+                    errors.push(Error::new("the field of a SqlType must have a type supported by the database",
+                                           field.ty.span())),
+                _ => (),
+            }
+            res(field.ty.clone(), errors)
+        },
+        _ => {
+            errors.push(Error::new("SqlType can only be derived for a struct with one unnamed field",
+                                   item_struct.ident.span()));
+            Err(errors)
+        },
+    }
+}
+
+/// Add the ToSql and FromSql implementations for the newtype, as well as the macros giving its SQL
+/// type.
+/// A newtype is saved in the database as the value of its field.
+pub fn sql_type_impl(item_struct: &ItemStruct, field_type: &syn::Type) -> Tokens {
+    let type_ident = &item_struct.ident;
+    let backend = create_backend();
+    let impls = backend.newtype_impls(type_ident, field_type);
+    let column_type = type_to_sql(&Type::Nullable(Box::new(field_ty_to_type(field_type).node)));
+    let sql_type_macros = sql_type_macros(type_ident, quote! { concat!(#column_type) }, quote! { "" }, "");
+    quote! {
+        #impls
+        #sql_type_macros
+    }
+}
+
+/// Create the macros giving the SQL of a user-defined type: the type of the column, its CHECK
+/// constraint and the query creating the type.
+fn sql_type_macros(type_ident: &Ident, column_type: Tokens, check: Tokens, create_query: &str) -> Tokens {
+    let sql_type_macro_name = Ident::new(&format!("tql_{}_sql_type", type_ident), Span::call_site());
+    let check_macro_name = Ident::new(&format!("tql_{}_check", type_ident), Span::call_site());
+    let create_type_macro_name = Ident::new(&format!("tql_{}_create_type", type_ident), Span::call_site());
+    let trait_ident = quote_spanned! { type_ident.span() =>
+        ::tql::SqlType
+    };
+    let min_max_trait_ident = quote_spanned! { type_ident.span() =>
+        ::tql::aggregates::MinMaxType
+    };
+    quote! {
+        impl #trait_ident for #type_ident {
+        }
+
+        impl #min_max_trait_ident for #type_ident {
+            type Output = Option<#type_ident>;
+        }
+
+        #[macro_export]
+        macro_rules! #sql_type_macro_name {
+            () => { #column_type };
+        }
+
+        #[macro_export]
+        macro_rules! #check_macro_name {
+            ($column:tt) => { #check };
+        }
+
+        #[macro_export]
+        macro_rules! #create_type_macro_name {
+            () => { #create_query };
+        }
+    }
+}
+
+/// Add the tql::InsertRow implementations for the struct and for the tuple of its field types.
+/// The primary key is not part of the inserted row.
+pub fn insert_row_impls(item_struct: &ItemStruct) -> Tokens {
+    let table_ident = &item_struct.ident;
+    if let Fields::Named(FieldsNamed { ref named , .. }) = item_struct.fields {
+        let fields = insert_fields(named);
+        let backend = create_backend();
+        if !fields.iter().all(|field| backend.is_to_sql_type(&field_ty_to_type(&field.ty).node)) {
+            // NOTE: the backend cannot send some field types as parameters, so the rows of this
+            // table cannot be inserted with insert_many().
+            return quote! {};
+        }
+        let field_count = fields.len();
+        let field_types = fields.iter()
+            .map(|field| &field.ty);
+        let to_sql_trait = backend.to_sql_trait(table_ident);
+        let field_values = fields.iter()
+            .map(|field| {
+                let ident = field.ident.expect("field has name");
+                field_parameter(field, quote! { &self.#ident }, &to_sql_trait)
+            });
+        let tuple_values = fields.iter().enumerate()
+            .map(|(index, field)| {
+                let index = Index::from(index);
+                field_parameter(field, quote! { &self.#index }, &to_sql_trait)
+            });
+        let tuple_checks = fields.iter().enumerate()
+            .filter_map(|(index, field)| {
+                let index = Index::from(index);
+                field_value_checks(field, table_ident, quote! { &self.#index })
+            });
+        let trait_ident = quote_spanned! { table_ident.span() =>
+            ::tql::InsertRow
+        };
+        let validation_error = quote_spanned! { table_ident.span() =>
+            ::tql::ValidationError
+        };
+        let std_ident = quote_spanned! { table_ident.span() =>
+            ::std
+        };
+        quote! {
+            impl #trait_ident<#table_ident> for #table_ident {
+                const FIELD_COUNT: usize = #field_count;
+
+                fn to_sql_values(&self) -> Vec<&#to_sql_trait> {
+                    vec![#(#field_values),*]
+                }
+
+                fn validate(&self) -> #std_ident::result::Result<(), Vec<#validation_error>> {
+                    #table_ident::validate(self)
+                }
+            }
+
+            impl #trait_ident<#table_ident> for (#(#field_types,)*) {
+                const FIELD_COUNT: usize = #field_count;
+
+                fn to_sql_values(&self) -> Vec<&#to_sql_trait> {
+                    vec![#(#tuple_values),*]
+                }
+
+                fn validate(&self) -> #std_ident::result::Result<(), Vec<#validation_error>> {
+                    #[allow(unused_mut)]
+                    let mut errors: Vec<#validation_error> = vec![];
+                    #(#tuple_checks)*
+                    if errors.is_empty() {
+                        Ok(())
+                    }
+                    else {
+                        Err(errors)
+                    }
+                }
+            }
+        }
+    }
+    else {
+        unreachable!("Check is done in get_struct_fields()")
+    }
+}
+
+/// Add the methods to insert, save, delete and reload an instance of the table struct and the
+/// tql::TrackedTable implementation.
+pub fn instance_methods(item_struct: &ItemStruct, primary_key_field: Option<String>) -> Tokens {
+    let table_ident = &item_struct.ident;
+    if let Fields::Named(FieldsNamed { ref named , .. }) = item_struct.fields {
+        let fields = insert_fields(named);
+        let backend = create_backend();
+        let primary_key_ident =
+            match primary_key_field {
+                Some(ref pk) => Ident::new(pk, Span::call_site()),
+                None => return quote! {},
+            };
+        let primary_key = named.iter()
+            .find(|field| field.ident == Some(primary_key_ident))
+            .expect("primary key field");
+        let primary_key_type = &primary_key.ty;
+        let table = table_ident.to_string();
+        let connection_type = backend.connection_type(table_ident);
+        let error_type = backend.error_type(table_ident);
+        let row_count_type = backend.row_count_type();
+        let to_sql_trait = backend.to_sql_trait(table_ident);
+        let std_ident = quote_spanned! { table_ident.span() =>
+            ::std
+        };
+        let primary_key_filter = || FilterExpression::Filter(Filter {
+            operand1: FilterValue::PrimaryKey(table.clone()),
+            operator: RelationalOperator::Equal,
+            operand2: parse_quote! { self.#primary_key_ident },
+            mirrored: false,
+        });
+        // NOTE: the automatic fields are assigned by the queries.
+        let assigned_fields: Vec<_> = fields.iter()
+            .filter(|field| !is_automatic_field(field))
+            .collect();
+        let assignments = || assigned_fields.iter()
+            .map(|field| {
+                let ident = field.ident.expect("field has name");
+                Assignment {
+                    identifier: Some(ident),
+                    operator: WithSpan {
+                        node: AssignmentOperator::Equal,
+                        span: ident.span(),
+                    },
+                    value: parse_quote! { self.#ident },
+                }
+            })
+            .collect();
+
+        let delete_query = Query::Delete {
+            filter: primary_key_filter(),
+            returning: Returning::NoReturning,
+            soft_delete: SoftDelete::NotDeleted,
+            table: table.clone(),
+            use_pk: true,
+        };
+        let primary_key_parameter = field_parameter(primary_key, quote! { &self.#primary_key_ident }, &to_sql_trait);
+        let delete_expr = gen_method_query(table_ident, delete_query, quote! {
+            [#primary_key_parameter]
+        });
+        let reload_query = Query::Select {
+            distinct: Distinct::NoDistinct,
+            filter: primary_key_filter(),
+            get: true,
+            joins: vec![],
+            limit: Limit::NoLimit,
+            order: vec![],
+            // NOTE: a deleted row can still be reloaded.
+            soft_delete: SoftDelete::WithDeleted,
+            table: table.clone(),
+            use_pk: true,
+        };
+        let reload_expr = gen_method_query(table_ident, reload_query, quote! {
+            [#primary_key_parameter]
+        });
+        // NOTE: the foreign keys are not selected without a join, so they are None in the rows
+        // returned by the queries.
+        let foreign_key_idents: Vec<_> = fields.iter()
+            .filter(|field| token_to_string(&field.ty).starts_with("ForeignKey"))
+            .map(|field| field.ident.expect("field has name"))
+            .collect();
+        let foreign_key_idents = &foreign_key_idents;
+        let foreign_key_idents2 = foreign_key_idents;
+        let hooks_trait = quote_spanned! { table_ident.span() =>
+            ::tql::Hooks
+        };
+        let save_error = quote_spanned! { table_ident.span() =>
+            ::tql::SaveError
+        };
+        // NOTE: the insert() and save() methods of a table with validation attributes return the
+        // errors of validate().
+        let has_validations = named.iter()
+            .any(|field| !validation_attributes(field).is_empty());
+        let (validate, validation_error_type) =
+            if has_validations {
+                (quote! { self.validate()?; }, save_error.clone())
+            }
+            else {
+                (quote! {}, error_type.clone())
+            };
+        // NOTE: the struct with the hooks attribute implements the Hooks trait itself.
+        let mut impls =
+            if has_struct_attribute(item_struct, StructAttribute::Hooks) {
+                quote! {}
+            }
+            else {
+                quote! {
+                    impl #hooks_trait for #table_ident {
+                    }
+                }
+            };
+        let mut methods = quote! {
+            /// Delete the row of this instance from the table.
+            #[allow(dead_code)]
+            pub fn delete(self, connection: &#connection_type) -> #std_ident::result::Result<#row_count_type, #error_type> {
+                let count = #delete_expr?;
+                #hooks_trait::after_delete(&self, connection)?;
+                Ok(count)
+            }
+
+            /// Replace the field values of this instance by the values of its row in the table.
+            #[allow(dead_code)]
+            pub fn reload(&mut self, connection: &#connection_type) -> #std_ident::result::Result<(), #error_type> {
+                let mut item = #reload_expr?;
+                // NOTE: the foreign keys are kept since they are not reloaded.
+                #(item.#foreign_key_idents = self.#foreign_key_idents2.take();)*
+                *self = item;
+                Ok(())
+            }
+        };
+
+        // NOTE: the insert and save queries cannot send the fields whose type is not supported by
+        // the backend and TrackedTable requires the InsertRow implementation which does not exist
+        // in this case.
+        if fields.iter().all(|field| backend.is_to_sql_type(&field_ty_to_type(&field.ty).node)) {
+            let insert_query = Query::Insert {
+                assignments: assignments(),
+                on_conflict: OnConflict::NoConflict,
+                returning: Returning::NoReturning,
+                table: table.clone(),
+            };
+            let insert_parameters = assigned_fields.iter()
+                .map(|field| {
+                    let ident = field.ident.expect("field has name");
+                    field_parameter(field, quote! { &self.#ident }, &to_sql_trait)
+                });
+            let insert_expr = gen_method_query(table_ident, insert_query, quote! {
+                [#(#insert_parameters),*]
+            });
+            let version_ident = fields.iter()
+                .find(|field| has_attribute(field, FieldAttribute::Version))
+                .map(|field| field.ident.expect("field has name"));
+            // NOTE: the primary key is not assigned by save() even when it is inserted and the
+            // version is incremented by the UPDATE query.
+            let save_assignments = assigned_fields.iter()
+                .filter(|field| field.ident != Some(primary_key_ident) && field.ident != version_ident)
+                .map(|field| {
+                    let ident = field.ident.expect("field has name");
+                    Assignment {
+                        identifier: Some(ident),
+                        operator: WithSpan {
+                            node: AssignmentOperator::Equal,
+                            span: ident.span(),
+                        },
+                        value: parse_quote! { self.#ident },
+                    }
+                })
+                .collect();
+            let save_filter =
+                if let Some(version_ident) = version_ident {
+                    FilterExpression::Filters(Filters {
+                        operand1: Box::new(primary_key_filter()),
+                        operator: LogicalOperator::And,
+                        operand2: Box::new(FilterExpression::Filter(Filter {
+                            operand1: FilterValue::Identifier(table.clone(), version_ident),
+                            operator: RelationalOperator::Equal,
+                            operand2: parse_quote! { self.#version_ident },
+                            mirrored: false,
+                        })),
+                    })
+                }
+                else {
+                    primary_key_filter()
+                };
+            let save_query = Query::Update {
+                assignments: save_assignments,
+                filter: save_filter,
+                returning: Returning::NoReturning,
+                // NOTE: like reload(), save() can update a deleted row.
+                soft_delete: SoftDelete::WithDeleted,
+                table: table.clone(),
+                use_pk: true,
+            };
+            // NOTE: the parameters of the filter come after the parameters of the assignments.
+            let filter_parameters = iter::once(primary_key_parameter.clone())
+                .chain(version_ident.map(|ident| quote! { &self.#ident as &#to_sql_trait }));
+            let save_parameters = assigned_fields.iter()
+                .filter(|field| field.ident != version_ident && field.ident != Some(primary_key_ident))
+                .map(|field| {
+                    let ident = field.ident.expect("field has name");
+                    field_parameter(field, quote! { &self.#ident }, &to_sql_trait)
+                })
+                .chain(filter_parameters);
+            let save_expr =
+                if foreign_key_idents.is_empty() {
+                    gen_method_query(table_ident, save_query, quote! {
+                        [#(#save_parameters),*]
+                    })
+                }
+                else {
+                    // NOTE: the foreign keys are only updated when they are set since they are None
+                    // when the row was fetched without a join, so the query is created at runtime.
+                    let field_names = assigned_fields.iter()
+                        .map(|field| field.ident.expect("field has name"))
+                        .filter(|ident| !foreign_key_idents.contains(ident) && *ident != primary_key_ident)
+                        .map(|ident| ident.to_string());
+                    let foreign_key_names = foreign_key_idents.iter()
+                        .map(|ident| ident.to_string());
+                    let update_fields = quote_spanned! { table_ident.span() =>
+                        ::tql::update_fields
+                    };
+                    quote! {{
+                        let mut __tql_fields = vec![#(#field_names),*];
+                        #(if self.#foreign_key_idents.is_some() {
+                            __tql_fields.push(#foreign_key_names);
+                        })*
+                        #update_fields(&*self, &__tql_fields, connection)
+                    }}
+                };
+            let save_result =
+                if has_validations {
+                    quote! { Ok(#save_expr?) }
+                }
+                else {
+                    save_expr.clone()
+                };
+            let save_method =
+                if let Some(version_ident) = version_ident {
+                    quote! {
+                        /// Update all the columns of the row of this instance in the table and
+                        /// increment its version.
+                        /// The `StaleObject` error is returned when the version of the row is not
+                        /// the version of this instance anymore.
+                        #[allow(dead_code)]
+                        pub fn save(&mut self, connection: &#connection_type) -> #std_ident::result::Result<(), #save_error> {
+                            #hooks_trait::before_update(self, connection)?;
+                            #validate
+                            let count = #save_expr?;
+                            if count == 0 {
+                                return Err(#save_error::StaleObject);
+                            }
+                            self.#version_ident += 1;
+                            Ok(())
+                        }
+                    }
+                }
+                else {
+                    quote! {
+                        /// Update all the columns of the row of this instance in the table.
+                        #[allow(dead_code)]
+                        pub fn save(&mut self, connection: &#connection_type) -> #std_ident::result::Result<#row_count_type, #validation_error_type> {
+                            #hooks_trait::before_update(self, connection)?;
+                            #validate
+                            #save_result
+                        }
+                    }
+                };
+            let version_field =
+                match version_ident {
+                    Some(ident) => {
+                        let name = ident.to_string();
+                        quote! { Some(#name) }
+                    },
+                    None => quote! { None },
+                };
+            let increment_version = version_ident.map(|ident| quote! {
+                self.#ident += 1;
+            });
+            let field_names = fields.iter()
+                .map(|field| field.ident.expect("field has name").to_string());
+            let automatic_fields = fields.iter()
+                .filter(|field| is_automatic_field(field))
+                .map(|field| field.ident.expect("field has name").to_string());
+            let automatic_assignments = automatic_update_assignments(named);
+            let primary_key = primary_key_ident.to_string();
+            let tracked_table_trait = quote_spanned! { table_ident.span() =>
+                ::tql::TrackedTable
+            };
+            let validation_error = quote_spanned! { table_ident.span() =>
+                ::tql::ValidationError
+            };
+            impls = quote! {
+                #impls
+
+                impl #tracked_table_trait for #table_ident {
+                    const AUTOMATIC_ASSIGNMENTS: &'static str = #automatic_assignments;
+                    const AUTOMATIC_FIELDS: &'static [&'static str] = &[#(#automatic_fields),*];
+                    const FIELD_NAMES: &'static [&'static str] = &[#(#field_names),*];
+                    const PRIMARY_KEY: &'static str = #primary_key;
+                    const TABLE_NAME: &'static str = #table;
+                    const VERSION_FIELD: Option<&'static str> = #version_field;
+
+                    fn increment_version(&mut self) {
+                        #increment_version
+                    }
+
+                    fn validate(&self) -> #std_ident::result::Result<(), Vec<#validation_error>> {
+                        #table_ident::validate(self)
+                    }
+
+                    fn primary_key_value(&self) -> &#to_sql_trait {
+                        #primary_key_parameter
+                    }
+                }
+            };
+            methods = quote! {
+                #methods
+
+                /// Insert this instance in the table and assign the new primary key to it.
+                #[allow(dead_code)]
+                pub fn insert(&mut self, connection: &#connection_type) -> #std_ident::result::Result<#primary_key_type, #validation_error_type> {
+                    #hooks_trait::before_insert(self, connection)?;
+                    #validate
+                    let id = #insert_expr?;
+                    self.#primary_key_ident = id;
+                    #hooks_trait::after_insert(self, connection)?;
+                    Ok(id)
+                }
+
+                #save_method
+            };
+        }
+
+        quote! {
+            impl #table_ident {
+                #methods
+            }
+
+            #impls
+        }
+    }
+    else {
+        unreachable!("Check is done in get_struct_fields()")
+    }
+}
+
+/// Add the validate() method checking the fields with the max_length, range and regex attributes.
+pub fn validate_method(item_struct: &ItemStruct) -> Tokens {
+    let table_ident = &item_struct.ident;
+    if let Fields::Named(FieldsNamed { ref named , .. }) = item_struct.fields {
+        let validation_error = quote_spanned! { table_ident.span() =>
+            ::tql::ValidationError
+        };
+        let std_ident = quote_spanned! { table_ident.span() =>
+            ::std
+        };
+        let field_checks = named.iter()
+            .filter_map(|field| {
+                let ident = field.ident.expect("field has name");
+                field_value_checks(field, table_ident, quote! { &self.#ident })
+            });
+        quote! {
+            impl #table_ident {
+                /// Check that the fields respect their validation attributes.
+                #[allow(dead_code)]
+                pub fn validate(&self) -> #std_ident::result::Result<(), Vec<#validation_error>> {
+                    #[allow(unused_mut)]
+                    let mut errors: Vec<#validation_error> = vec![];
+                    #(#field_checks)*
+                    if errors.is_empty() {
+                        Ok(())
+                    }
+                    else {
+                        Err(errors)
+                    }
+                }
+            }
+        }
+    }
+    else {
+        unreachable!("Check is done in get_struct_fields()")
+    }
+}
+
+/// Get the code pushing to `errors` the validation errors of the reference to the `value` of the
+/// `field`, if the field has validation attributes.
+fn field_value_checks(field: &Field, table_ident: &Ident, value: Tokens) -> Option<Tokens> {
+    let checks = validation_checks(field, table_ident)?;
+    // NOTE: a None value is valid.
+    if let Type::Nullable(_) = field_ty_to_type(&field.ty).node {
+        Some(quote! {
+            if let Some(ref value) = *#value {
+                #checks
+            }
+        })
+    }
+    else {
+        Some(quote! {{
+            let value = #value;
+            #checks
+        }})
+    }
+}
+
+/// Get the code pushing to `errors` the validation errors of the non-null `value` of the `field`,
+/// if the field has validation attributes.
+fn validation_checks(field: &Field, table_ident: &Ident) -> Option<Tokens> {
+    let attributes = validation_attributes(field);
+    if attributes.is_empty() {
+        return None;
+    }
+    let validation_error = quote_spanned! { table_ident.span() =>
+        ::tql::ValidationError
+    };
+    let error_kind = quote_spanned! { table_ident.span() =>
+        ::tql::ValidationErrorKind
+    };
+    let regex_is_match = quote_spanned! { table_ident.span() =>
+        ::tql::regex_is_match
+    };
+    let field_name = field.ident.expect("field has name").to_string();
+    let checks = attributes.iter()
+        .map(|attribute| {
+            let (condition, kind) =
+                match *attribute {
+                    FieldAttribute::MaxLength(max_length) => {
+                        let max_length = max_length as usize;
+                        (quote! { value.chars().count() > #max_length },
+                         quote! { TooLong { max_length: #max_length } })
+                    },
+                    FieldAttribute::Range(min, max) => {
+                        let min_condition = min.map(|min| {
+                            let min = f64_to_tokens(min);
+                            quote! { (*value as f64) < #min }
+                        });
+                        let max_condition = max.map(|max| {
+                            let max = f64_to_tokens(max);
+                            quote! { (*value as f64) > #max }
+                        });
+                        let conditions = min_condition.into_iter().chain(max_condition);
+                        let min = option_to_tokens(min);
+                        let max = option_to_tokens(max);
+                        (quote! { #(#conditions)||* },
+                         quote! { OutOfRange { min: #min, max: #max } })
+                    },
+                    FieldAttribute::Regex(ref regex) =>
+                        (quote! { !#regex_is_match(#regex, value) },
+                         quote! { NoMatch { regex: #regex } }),
+                    _ => unreachable!("validation attribute"),
+                };
+            quote! {
+                if #condition {
+                    errors.push(#validation_error {
+                        field: #field_name,
+                        kind: #error_kind::#kind,
+                    });
+                }
+            }
+        });
+    Some(quote! {
+        #(#checks)*
+    })
+}
+
+fn option_to_tokens(option: Option<f64>) -> Tokens {
+    match option {
+        Some(value) => {
+            let value = f64_to_tokens(value);
+            quote! { Some(#value) }
+        },
+        None => quote! { None },
+    }
+}
+
+/// Convert a number to a literal, preceded by a minus sign if it is negative.
+fn f64_to_tokens(value: f64) -> Tokens {
+    let absolute_value = value.abs();
+    if value < 0.0 {
+        quote! { -#absolute_value }
+    }
+    else {
+        quote! { #absolute_value }
+    }
+}
+
+/// Generate the Rust code of a query executed by a method of the table struct.
+/// The connection is the `connection` parameter of the method.
+fn gen_method_query(table_ident: &Ident, query: Query, args_expr: Tokens) -> Tokens {
+    let args = SqlQueryWithArgs {
+        aggregate_calls: vec![],
+        aggregates: vec![],
+        arguments: vec![],
+        filter_method_calls: vec![],
+        idents: vec![],
+        #[cfg(feature = "unstable")]
+        insert_call_span: None,
+        insert_idents: None,
+        joins: vec![],
+        limit_exprs: vec![],
+        literal_arguments: vec![],
+        literal_assignments: vec![],
+        query_type: query_type(&query),
+        returning: Returning::NoReturning,
+        sql: query.to_tokens(),
+        stable_macro_query: quote! {},
+        table_name: table_ident.clone(),
+    };
+    let struct_expr = create_struct(table_ident, &[]);
+    let backend = create_backend();
+    backend.gen_query_expr(quote! { connection }, &args, args_expr, struct_expr, quote! {}, quote! {})
+}
+
 fn create_debug_impl(item_struct: &ItemStruct) -> Tokens {
     let table_ident = &item_struct.ident;
     let table_name = table_ident.to_string();
@@ -228,8 +1029,14 @@ pub fn generate_errors(errors: Vec<Error>) -> TokenStream {
 
 /// Generate the Rust code from the SQL query.
 pub(crate) fn gen_query(args: &SqlQueryWithArgs, connection_expr: Tokens) -> (TokenStream, Vec<Tokens>) {
-    let struct_expr = create_struct(&args.table_name, &args.joins);
-    let (aggregate_struct, aggregate_expr) = gen_aggregate_struct(&args.aggregates);
+    let struct_expr =
+        if let Returning::Fields(ref fields) = args.returning {
+            create_returning_fields(&args.table_name, fields)
+        }
+        else {
+            create_struct(&args.table_name, &args.joins)
+        };
+    let (aggregate_struct, aggregate_expr) = gen_aggregate_struct(&args.table_name, &args.aggregates);
     let (args_expr, metavars) = typecheck_arguments(args);
     let backend = create_backend();
     let tokens = backend.gen_query_expr(connection_expr, args, args_expr, struct_expr, aggregate_struct,
@@ -237,6 +1044,16 @@ pub(crate) fn gen_query(args: &SqlQueryWithArgs, connection_expr: Tokens) -> (To
     (tokens.into(), metavars)
 }
 
+/// Generate the Rust code from the raw SQL query.
+pub(crate) fn gen_raw_query(query: &RawQuery, connection_expr: Tokens, arg_refs: &[Tokens]) -> TokenStream {
+    let struct_expr = create_struct(&query.table_name, &[]);
+    let sql_query = &query.query;
+    let sql_query = quote! { #sql_query };
+    let args_expr = quote! { [#(#arg_refs),*] };
+    let backend = create_backend();
+    backend.gen_select_multi_expr(connection_expr, &sql_query, args_expr, struct_expr).into()
+}
+
 /// Create the struct expression needed by the generated code.
 fn create_struct(table_ident: &Ident, joins: &[Join]) -> Tokens {
     let row_ident = quote! { __tql_item_row };
@@ -258,23 +1075,103 @@ fn create_struct(table_ident: &Ident, joins: &[Join]) -> Tokens {
     }}
 }
 
+/// Create the expression of the fields returned by the returning() method: a value or a tuple.
+fn create_returning_fields(table_ident: &Ident, fields: &[Ident]) -> Tokens {
+    let backend = create_backend();
+    let row_ident = quote! { __tql_item_row };
+    let get_macro_name = Ident::new(&format!("tql_{}_get", table_ident), Span::call_site());
+    let values: Vec<_> = fields.iter().enumerate()
+        .map(|(index, field)| {
+            let index = backend.int_literal(index);
+            quote! { #get_macro_name!(#field, #row_ident, #index) }
+        })
+        .collect();
+    let table_fields: Vec<_> = fields.iter()
+        .map(|field| quote! { _table.#field })
+        .collect();
+    let (value, table_value) =
+        if fields.len() == 1 {
+            (values[0].clone(), table_fields[0].clone())
+        }
+        else {
+            (quote! { (#(#values),*) }, quote! { (#(#table_fields),*) })
+        };
+    quote_spanned! { table_ident.span() => {
+        let __tql_value = #value;
+        {
+            // NOTE: hack to get the types of the values from the types of the fields.
+            fn same_type<T>(_value1: &T, _value2: &T) {}
+            let _tql_closure = || {
+                let _table = <#table_ident as ::tql::SqlTable>::_tql_default();
+                same_type(&__tql_value, &#table_value);
+            };
+        }
+        __tql_value
+    }}
+}
+
+/// Convert the argument of an aggregate function to a Rust expression using the fields of the table
+/// `instance`.
+pub fn aggregate_argument_to_rust(expression: &Expression, instance: &Tokens) -> Tokens {
+    match *expression {
+        Expr::Binary(ref bin) => {
+            let left = aggregate_argument_to_rust(&bin.left, instance);
+            let right = aggregate_argument_to_rust(&bin.right, instance);
+            let operator = &bin.op;
+            quote! {
+                (#left #operator #right)
+            }
+        },
+        Expr::Field(ref field) => {
+            let base = &field.base;
+            let member = &field.member;
+            quote! {
+                #instance.#base.as_ref().unwrap().#member
+            }
+        },
+        Expr::Paren(ref paren) => aggregate_argument_to_rust(&paren.expr, instance),
+        Expr::Path(ref path) => quote! {
+            #instance.#path
+        },
+        _ => quote! {
+            #expression
+        },
+    }
+}
+
 /// Generate the aggregate struct and struct expression.
-fn gen_aggregate_struct(aggregates: &[Aggregate]) -> (Tokens, Tokens) {
+/// The struct is generic over the types of its fields: they are inferred from the signature of the
+/// aggregate functions in `tql::aggregates`.
+fn gen_aggregate_struct(table_ident: &Ident, aggregates: &[Aggregate]) -> (Tokens, Tokens) {
     let mut aggregate_field_idents = vec![];
     let mut aggregate_field_values = vec![];
     let mut def_field_idents = vec![];
+    let mut type_params = vec![];
     let backend = create_backend();
+    let tql_ident = quote_spanned! { Span::call_site() =>
+        ::tql
+    };
+    let instance = quote! {
+        <#table_ident as #tql_ident::SqlTable>::_tql_default()
+    };
     for (index, aggregate) in aggregates.iter().enumerate() {
+        type_params.push(Ident::from(format!("T{}", index)));
         let index = backend.convert_index(index);
         let field_name = aggregate.result_name.clone();
+        let function = Ident::new(&aggregate.function, Span::call_site());
+        let argument = aggregate_argument_to_rust(aggregate.argument.as_ref().expect("Aggregate argument"), &instance);
         aggregate_field_idents.push(field_name.clone());
-        aggregate_field_values.push(quote! { __tql_item_row.get(#index) });
+        aggregate_field_values.push(quote! {
+            #tql_ident::aggregates::infer(__tql_item_row.get(#index), ||
+                #tql_ident::aggregates::#function(&#argument))
+        });
         def_field_idents.push(field_name);
     }
     let struct_ident = new_ident("Aggregate");
+    let type_params2 = type_params.clone();
     (quote! {
-        struct #struct_ident {
-            #(#def_field_idents: f64),* // TODO: choose the type from the aggregate function?
+        struct #struct_ident<#(#type_params),*> {
+            #(#def_field_idents: #type_params2),*
         }
     },
     quote! {{
@@ -284,6 +1181,64 @@ fn gen_aggregate_struct(aggregates: &[Aggregate]) -> (Tokens, Tokens) {
     }})
 }
 
+/// Check that the type `type_name` of a field derives SqlEnum or SqlType.
+#[cfg_attr(not(feature = "unstable"), allow(unused_variables))]
+fn check_sql_type(type_name: &str, span: Span) -> TokenStream {
+    let type_ident = new_ident(type_name);
+    let struct_ident = new_ident(&format!("CheckSqlType{}", rand_string()));
+    #[cfg(feature = "unstable")]
+    let mut code: TokenStream;
+    #[cfg(not(feature = "unstable"))]
+    let code: TokenStream;
+    code = quote! {
+        #[allow(dead_code)]
+        struct #struct_ident where #type_ident: ::tql::SqlType {
+            field: #type_ident,
+        }
+    }.into();
+    #[cfg(feature = "unstable")]
+    {
+        // NOTE: position the trait at this position so that the error message points on the type.
+        code = respan_with(code, span.unstable());
+    }
+    code
+}
+
+/// Check that the type named like a type from another crate (e.g. `Uuid`) is this type, which has
+/// the tql type `target`.
+#[cfg_attr(not(feature = "unstable"), allow(unused_variables))]
+fn check_external_type(path: &Path, target: &str, span: Span) -> TokenStream {
+    let target_ident = new_ident(target);
+    let struct_ident = new_ident(&format!("CheckExternalType{}", rand_string()));
+    #[cfg(feature = "unstable")]
+    let mut code: TokenStream;
+    #[cfg(not(feature = "unstable"))]
+    let code: TokenStream;
+    code = quote! {
+        #[allow(dead_code)]
+        struct #struct_ident where #path: ::tql::ToTqlType<Target = ::tql::#target_ident> {
+            field: #path,
+        }
+    }.into();
+    #[cfg(feature = "unstable")]
+    {
+        // NOTE: position the trait at this position so that the error message points on the type.
+        code = respan_with(code, span.unstable());
+    }
+    code
+}
+
+/// Get the path of the type of the values of a field, without the Option and Vec wrappers.
+fn value_type_path(path: &Path) -> &Path {
+    let segment = path.segments.last().expect("last segment of path").into_value();
+    if segment.ident == "Option" || segment.ident == "Vec" {
+        if let Some(path) = get_type_parameter_as_path(&segment.arguments) {
+            return value_type_path(path);
+        }
+    }
+    path
+}
+
 /// Get the fields from the struct (also returns the ToSql implementations to check that the types
 /// used for ForeignKey have a #[derive(SqlTable)]).
 /// Also check if the field types from the struct are supported types.
@@ -303,21 +1258,158 @@ pub fn get_struct_fields(item_struct: &ItemStruct) -> (Result<SqlFields>, Option
             Fields::Named(FieldsNamed { ref named , .. }) => named.into_iter().cloned().collect(),
             _ => return (Err(vec![Error::new("Expected normal struct, found", position)]), None, empty_token_stream()), // TODO: improve this message.
         };
+    if let Err(attribute_errors) = struct_attributes(item_struct) {
+        errors.extend(attribute_errors);
+    }
     let mut primary_key_count = 0;
+    let mut soft_delete_count = 0;
+    let mut version_count = 0;
     for field in &fields {
         if let Some(field_ident) = field.ident {
             #[cfg(feature = "unstable")]
             let field_type = &field.ty;
             let field_name = field_ident.to_string();
+            let field_path =
+                match field.ty {
+                    syn::Type::Path(TypePath { ref path, .. }) => Some(path),
+                    _ => None,
+                };
+            let has_primary_key_attribute = has_attribute(field, FieldAttribute::PrimaryKey);
+            let attributes = field_attributes(field);
             let field = field_ty_to_type(&field.ty);
+            let non_nullable_type =
+                match field.node {
+                    Type::Nullable(ref typ) => &**typ,
+                    ref typ => typ,
+                };
+            match attributes {
+                Ok(attributes) => {
+                    for attribute in attributes {
+                        match attribute.node {
+                            FieldAttribute::AutoNow | FieldAttribute::AutoNowAdd =>
+                                if current_date_time_sql(&field.node).is_none() {
+                                    errors.push(Error::new("the auto_now and auto_now_add fields must have a date or time type",
+                                                           field.span));
+                                },
+                            FieldAttribute::MaxLength(_) | FieldAttribute::Regex(_) => {
+                                match *non_nullable_type {
+                                    Type::String => (),
+                                    _ => errors.push(Error::new("the max_length and regex fields must have a string type",
+                                                                field.span)),
+                                }
+                                if let FieldAttribute::Regex(ref regex) = attribute.node {
+                                    if let Err(error) = Regex::new(regex) {
+                                        errors.push(Error::new(&format!("invalid regex: {}", error), attribute.span));
+                                    }
+                                    else if let Some(syntax) = unsupported_regex_syntax(regex) {
+                                        errors.push(Error::new(&format!("unsupported syntax in regex: {} (only the \
+                                            syntax common to Rust and PostgreSQL is supported)", syntax),
+                                            attribute.span));
+                                    }
+                                }
+                            },
+                            FieldAttribute::GenRandomUuid => {
+                                if !has_primary_key_attribute {
+                                    errors.push(Error::new("the gen_random_uuid attribute requires the primary_key attribute",
+                                                           attribute.span));
+                                }
+                            },
+                            FieldAttribute::Numeric(precision, scale) => {
+                                match *non_nullable_type {
+                                    Type::Decimal => (),
+                                    _ => errors.push(Error::new("the numeric field must have the type Decimal",
+                                                                field.span)),
+                                }
+                                if precision == 0 {
+                                    errors.push(Error::new("the precision of the numeric field must be positive",
+                                                           attribute.span));
+                                }
+                                else if scale > precision {
+                                    errors.push(Error::new("the scale of the numeric field is greater than its precision",
+                                                           attribute.span));
+                                }
+                            },
+                            FieldAttribute::PrimaryKey => {
+                                match field.node {
+                                    Type::Uuid => (),
+                                    _ => errors.push(Error::new("the primary_key field must have the type Uuid (use PrimaryKey for an integer)",
+                                                                field.span)),
+                                }
+                                primary_key_field = Some(field_name.clone());
+                                primary_key_count += 1;
+                            },
+                            FieldAttribute::Range(min, max) => {
+                                match *non_nullable_type {
+                                    Type::F32 | Type::F64 | Type::I16 | Type::I32 | Type::I64 | Type::U8 | Type::U16 |
+                                        Type::U32 | Type::U64 => (),
+                                    _ => errors.push(Error::new("the range field must have a numeric type", field.span)),
+                                }
+                                if let (Some(min), Some(max)) = (min, max) {
+                                    if min > max {
+                                        errors.push(Error::new("the minimum of the range is greater than its maximum",
+                                                               attribute.span));
+                                    }
+                                }
+                            },
+                            FieldAttribute::SoftDelete => {
+                                match field.node {
+                                    Type::Nullable(ref typ) if current_date_time_sql(typ).is_some() => (),
+                                    _ => errors.push(Error::new("the soft_delete field must have an optional date or time type",
+                                                                field.span)),
+                                }
+                                soft_delete_count += 1;
+                            },
+                            FieldAttribute::Version => {
+                                match field.node {
+                                    Type::I16 | Type::I32 | Type::I64 => (),
+                                    _ => errors.push(Error::new("the version field must be an integer (i16, i32 or i64)",
+                                                                field.span)),
+                                }
+                                version_count += 1;
+                            },
+                        }
+                    }
+                },
+                Err(attribute_errors) => errors.extend(attribute_errors),
+            }
+            // NOTE: SQLite has no array type and rusqlite cannot convert the Decimal type.
+            #[cfg(feature = "rusqlite")]
+            {
+                match *non_nullable_type {
+                    Type::Array(_) | Type::Decimal =>
+                        errors.push(Error::new(&format!("the {} type is not supported by SQLite", non_nullable_type),
+                                               field.span)),
+                    _ => (),
+                }
+            }
+            // NOTE: these types are recognized by their name, so they are checked to be the types from
+            // the other crates.
+            let value_type =
+                match *non_nullable_type {
+                    Type::Array(ref typ) => &**typ,
+                    ref typ => typ,
+                };
+            let external_type_target =
+                match *value_type {
+                    Type::Decimal => Some("TqlDecimal"),
+                    Type::Json => Some("TqlJson"),
+                    Type::Uuid => Some("TqlUuid"),
+                    _ => None,
+                };
+            if let (Some(target), Some(path)) = (external_type_target, field_path) {
+                impls = concat_token_stream(impls, check_external_type(value_type_path(path), target, field.span));
+            }
             match field.node {
                 Type::Nullable(ref inner_type) => {
-                    if let Type::UnsupportedType(ref typ) = **inner_type {
-                        errors.push(error(field.span, typ));
+                    match **inner_type {
+                        Type::UnsupportedType(ref typ) => errors.push(error(field.span, typ)),
+                        Type::UserDefined(ref typ) => impls = concat_token_stream(impls, check_sql_type(typ, field.span)),
+                        _ => (),
                     }
                 },
                 Type::UnsupportedType(ref typ) =>
                     errors.push(error(field.span, typ)),
+                Type::UserDefined(ref typ) => impls = concat_token_stream(impls, check_sql_type(typ, field.span)),
                 // NOTE: Other types are supported.
                 Type::Serial => {
                     primary_key_field = Some(field_name);
@@ -371,11 +1463,38 @@ pub fn get_struct_fields(item_struct: &ItemStruct) -> (Result<SqlFields>, Option
         1 => (), // One primary key is OK.
         _ => errors.insert(0, Error::new_warning("More than one primary key is currently not supported", position)),
     }
+    if version_count > 1 {
+        errors.push(Error::new("More than one version field found", position));
+    }
+    if soft_delete_count > 1 {
+        errors.push(Error::new("More than one soft_delete field found", position));
+    }
 
     let fields = fields_vec_to_hashmap(&fields);
     (res(fields, errors), primary_key_field, impls)
 }
 
+/// Create the macro giving the columns of the table, in the order expected by from_row(), without
+/// the table name (for the RETURNING clause).
+fn column_list_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let column_list = named.iter()
+        .filter(|field| {
+            let typ = token_to_string(&field.ty);
+            !typ.starts_with("ForeignKey")
+        })
+        .map(|field| field.ident.expect("field has name").to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let column_list = string_literal(&column_list);
+    let macro_name = Ident::new(&format!("tql_{}_column_list", table_ident), Span::call_site());
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            () => { #column_list };
+        }
+    }
+}
+
 fn field_list_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
     let field_list = named.iter()
         .filter(|field| {
@@ -395,17 +1514,251 @@ fn field_list_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> To
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
-            () => { #field_list };
+            () => { #field_list };
+        }
+    }
+}
+
+fn insert_field_list_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let fields = insert_fields(named);
+    let field_list =
+        if fields.is_empty() {
+            // NOTE: the VALUES clause of insert_many() would be empty.
+            let error = format!("insert_many() cannot be called on table {} which has no field other than \
+                                 its primary key and its automatic fields", table_ident);
+            quote! { compile_error!(#error) }
+        }
+        else {
+            let field_list = fields.iter()
+                .map(|field| field.ident.expect("field has name").to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let field_list = string_literal(&field_list);
+            quote! { #field_list }
+        };
+    let macro_name = Ident::new(&format!("tql_{}_insert_field_list", table_ident), Span::call_site());
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            () => { #field_list };
+        }
+    }
+}
+
+/// Get the fields inserted by the insert_many() method, i.e. all the fields except the primary key
+/// generated by the database and the fields with the auto_now or auto_now_add attributes, which are
+/// inserted with the current date/time.
+fn insert_fields(named: &Punctuated<Field, Comma>) -> Vec<&Field> {
+    named.iter()
+        .filter(|field| !is_generated_primary_key(field) && !is_automatic_field(field))
+        .collect()
+}
+
+/// Get the syntax of the `regex` which is not in the subset common to the regex crate, used by
+/// validate(), and to the regular expressions of PostgreSQL, used by the CHECK constraint.
+fn unsupported_regex_syntax(regex: &str) -> Option<String> {
+    // NOTE: the escapes of other letters and digits have a different meaning (like \b) or do not
+    // exist (like \p and \z) in one of the syntaxes.
+    const COMMON_ESCAPES: &str = "dDnrsStwW";
+    let mut chars = regex.chars().peekable();
+    let mut in_class = false;
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' =>
+                match chars.next() {
+                    Some(escape) if escape.is_alphanumeric() && !COMMON_ESCAPES.contains(escape) =>
+                        return Some(format!("\\{}", escape)),
+                    _ => (),
+                },
+            '[' => in_class = true,
+            ']' => in_class = false,
+            // NOTE: the flags and the named groups are not supported, only the non-capturing groups.
+            '(' if !in_class && chars.peek() == Some(&'?') => {
+                chars.next();
+                if chars.peek() != Some(&':') {
+                    return Some("(?".to_string());
+                }
+            },
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Get the SQL expression of the current date/time for a field of type `typ`.
+fn current_date_time_sql(typ: &Type) -> Option<&'static str> {
+    match *typ {
+        Type::LocalDateTime | Type::NaiveDateTime | Type::UtcDateTime => Some("CURRENT_TIMESTAMP"),
+        Type::NaiveDate => Some("CURRENT_DATE"),
+        Type::NaiveTime => Some("CURRENT_TIME"),
+        Type::Nullable(ref typ) => current_date_time_sql(typ),
+        _ => None,
+    }
+}
+
+/// Get the assignments of the automatic fields having one of the `attributes`, e.g.
+/// `("date_added", "CURRENT_TIMESTAMP")`.
+fn automatic_assignments(named: &Punctuated<Field, Comma>, attributes: &[FieldAttribute]) -> Vec<(String, &'static str)> {
+    named.iter()
+        .filter(|field| attributes.iter().any(|attribute| has_attribute(field, attribute.clone())))
+        .filter_map(|field| {
+            let value = current_date_time_sql(&field_ty_to_type(&field.ty).node)?;
+            Some((field.ident.expect("field has name").to_string(), value))
+        })
+        .collect()
+}
+
+/// Get the SQL assignments added to the updates for the fields with the auto_now attribute, e.g.
+/// `", date_modified = CURRENT_TIMESTAMP"`.
+fn automatic_update_assignments(named: &Punctuated<Field, Comma>) -> String {
+    automatic_assignments(named, &[FieldAttribute::AutoNow]).iter()
+        .map(|&(ref field, value)| format!(", {} = {}", field, value))
+        .collect()
+}
+
+/// Create the macros giving the columns and values inserted automatically and the assignments
+/// added to the updates for the fields with the auto_now or auto_now_add attributes.
+fn automatic_fields_macros(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let insert_assignments = automatic_assignments(named, &[FieldAttribute::AutoNow, FieldAttribute::AutoNowAdd]);
+    let columns: String = insert_assignments.iter()
+        .map(|&(ref field, _)| format!(", {}", field))
+        .collect();
+    let values: String = insert_assignments.iter()
+        .map(|&(_, value)| format!(", {}", value))
+        .collect();
+    let update_assignments = automatic_update_assignments(named);
+    let columns_macro_name = Ident::new(&format!("tql_{}_automatic_columns", table_ident), Span::call_site());
+    let values_macro_name = Ident::new(&format!("tql_{}_automatic_values", table_ident), Span::call_site());
+    let assignments_macro_name = Ident::new(&format!("tql_{}_automatic_assignments", table_ident),
+                                            Span::call_site());
+    quote! {
+        #[macro_export]
+        macro_rules! #columns_macro_name {
+            () => { #columns };
+        }
+
+        #[macro_export]
+        macro_rules! #values_macro_name {
+            () => { #values };
+        }
+
+        #[macro_export]
+        macro_rules! #assignments_macro_name {
+            () => { #update_assignments };
+        }
+    }
+}
+
+/// Create the macro giving the assignment incrementing the version field, which is added to the
+/// UPDATE queries.
+fn version_assignment_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_version_assignment", table_ident), Span::call_site());
+    let assignment = named.iter()
+        .find(|field| has_attribute(field, FieldAttribute::Version))
+        .map(|field| format!(", {field} = {field} + 1", field = field.ident.expect("field has name")))
+        .unwrap_or_default();
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            () => { #assignment };
+        }
+    }
+}
+
+/// Create the macro giving the start of the DELETE queries (the soft delete and the real delete)
+/// and the conditions added to the WHERE
+/// clauses to exclude (or only select) the rows deleted with the soft_delete field.
+fn soft_delete_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_soft_delete", table_ident), Span::call_site());
+    let soft_delete_field = named.iter()
+        .find(|field| has_attribute(field, FieldAttribute::SoftDelete))
+        .and_then(|field| {
+            let value = current_date_time_sql(&field_ty_to_type(&field.ty).node)?;
+            Some((field.ident.expect("field has name"), value))
+        });
+    if let Some((field, value)) = soft_delete_field {
+        let delete = format!("UPDATE {table} SET {field} = {value}", table = table_ident, field = field, value = value);
+        let hard_delete = format!("DELETE FROM {}", table_ident);
+        let not_deleted = format!("{}.{} IS NULL", table_ident, field);
+        let only_deleted = format!("{}.{} IS NOT NULL", table_ident, field);
+        let where_not_deleted = format!(" WHERE {}", not_deleted);
+        let where_only_deleted = format!(" WHERE {}", only_deleted);
+        let filter_not_deleted = format!("{} AND (", not_deleted);
+        let filter_only_deleted = format!("{} AND (", only_deleted);
+        quote! {
+            #[macro_export]
+            macro_rules! #macro_name {
+                (delete) => { #delete };
+                (hard_delete) => { #hard_delete };
+                (where_clause, not_deleted) => { #where_not_deleted };
+                (where_clause, only_deleted) => { #where_only_deleted };
+                (filter_start, not_deleted) => { #filter_not_deleted };
+                (filter_start, only_deleted) => { #filter_only_deleted };
+                (filter_end) => { ")" };
+            }
+        }
+    }
+    else {
+        let delete = format!("DELETE FROM {}", table_ident);
+        quote! {
+            #[macro_export]
+            macro_rules! #macro_name {
+                (delete) => { #delete };
+                (hard_delete) => { #delete };
+                ($clause:ident, only_deleted) => {
+                    compile_error!("only_deleted() can only be called on a table with a soft_delete field")
+                };
+                ($($tt:tt)*) => { "" };
+            }
         }
     }
 }
 
 fn create_query_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
     let mut fields_to_create = vec![];
+    let mut type_names: Vec<String> = vec![];
     for field in named {
+        let identifier = field.ident.expect("field ident").to_string();
+        let field_type = field_ty_to_type(&field.ty).node;
+        let mut typ = type_to_sql(&field_type);
+        if let Some(type_name) = user_defined_type_name(&field_type) {
+            let check_macro_name = Ident::new(&format!("tql_{}_check", type_name), Span::call_site());
+            typ = quote! { #typ, #check_macro_name!(#identifier) };
+            if !type_names.contains(&type_name.to_string()) {
+                type_names.push(type_name.to_string());
+            }
+        }
+        if let Some((precision, scale)) = numeric_attribute(field) {
+            let numeric = format!("NUMERIC({}, {})", precision, scale);
+            typ =
+                if let Type::Nullable(_) = field_type {
+                    quote! { #numeric }
+                }
+                else {
+                    quote! { #numeric, " NOT NULL" }
+                };
+        }
+        if has_attribute(field, FieldAttribute::PrimaryKey) {
+            typ = quote! { #typ, " PRIMARY KEY" };
+        }
+        if has_attribute(field, FieldAttribute::GenRandomUuid) {
+            // NOTE: SQLite has no function to generate a UUID, so the random hexadecimal digits of a
+            // version 4 UUID are concatenated: the version digit is 4 and the variant digit is one
+            // of 8, 9, A and B.
+            #[cfg(feature = "rusqlite")]
+            let default = " DEFAULT (unhex(hex(randomblob(6)) || '4' || substr(hex(randomblob(2)), 2) || \
+                substr('89AB', 1 + (random() & 3), 1) || substr(hex(randomblob(2)), 2) || hex(randomblob(6))))";
+            #[cfg(not(feature = "rusqlite"))]
+            let default = " DEFAULT gen_random_uuid()";
+            typ = quote! { #typ, #default };
+        }
+        let checks = checks_to_sql(&identifier, &validation_attributes(field));
+        if !checks.is_empty() {
+            typ = quote! { #typ, #checks };
+        }
         fields_to_create.push(TypedField {
-            identifier: field.ident.expect("field ident").to_string(),
-            typ: type_to_sql(&field_ty_to_type(&field.ty).node),
+            identifier,
+            typ,
         });
     }
     let table = table_ident.to_string();
@@ -414,11 +1767,178 @@ fn create_query_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) ->
         concat!("CREATE TABLE ", #table, " (", #fields, ")")
     };
     let macro_name = Ident::new(&format!("tql_{}_create_query", table_ident), Span::call_site());
+    let create_type_macro_names = type_names.iter()
+        .map(|type_name| Ident::new(&format!("tql_{}_create_type", type_name), Span::call_site()));
+    let types_macro_name = Ident::new(&format!("tql_{}_create_types", table_ident), Span::call_site());
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
             () => { #create_query };
         }
+
+        #[macro_export]
+        macro_rules! #types_macro_name {
+            () => { [#(#create_type_macro_names!()),*] };
+        }
+    }
+}
+
+/// Get the name of the user-defined type of a field, if any.
+fn user_defined_type_name(typ: &Type) -> Option<&str> {
+    match *typ {
+        Type::Nullable(ref typ) => user_defined_type_name(typ),
+        Type::UserDefined(ref name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Create the macro giving the SQL of the methods defined on many types (like `contains()`) when
+/// they are called on the fields of the table, since sql!() does not know the type of the fields.
+fn method_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let methods = methods_singleton();
+    let mut method_names: Vec<_> = methods.iter()
+        .filter(|&(_, methods)| methods.len() > 1)
+        .map(|(name, _)| name)
+        .collect();
+    method_names.sort();
+    let mut rules = vec![];
+    for field in named {
+        let ident = field.ident.expect("field has name");
+        let field_type = field_ty_to_type(&field.ty).node;
+        for &name in &method_names {
+            if let Some(method) = methods[name].iter()
+                .find(|method| is_method_object_type(&method.object_type, &field_type))
+            {
+                let method_ident = Ident::new(name, Span::call_site());
+                let args: Vec<_> = (1..method.argument_types.len() + 1)
+                    .map(|index| Ident::new(&format!("arg{}", index), Span::call_site()))
+                    .collect();
+                let sql =
+                    match method.template {
+                        Some(ref template) => template_to_concat(template, &ident.to_string()),
+                        None => {
+                            let error = format!("The method {} is not available on this backend", name);
+                            quote! { compile_error!(#error) }
+                        },
+                    };
+                rules.push(quote! {
+                    (#ident, #method_ident #(, $#args:expr)*) => { #sql };
+                });
+            }
+        }
+    }
+    let macro_name = Ident::new(&format!("tql_{}_method", table_ident), Span::call_site());
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#rules)*
+            // NOTE: the type checking shows the error when the method does not exist for the field.
+            ($($tt:tt)*) => { "" };
+        }
+    }
+}
+
+/// Check if a method defined on `object_type` can be called on a field of type `field_type`.
+fn is_method_object_type(object_type: &Type, field_type: &Type) -> bool {
+    match (object_type, field_type) {
+        (&Type::Array(ref object_type), &Type::Array(ref field_type)) =>
+            is_method_object_type(object_type, field_type),
+        (&Type::Generic, _) => true,
+        _ => object_type == field_type,
+    }
+}
+
+/// Convert a method template to a concat!() call where `$0` is replaced by the `object` name and
+/// `$1`, `$2`, … by the macro variables `$arg1`, `$arg2`, ….
+fn template_to_concat(template: &str, object: &str) -> Tokens {
+    let mut parts = vec![];
+    let mut string = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(character) = chars.next() {
+        if character == '$' && chars.peek().map_or(false, |next| next.is_digit(10)) {
+            let mut index = String::new();
+            while let Some(digit) = chars.peek().cloned().filter(|next| next.is_digit(10)) {
+                index.push(digit);
+                chars.next();
+            }
+            if index == "0" {
+                string.push_str(object);
+            }
+            else {
+                parts.push(quote! { #string });
+                string = String::new();
+                let arg = Ident::new(&format!("arg{}", index), Span::call_site());
+                parts.push(quote! { $#arg });
+            }
+        }
+        else {
+            string.push(character);
+        }
+    }
+    parts.push(quote! { #string });
+    quote! {
+        concat!(#(#parts),*)
+    }
+}
+
+/// Get the SQL type of the sum of values of type `typ`.
+fn sum_type(typ: &Type) -> Option<&'static str> {
+    match *typ {
+        // NOTE: the sum of u64 values is cast to a BIGINT, which fails when it overflows.
+        Type::I16 | Type::I32 | Type::I64 | Type::Serial | Type::U8 | Type::U16 | Type::U32 | Type::U64 =>
+            Some("BIGINT"),
+        Type::Decimal => Some("NUMERIC"),
+        Type::F32 => Some("REAL"),
+        Type::F64 => Some("DOUBLE PRECISION"),
+        Type::Nullable(ref typ) => sum_type(typ),
+        _ => None,
+    }
+}
+
+/// Get the SQL type of the average of values of type `typ`.
+fn avg_type(typ: &Type) -> Option<&'static str> {
+    match *typ {
+        Type::Decimal => Some("NUMERIC"),
+        // NOTE: the sum of u64 values could overflow a BIGINT, but not their average.
+        Type::U64 => Some("DOUBLE PRECISION"),
+        Type::Nullable(ref typ) => avg_type(typ),
+        ref typ => sum_type(typ).map(|_| "DOUBLE PRECISION"),
+    }
+}
+
+/// Create the macro giving the SQL type of the `aggregate` (like sum) of a field.
+fn aggregate_type_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident, aggregate: &str,
+                        aggregate_type: fn(&Type) -> Option<&'static str>) -> Tokens
+{
+    let mut fields = vec![];
+    let mut sql_types = vec![];
+    let mut related_fields = vec![];
+    let mut related_macro_names = vec![];
+    for field in named {
+        let ident = field.ident.expect("field ident");
+        match field_ty_to_type(&field.ty).node {
+            Type::Custom(ref related_table) => {
+                related_fields.push(ident);
+                related_macro_names.push(Ident::new(&format!("tql_{}_{}_type", related_table, aggregate),
+                    Span::call_site()));
+            },
+            ref typ =>
+                if let Some(sql_type) = aggregate_type(typ) {
+                    fields.push(ident);
+                    sql_types.push(sql_type);
+                },
+        }
+    }
+    let macro_name = Ident::new(&format!("tql_{}_{}_type", table_ident, aggregate), Span::call_site());
+    // NOTE: the fallback pattern avoids a macro error when aggregating a non-numeric field: the
+    // type error is reported instead.
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #((#fields) => { #sql_types };)*
+            #((#related_fields . $field:ident) => { #related_macro_names!($field) };)*
+            ($($tt:tt)*) => { "" };
+        }
     }
 }
 
@@ -453,43 +1973,121 @@ fn related_pks_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> T
     }
 }
 
+/// Create the macros giving the name and the SQL type of the primary key (the latter for the
+/// foreign keys referencing this table) and converting its values from and to the query
+/// parameters and the rows.
 fn pk_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
     let macro_name = Ident::new(&format!("tql_{}_primary_key_field", table_ident), Span::call_site());
+    let sql_type_macro_name = Ident::new(&format!("tql_{}_primary_key_sql_type", table_ident), Span::call_site());
+    let parameter_macro_name = Ident::new(&format!("tql_{}_primary_key_parameter", table_ident), Span::call_site());
+    let get_macro_name = Ident::new(&format!("tql_{}_primary_key_get", table_ident), Span::call_site());
     let mut primary_key = None;
     for field in named {
-        let typ = token_to_string(&field.ty);
         if let Some(ident) = field.ident {
-            if typ == "PrimaryKey" {
-                primary_key = Some(ident);
+            if is_primary_key(field) {
+                primary_key = Some((ident, field_ty_to_type(&field.ty).node));
             }
         }
     }
-    let primary_key =
-        if let Some(ident) = primary_key {
+    let wrapper = primary_key.as_ref().and_then(|&(_, ref typ)| value_wrapper(typ));
+    // NOTE: an integer primary key is the rowid in SQLite.
+    let is_rowid = primary_key.as_ref().map_or(true, |&(_, ref typ)| *typ == Type::Serial);
+    let (primary_key, sql_type) =
+        if let Some((ident, typ)) = primary_key {
             let ident = ident.to_string();
-            quote! {
+            let sql_type =
+                match typ {
+                    Type::Serial => quote! { "INTEGER" },
+                    typ => type_to_sql(&Type::Nullable(Box::new(typ))),
+                };
+            (quote! {
                 #ident
-            }
+            }, sql_type)
         }
         else {
-            quote! {
+            (quote! {
                 "-1" // FIXME: hack for when the table has no primary key.
-            }
+            }, quote! { "INTEGER" })
         };
+    let (parameter, get) =
+        if let Some(ref wrapper) = wrapper {
+            (quote! { #wrapper::from_ref($value) },
+             quote! { $row.get::<_, #wrapper<<#table_ident as ::tql::SqlTable>::PrimaryKey>>($index).0 })
+        }
+        else {
+            (quote! { $value }, quote! { $row.get::<_, <#table_ident as ::tql::SqlTable>::PrimaryKey>($index) })
+        };
+    let rowid_macro = rowid_primary_key_macro(table_ident, &primary_key, is_rowid);
     quote! {
         #[macro_export]
         macro_rules! #macro_name {
             () => { #primary_key };
         }
+
+        #[macro_export]
+        macro_rules! #sql_type_macro_name {
+            () => { #sql_type };
+        }
+
+        #[macro_export]
+        macro_rules! #parameter_macro_name {
+            ($value:expr) => { #parameter };
+        }
+
+        #[macro_export]
+        macro_rules! #get_macro_name {
+            ($row:expr, $index:expr) => { #get };
+        }
+
+        #rowid_macro
+    }
+}
+
+/// Create the macro getting the primary key of the row with the specified rowid.
+#[cfg(feature = "rusqlite")]
+fn rowid_primary_key_macro(table_ident: &Ident, primary_key: &Tokens, is_rowid: bool) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_rowid_primary_key", table_ident), Span::call_site());
+    let get_macro_name = Ident::new(&format!("tql_{}_primary_key_get", table_ident), Span::call_site());
+    let table = table_ident.to_string();
+    let primary_key_value =
+        if is_rowid {
+            quote! { Ok($rowid as i32) } // FIXME: don't cast?
+        }
+        else {
+            quote! {
+                $connection.prepare(concat!("SELECT ", #primary_key, " FROM ", #table, " WHERE rowid = ?1"))
+                    .and_then(|mut __tql_result| {
+                        __tql_result.query_map(&[&$rowid as &::rusqlite::types::ToSql], |__tql_item_row| {
+                                #get_macro_name!(__tql_item_row, 0)
+                            })?
+                            .next()
+                            .ok_or_else(|| ::rusqlite::Error::QueryReturnedNoRows)?
+                    })
+            }
+        };
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            ($connection:expr, $rowid:expr) => { #primary_key_value };
+        }
     }
 }
 
+#[cfg(not(feature = "rusqlite"))]
+fn rowid_primary_key_macro(_table_ident: &Ident, _primary_key: &Tokens, _is_rowid: bool) -> Tokens {
+    quote! {}
+}
+
 fn check_missing_fields_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
     let mut mandatory_fields = vec![];
+    let mut automatic_fields = vec![];
     for field in named {
         let typ = token_to_string(&field.ty);
         if let Some(ident) = field.ident {
-            if !typ.starts_with("Option") && typ != "PrimaryKey" {
+            if is_automatic_field(field) {
+                automatic_fields.push(ident);
+            }
+            else if !typ.starts_with("Option") && !is_generated_primary_key(field) {
                 mandatory_fields.push(ident);
             }
         }
@@ -507,7 +2105,60 @@ fn check_missing_fields_macro(named: &Punctuated<Field, Comma>, table_ident: &Id
         #[macro_export]
         macro_rules! #macro_name {
             ($($insert_idents:ident),*) => {
-                #macro_call([#(#mandatory_fields),*], [$($insert_idents),*])
+                #macro_call([#(#mandatory_fields),*], [$($insert_idents),*], [#(#automatic_fields),*])
+            };
+        }
+    }
+}
+
+/// Create the macro checking the literal values assigned to the fields with validation attributes.
+fn check_field_values_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let mut rules = vec![];
+    for field in named {
+        let ident = field.ident.expect("field has name");
+        for attribute in validation_attributes(field) {
+            match attribute {
+                FieldAttribute::MaxLength(max_length) => {
+                    let max_length = max_length as usize;
+                    rules.push(quote! { (#ident, max_length, #max_length) });
+                },
+                FieldAttribute::Range(min, max) => {
+                    if let Some(min) = min {
+                        let min = f64_to_tokens(min);
+                        rules.push(quote! { (#ident, min, #min) });
+                    }
+                    if let Some(max) = max {
+                        let max = f64_to_tokens(max);
+                        rules.push(quote! { (#ident, max, #max) });
+                    }
+                },
+                FieldAttribute::Regex(ref regex) => rules.push(quote! { (#ident, regex, #regex) }),
+                _ => (),
+            }
+        }
+    }
+    let macro_name = Ident::new(&format!("tql_{}_check_field_values", table_ident), Span::call_site());
+    if rules.is_empty() {
+        return quote! {
+            #[macro_export]
+            macro_rules! #macro_name {
+                ($($tt:tt)*) => {};
+            }
+        };
+    }
+    #[cfg(feature = "unstable")]
+    let macro_call = quote_spanned! { table_ident.span() =>
+        ::tql_macros::check_field_values!
+    };
+    #[cfg(not(feature = "unstable"))]
+    let macro_call = quote! {
+        check_field_values!
+    };
+    quote_spanned! { table_ident.span() =>
+        #[macro_export]
+        macro_rules! #macro_name {
+            ($($field:ident = $value:expr),*) => {
+                #macro_call([#(#rules),*], [$($field = $value),*])
             };
         }
     }
@@ -581,8 +2232,7 @@ expected type `ForeignKey<_>`
 fn check_pk_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
     let mut primary_key_found = false;
     for field in named {
-        let typ = token_to_string(&field.ty);
-        if typ == "PrimaryKey" {
+        if is_primary_key(field) {
             primary_key_found = true;
         }
     }
@@ -618,7 +2268,7 @@ pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
         for field in named {
             let typ = token_to_string(&field.ty);
             if let Some(ident) = field.ident {
-                if !typ.starts_with("Option") && typ != "PrimaryKey" {
+                if !typ.starts_with("Option") && !is_generated_primary_key(field) {
                     mandatory_fields.push(ident);
                 }
                 if typ.starts_with("ForeignKey") {
@@ -637,13 +2287,25 @@ pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
         }
 
         let related_field_list_macro_name = Ident::new(&format!("tql_{}_related_field_list", table_ident), Span::call_site());
+        let automatic_fields_macros = automatic_fields_macros(named, table_ident);
+        let check_field_values_macro = check_field_values_macro(named, table_ident);
         let check_missing_fields_macro = check_missing_fields_macro(named, table_ident);
+        let column_list_macro = column_list_macro(named, table_ident);
         let field_list_macro = field_list_macro(named, table_ident);
+        let insert_field_list_macro = insert_field_list_macro(named, table_ident);
         let create_query_macro = create_query_macro(named, table_ident);
         let pk_macro = pk_macro(named, table_ident);
         let related_pks_macro = related_pks_macro(named, table_ident);
         let related_table_macro = related_table_macro(named, table_ident);
         let check_pk_macro = check_pk_macro(named, table_ident);
+        let soft_delete_macro = soft_delete_macro(named, table_ident);
+        let version_assignment_macro = version_assignment_macro(named, table_ident);
+        let sum_type_macro = aggregate_type_macro(named, table_ident, "sum", sum_type);
+        let avg_type_macro = aggregate_type_macro(named, table_ident, "avg", avg_type);
+        let method_macro = method_macro(named, table_ident);
+        let parameter_macro = parameter_macro(named, table_ident);
+        let validated_parameter_macro = validated_parameter_macro(named, table_ident);
+        let get_macro = get_macro(named, table_ident);
         quote! {
             #[macro_export]
             macro_rules! #related_field_list_macro_name {
@@ -651,13 +2313,25 @@ pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
                 ($tt:tt) => { "" };
             }
 
+            #automatic_fields_macros
             #check_pk_macro
             #related_table_macro
+            #check_field_values_macro
             #check_missing_fields_macro
+            #column_list_macro
             #field_list_macro
+            #insert_field_list_macro
             #create_query_macro
             #related_pks_macro
             #pk_macro
+            #soft_delete_macro
+            #version_assignment_macro
+            #sum_type_macro
+            #avg_type_macro
+            #method_macro
+            #parameter_macro
+            #validated_parameter_macro
+            #get_macro
         }
     }
     else {
@@ -665,8 +2339,126 @@ pub fn table_macro(item_struct: &ItemStruct) -> Tokens {
     }
 }
 
+/// Create the macro converting a reference to the value of a field to a query parameter.
+/// The values of some types are wrapped to be sent as the type stored in the database.
+fn parameter_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_parameter", table_ident), Span::call_site());
+    let mut patterns = vec![];
+    for field in named {
+        if let Some(wrapper) = value_wrapper(&field_ty_to_type(&field.ty).node) {
+            let ident = field.ident.expect("field has name");
+            patterns.push(quote! {
+                (#ident, $value:expr) => { #wrapper::from_ref($value) };
+            });
+        }
+    }
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#patterns)*
+            ($field:ident, $value:expr) => { $value };
+        }
+    }
+}
+
+/// Create the macro converting a reference to the value assigned to a field to a query parameter
+/// whose conversion fails if the value does not respect the validation attributes of the field.
+/// The `nullable` pattern is used for the value `x` of `field = Some(x)`.
+fn validated_parameter_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_validated_parameter", table_ident), Span::call_site());
+    let parameter_macro_name = Ident::new(&format!("tql_{}_parameter", table_ident), Span::call_site());
+    let validated = quote_spanned! { table_ident.span() =>
+        ::tql::Validated
+    };
+    let mut patterns = vec![];
+    for field in named {
+        if let Some(checks) = validation_checks(field, table_ident) {
+            let ident = field.ident.expect("field has name");
+            let field_checks = field_value_checks(field, table_ident, quote! { value })
+                .expect("validation checks");
+            // NOTE: the value is bound to a variable to evaluate its expression only once.
+            patterns.push(quote! {
+                (#ident, nullable $value:expr) => {
+                    &match $value {
+                        value => #validated::new(#parameter_macro_name!(#ident, value), {
+                            let mut errors = vec![];
+                            #checks
+                            errors
+                        }),
+                    }
+                };
+                (#ident, $value:expr) => {
+                    &match $value {
+                        value => #validated::new(#parameter_macro_name!(#ident, value), {
+                            let mut errors = vec![];
+                            #field_checks
+                            errors
+                        }),
+                    }
+                };
+            });
+        }
+    }
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#patterns)*
+            ($field:ident, nullable $value:expr) => { #parameter_macro_name!($field, $value) };
+            ($field:ident, $value:expr) => { #parameter_macro_name!($field, $value) };
+        }
+    }
+}
+
+/// Create the macro getting the value of a field from a row.
+/// The values of some types are read as the type stored in the database and then converted.
+fn get_macro(named: &Punctuated<Field, Comma>, table_ident: &Ident) -> Tokens {
+    let macro_name = Ident::new(&format!("tql_{}_get", table_ident), Span::call_site());
+    let mut patterns = vec![];
+    for field in named {
+        if let Some(wrapper) = value_wrapper(&field_ty_to_type(&field.ty).node) {
+            let ident = field.ident.expect("field has name");
+            // NOTE: the type of the value is inferred since the type of the field might not be
+            // in scope where the macro is used.
+            patterns.push(quote! {
+                (#ident, $row:expr, $index:expr) => { $row.get::<_, #wrapper<_>>($index).0 };
+            });
+        }
+    }
+    quote! {
+        #[macro_export]
+        macro_rules! #macro_name {
+            #(#patterns)*
+            ($field:ident, $row:expr, $index:expr) => { $row.get($index) };
+        }
+    }
+}
+
+/// Get the wrapper converting the values of the type `typ` from and to the type stored in the
+/// database, if any.
+fn value_wrapper(typ: &Type) -> Option<Tokens> {
+    match *typ {
+        Type::Nullable(ref typ) => value_wrapper(typ),
+        // NOTE: the unsigned integers are stored as signed integers.
+        ref typ if typ.is_unsigned() => Some(quote! { ::tql::Unsigned }),
+        // NOTE: rusqlite cannot convert the Uuid type, so it is stored in a blob.
+        #[cfg(feature = "rusqlite")]
+        Type::Uuid => Some(quote! { ::tql::UuidBlob }),
+        _ => None,
+    }
+}
+
+/// Convert a reference to the value of the `field` to a query parameter.
+fn field_parameter(field: &Field, value: Tokens, to_sql_trait: &Tokens) -> Tokens {
+    if let Some(wrapper) = value_wrapper(&field_ty_to_type(&field.ty).node) {
+        quote! { #wrapper::from_ref(#value) as &#to_sql_trait }
+    }
+    else {
+        quote! { #value as &#to_sql_trait }
+    }
+}
+
 fn to_row_get(typ: syn::Type, with_delta: bool, index: &mut usize) -> Tokens {
-    if let syn::Type::Path(path) = typ {
+    if let syn::Type::Path(ref path) = typ {
         let segment = path.path.segments.first().expect("first segment").into_value();
         if segment.ident == "ForeignKey" {
             // NOTE: this use the Span call_site() to work-around a privacy issue:
@@ -676,6 +2468,7 @@ fn to_row_get(typ: syn::Type, with_delta: bool, index: &mut usize) -> Tokens {
             };
         }
     }
+    let wrapper = value_wrapper(&field_ty_to_type(&typ).node);
     let backend = create_backend();
     let index_lit = backend.int_literal(*index);
     *index += 1;
@@ -690,8 +2483,15 @@ fn to_row_get(typ: syn::Type, with_delta: bool, index: &mut usize) -> Tokens {
         };
     // NOTE: this use the Span call_site() to work-around a privacy issue:
     // https://github.com/rust-lang/rust/issues/46635
-    quote_spanned! { Span::call_site() =>
-        __tql_item_row.get(#index_lit)
+    if let Some(wrapper) = wrapper {
+        quote_spanned! { Span::call_site() =>
+            __tql_item_row.get::<_, #wrapper<#typ>>(#index_lit).0
+        }
+    }
+    else {
+        quote_spanned! { Span::call_site() =>
+            __tql_item_row.get(#index_lit)
+        }
     }
 }
 
@@ -702,6 +2502,7 @@ pub fn gen_check_missing_fields(input: TokenStream) -> TokenStream {
     let arg2 = &args[1];
     let mut mandatory_fields = vec![];
     let mut fields = vec![];
+    let mut automatic_fields = vec![];
 
     if let Expr::Array(ref array) = *arg1 {
         for elem in &array.elems {
@@ -733,6 +2534,40 @@ pub fn gen_check_missing_fields(input: TokenStream) -> TokenStream {
         }
     }
 
+    if let Some(&Expr::Array(ref array)) = args.iter().nth(2) {
+        for elem in &array.elems {
+            let path =
+                if let Expr::Group(ref group) = *elem {
+                    if let Expr::Path(ref path) = *group.expr {
+                        path
+                    }
+                    else {
+                        panic!("Expecting path");
+                    }
+                }
+                // NOTE: need this condition on stable.
+                else if let Expr::Path(ref path) = *elem {
+                    path
+                }
+                else {
+                    panic!("Expecting path");
+                };
+            automatic_fields.push(path.path.segments[0].ident.clone());
+        }
+    }
+
+    let assigned_automatic_fields: Vec<_> = automatic_fields.iter()
+        .filter(|field| fields.contains(field))
+        .map(|field| field.to_string())
+        .collect();
+    if !assigned_automatic_fields.is_empty() {
+        let error = string_literal(&format!("cannot assign automatically assigned fields: {}",
+                                            assigned_automatic_fields.join(", ")));
+        return (quote! {
+            compile_error!(#error);
+        }).into();
+    }
+
     let mut missing_fields = vec![];
 
     for field in mandatory_fields {
@@ -754,17 +2589,130 @@ pub fn gen_check_missing_fields(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Check that the literal values assigned to the fields respect their validation attributes.
+/// The first argument is the list of rules, like `(name, max_length, 255)`, and the second one is
+/// the list of assignments, like `name = "value"`.
+pub fn gen_check_field_values(input: TokenStream) -> TokenStream {
+    fn ungroup(expr: &Expr) -> &Expr {
+        // NOTE: need this condition on unstable.
+        if let Expr::Group(ref group) = *expr {
+            ungroup(&group.expr)
+        }
+        else {
+            expr
+        }
+    }
+
+    fn expr_to_ident(expr: &Expr) -> Option<Ident> {
+        if let Expr::Path(ref path) = *ungroup(expr) {
+            return path.path.segments.first().map(|segment| segment.value().ident);
+        }
+        None
+    }
+
+    fn expr_to_f64(expr: &Expr) -> Option<f64> {
+        match *ungroup(expr) {
+            Expr::Lit(ExprLit { lit: Lit::Float(ref float), .. }) => Some(float.value()),
+            Expr::Lit(ExprLit { lit: Lit::Int(ref int), .. }) => Some(int.value() as f64),
+            Expr::Unary(ExprUnary { op: UnOp::Neg(_), ref expr, .. }) => expr_to_f64(expr).map(|value| -value),
+            _ => None,
+        }
+    }
+
+    fn expr_to_string(expr: &Expr) -> Option<String> {
+        if let Expr::Lit(ExprLit { lit: Lit::Str(ref string), .. }) = *ungroup(expr) {
+            return Some(string.value());
+        }
+        None
+    }
+
+    let args: Arguments = parse(input).expect("parse check_field_values!()");
+    let args = args.0;
+    let mut rules = vec![];
+    if let Expr::Array(ref array) = args[0] {
+        for elem in &array.elems {
+            if let Expr::Tuple(ref tuple) = *ungroup(elem) {
+                let elems: Vec<_> = tuple.elems.iter().collect();
+                if let (Some(field), Some(rule)) = (expr_to_ident(elems[0]), expr_to_ident(elems[1])) {
+                    rules.push((field, rule.to_string(), elems[2]));
+                }
+            }
+        }
+    }
+
+    let mut errors = vec![];
+    if let Expr::Array(ref array) = args[1] {
+        for elem in &array.elems {
+            if let Expr::Assign(ref assign) = *ungroup(elem) {
+                let field = expr_to_ident(&assign.left);
+                let value = ungroup(&assign.right);
+                let value = some_inner_expr(value).map(ungroup).unwrap_or(value);
+                for &(field, ref rule, parameter) in rules.iter().filter(|rule| Some(rule.0) == field) {
+                    // NOTE: a value of the wrong type is reported by the type checking.
+                    let error =
+                        match rule.as_str() {
+                            "max_length" => {
+                                let max_length = expr_to_f64(parameter).expect("max_length") as usize;
+                                expr_to_string(value)
+                                    .filter(|string| string.chars().count() > max_length)
+                                    .map(|_| format!("{} has more than {} characters", field, max_length))
+                            },
+                            "min" => {
+                                let min = expr_to_f64(parameter).expect("min");
+                                expr_to_f64(value)
+                                    .filter(|&number| number < min)
+                                    .map(|_| format!("{} is less than {}", field, min))
+                            },
+                            "max" => {
+                                let max = expr_to_f64(parameter).expect("max");
+                                expr_to_f64(value)
+                                    .filter(|&number| number > max)
+                                    .map(|_| format!("{} is greater than {}", field, max))
+                            },
+                            "regex" => {
+                                let regex = expr_to_string(parameter).expect("regex");
+                                let compiled_regex = Regex::new(&regex).expect("valid regex");
+                                expr_to_string(value)
+                                    .filter(|string| !compiled_regex.is_match(string))
+                                    .map(|_| format!("{} does not match the regex `{}`", field, regex))
+                            },
+                            _ => None,
+                        };
+                    if let Some(error) = error {
+                        errors.push(quote_spanned! { value.span() =>
+                            compile_error!(#error);
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    (quote! {
+        #(#errors)*
+    }).into()
+}
+
 fn rand_string() -> String {
     rand::thread_rng().gen_ascii_chars().take(30).collect()
 }
 
 trait BackendGen {
+    fn connection_type(&self, table_ident: &Ident) -> Tokens;
     fn convert_index(&self, index: usize) -> Tokens;
     fn delta_type(&self) -> Tokens;
+    fn enum_impls(&self, enum_ident: &Ident, to_label: Tokens, from_label: Tokens) -> Tokens;
+    fn error_type(&self, table_ident: &Ident) -> Tokens;
     fn gen_query_expr(&self, connection_expr: Tokens, args: &SqlQueryWithArgs, args_expr: Tokens, struct_expr: Tokens,
                       aggregate_struct: Tokens, aggregate_expr: Tokens) -> Tokens;
+    fn gen_select_multi_expr(&self, connection_expr: Tokens, sql_query: &Tokens, args_expr: Tokens, struct_expr: Tokens)
+        -> Tokens;
     fn int_literal(&self, num: usize) -> Expr;
+    fn is_to_sql_type(&self, typ: &Type) -> bool;
+    fn newtype_impls(&self, type_ident: &Ident, field_type: &syn::Type) -> Tokens;
+    fn row_count_type(&self) -> Tokens;
     fn row_type_ident(&self, table_ident: &Ident) -> Tokens;
-    fn to_sql(&self, primary_key_ident: &Ident) -> Tokens;
+    fn to_sql(&self, primary_key_value: Tokens) -> Tokens;
     fn to_sql_impl(&self, table_ident: &Ident, to_sql_code: Tokens) -> Tokens;
+    fn to_sql_trait(&self, table_ident: &Ident) -> Tokens;
 }
@@ -22,6 +22,7 @@
 use proc_macro2::Span;
 use quote::Tokens;
 use syn::{
+    self,
     Expr,
     ExprLit,
     Ident,
@@ -33,6 +34,7 @@ use syn::spanned::Spanned;
 
 use ast::QueryType;
 use super::BackendGen;
+use types::Type;
 use SqlQueryWithArgs;
 
 pub struct PostgresBackend {}
@@ -43,6 +45,12 @@ pub fn create_backend() -> PostgresBackend {
 }
 
 impl BackendGen for PostgresBackend {
+    fn connection_type(&self, table_ident: &Ident) -> Tokens {
+        quote_spanned! { table_ident.span() =>
+            ::postgres::GenericConnection
+        }
+    }
+
     fn convert_index(&self, index: usize) -> Tokens {
         quote! {
             #index
@@ -53,6 +61,59 @@ impl BackendGen for PostgresBackend {
         quote! { usize }
     }
 
+    fn enum_impls(&self, enum_ident: &Ident, to_label: Tokens, from_label: Tokens) -> Tokens {
+        let std_ident = quote_spanned! { enum_ident.span() =>
+            ::std
+        };
+        let postgres_ident = quote_spanned! { enum_ident.span() =>
+            ::postgres
+        };
+        // NOTE: PostgreSQL converts the name of the type to lowercase.
+        let type_name = enum_ident.to_string().to_lowercase();
+        quote! {
+            impl #postgres_ident::types::ToSql for #enum_ident {
+                fn to_sql(&self, _ty: &#postgres_ident::types::Type, out: &mut Vec<u8>) ->
+                    Result<#postgres_ident::types::IsNull, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    let label: &str = #to_label;
+                    out.extend_from_slice(label.as_bytes());
+                    Ok(#postgres_ident::types::IsNull::No)
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    ty.name() == #type_name
+                }
+
+                fn to_sql_checked(&self, ty: &#postgres_ident::types::Type, out: &mut #std_ident::vec::Vec<u8>)
+                    -> #std_ident::result::Result<#postgres_ident::types::IsNull,
+                    Box<#std_ident::error::Error + #std_ident::marker::Sync + #std_ident::marker::Send>>
+                {
+                    #postgres_ident::types::__to_sql_checked(self, ty, out)
+                }
+            }
+
+            impl #postgres_ident::types::FromSql for #enum_ident {
+                fn from_sql(_ty: &#postgres_ident::types::Type, raw: &[u8]) ->
+                    Result<Self, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    let label = #std_ident::str::from_utf8(raw)?;
+                    #from_label.ok_or_else(|| format!("invalid value for enum {}: {}", stringify!(#enum_ident), label)
+                        .into())
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    ty.name() == #type_name
+                }
+            }
+        }
+    }
+
+    fn error_type(&self, table_ident: &Ident) -> Tokens {
+        quote_spanned! { table_ident.span() =>
+            ::postgres::Error
+        }
+    }
+
     /// Generate the Rust code using the `postgres` library depending on the `QueryType`.
     fn gen_query_expr(&self, connection_expr: Tokens, args: &SqlQueryWithArgs, args_expr: Tokens, struct_expr: Tokens,
                       aggregate_struct: Tokens, aggregate_expr: Tokens) -> Tokens
@@ -91,37 +152,97 @@ impl BackendGen for PostgresBackend {
                         })
                 }}
             },
-            QueryType::Create => {
+            QueryType::Count | QueryType::Exists => {
+                let typ =
+                    match args.query_type {
+                        QueryType::Count => quote! { i64 },
+                        _ => quote! { bool },
+                    };
                 quote! {
                     #connection_expr.prepare(#sql_query)
-                        .and_then(|result| result.execute(&[]))
+                        .and_then(|#result_ident| {
+                            let #result_ident = #result_ident.query(&#args_expr)?;
+                            let __tql_item_row = #result_ident.iter().next()
+                                .ok_or_else(|| #std_ident::io::Error::from(#std_ident::io::ErrorKind::NotFound))?;
+                            let value: #typ = __tql_item_row.get(0);
+                            Ok(value)
+                        })
+                }
+            },
+            QueryType::Create => {
+                let types_macro = Ident::new(&format!("tql_{}_create_types", args.table_name), Span::call_site());
+                let postgres_ident = quote_spanned! { connection_expr.span() =>
+                    ::postgres
+                };
+                // NOTE: the types used by the table are created before the table.
+                quote! {
+                    (|| -> #std_ident::result::Result<u64, #postgres_ident::Error> {
+                        let __tql_type_queries: &[&str] = &#types_macro!();
+                        for __tql_query in __tql_type_queries {
+                            if !__tql_query.is_empty() {
+                                #connection_expr.execute(__tql_query, &[])?;
+                            }
+                        }
+                        #connection_expr.prepare(#sql_query)
+                            .and_then(|result| result.execute(&[]))
+                    })()
+                }
+            },
+            QueryType::InsertMany => {
+                let table_ident = &args.table_name;
+                let automatic_values_macro_name = Ident::new(&format!("tql_{}_automatic_values", table_ident),
+                    Span::call_site());
+                let postgres_ident = quote_spanned! { connection_expr.span() =>
+                    ::postgres
+                };
+                let primary_key_type = quote! { <#table_ident as ::tql::SqlTable>::PrimaryKey };
+                quote! {
+                    (|| -> #std_ident::result::Result<Vec<#primary_key_type>, #postgres_ident::Error> {
+                        let mut __tql_ids = vec![];
+                        for __tql_chunk in ::tql::insert_many_chunks::<#table_ident, _, _>(#args_expr) {
+                            ::tql::validate_rows::<#table_ident, _>(&__tql_chunk)?;
+                            let __tql_query = format!(#sql_query,
+                                                      ::tql::insert_many_values::<#table_ident, _>(&__tql_chunk,
+                                                          #automatic_values_macro_name!()));
+                            let #result_ident = #connection_expr.prepare(&__tql_query)?;
+                            let __tql_parameters = ::tql::insert_many_parameters::<#table_ident, _>(&__tql_chunk);
+                            for __tql_item_row in #result_ident.query(&__tql_parameters)?.iter() {
+                                let id: #primary_key_type = __tql_item_row.get(0);
+                                __tql_ids.push(id);
+                            }
+                        }
+                        Ok(__tql_ids)
+                    })()
                 }
             },
             QueryType::InsertOne => {
+                let table_ident = &args.table_name;
                 quote! {
                     #connection_expr.prepare(#sql_query)
                         .and_then(|result| {
                             let rows = result.query(&#args_expr)?;
                             let __tql_item_row = rows.iter().next()
                                 .ok_or_else(|| #std_ident::io::Error::from(#std_ident::io::ErrorKind::NotFound))?;
-                            let count: i32 = __tql_item_row.get(0);
-                            Ok(count)
+                            let id: <#table_ident as ::tql::SqlTable>::PrimaryKey = __tql_item_row.get(0);
+                            Ok(id)
                         })
                 }
             },
-            QueryType::SelectMulti => {
+            QueryType::Upsert => {
+                let table_ident = &args.table_name;
                 quote! {
                     #connection_expr.prepare(#sql_query)
-                        .and_then(|#result_ident| {
-                            let #result_ident = #result_ident.query(&#args_expr)?;
-                            let #result_ident = #result_ident.iter();
-                            Ok(#result_ident.map(|__tql_item_row| {
-                                #struct_expr
-                            }).collect::<Vec<_>>())
-                            // TODO: return an iterator instead of a vector.
+                        .and_then(|result| {
+                            let rows = result.query(&#args_expr)?;
+                            // NOTE: no row is returned when the conflicting row is ignored.
+                            Ok(rows.iter().next().map(|__tql_item_row| {
+                                let id: <#table_ident as ::tql::SqlTable>::PrimaryKey = __tql_item_row.get(0);
+                                id
+                            }))
                         })
                 }
             },
+            QueryType::SelectMulti => self.gen_select_multi_expr(connection_expr, sql_query, args_expr, struct_expr),
             QueryType::SelectOne => {
                 quote! {
                     #connection_expr.prepare(#sql_query)
@@ -133,6 +254,17 @@ impl BackendGen for PostgresBackend {
                         })
                 }
             },
+            QueryType::SelectOptional => {
+                quote! {
+                    #connection_expr.prepare(#sql_query)
+                        .and_then(|#result_ident| {
+                            let #result_ident = #result_ident.query(&#args_expr)?;
+                            Ok(#result_ident.iter().next().map(|__tql_item_row| {
+                                #struct_expr
+                            }))
+                        })
+                }
+            },
             QueryType::Exec => {
                 quote! {
                     #connection_expr.prepare(#sql_query)
@@ -142,6 +274,23 @@ impl BackendGen for PostgresBackend {
         }
     }
 
+    fn gen_select_multi_expr(&self, connection_expr: Tokens, sql_query: &Tokens, args_expr: Tokens, struct_expr: Tokens)
+        -> Tokens
+    {
+        let result_ident = Ident::from("result");
+        quote! {
+            #connection_expr.prepare(#sql_query)
+                .and_then(|#result_ident| {
+                    let #result_ident = #result_ident.query(&#args_expr)?;
+                    let #result_ident = #result_ident.iter();
+                    Ok(#result_ident.map(|__tql_item_row| {
+                        #struct_expr
+                    }).collect::<Vec<_>>())
+                    // TODO: return an iterator instead of a vector.
+                })
+        }
+    }
+
     fn int_literal(&self, num: usize) -> Expr {
         Expr::Lit(ExprLit {
             attrs: vec![],
@@ -149,15 +298,68 @@ impl BackendGen for PostgresBackend {
         })
     }
 
+    fn is_to_sql_type(&self, typ: &Type) -> bool {
+        match *typ {
+            Type::Char => false,
+            Type::Nullable(ref typ) => self.is_to_sql_type(typ),
+            _ => true,
+        }
+    }
+
+    fn newtype_impls(&self, type_ident: &Ident, field_type: &syn::Type) -> Tokens {
+        let std_ident = quote_spanned! { type_ident.span() =>
+            ::std
+        };
+        let postgres_ident = quote_spanned! { type_ident.span() =>
+            ::postgres
+        };
+        quote! {
+            impl #postgres_ident::types::ToSql for #type_ident {
+                fn to_sql(&self, ty: &#postgres_ident::types::Type, out: &mut Vec<u8>) ->
+                    Result<#postgres_ident::types::IsNull, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    #postgres_ident::types::ToSql::to_sql(&self.0, ty, out)
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    <#field_type as #postgres_ident::types::ToSql>::accepts(ty)
+                }
+
+                fn to_sql_checked(&self, ty: &#postgres_ident::types::Type, out: &mut #std_ident::vec::Vec<u8>)
+                    -> #std_ident::result::Result<#postgres_ident::types::IsNull,
+                    Box<#std_ident::error::Error + #std_ident::marker::Sync + #std_ident::marker::Send>>
+                {
+                    #postgres_ident::types::__to_sql_checked(self, ty, out)
+                }
+            }
+
+            impl #postgres_ident::types::FromSql for #type_ident {
+                fn from_sql(ty: &#postgres_ident::types::Type, raw: &[u8]) ->
+                    Result<Self, Box<#std_ident::error::Error + 'static + Sync + Send>>
+                {
+                    <#field_type as #postgres_ident::types::FromSql>::from_sql(ty, raw).map(#type_ident)
+                }
+
+                fn accepts(ty: &#postgres_ident::types::Type) -> bool {
+                    <#field_type as #postgres_ident::types::FromSql>::accepts(ty)
+                }
+            }
+        }
+    }
+
+    fn row_count_type(&self) -> Tokens {
+        quote! { u64 }
+    }
+
     fn row_type_ident(&self, table_ident: &Ident) -> Tokens {
         quote_spanned! { table_ident.span() =>
             ::postgres::rows::Row
         }
     }
 
-    fn to_sql(&self, primary_key_ident: &Ident) -> Tokens {
+    fn to_sql(&self, primary_key_value: Tokens) -> Tokens {
         quote! {
-            self.#primary_key_ident.to_sql(ty, out)
+            #primary_key_value.to_sql(ty, out)
         }
     }
 
@@ -177,7 +379,7 @@ impl BackendGen for PostgresBackend {
                 }
 
                 fn accepts(ty: &#postgres_ident::types::Type) -> bool {
-                    *ty == #postgres_ident::types::INT4
+                    <<#table_ident as ::tql::SqlTable>::PrimaryKey as #postgres_ident::types::ToSql>::accepts(ty)
                 }
 
                 fn to_sql_checked(&self, ty: &#postgres_ident::types::Type, out: &mut #std_ident::vec::Vec<u8>)
@@ -189,4 +391,10 @@ impl BackendGen for PostgresBackend {
             }
         }
     }
+
+    fn to_sql_trait(&self, table_ident: &Ident) -> Tokens {
+        quote_spanned! { table_ident.span() =>
+            ::postgres::types::ToSql
+        }
+    }
 }
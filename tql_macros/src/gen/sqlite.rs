@@ -22,6 +22,7 @@
 use proc_macro2::Span;
 use quote::Tokens;
 use syn::{
+    self,
     Expr,
     ExprLit,
     Ident,
@@ -33,6 +34,7 @@ use syn::spanned::Spanned;
 
 use ast::QueryType;
 use super::BackendGen;
+use types::Type;
 use SqlQueryWithArgs;
 
 pub struct SqliteBackend {}
@@ -43,6 +45,12 @@ pub fn create_backend() -> SqliteBackend {
 }
 
 impl BackendGen for SqliteBackend {
+    fn connection_type(&self, table_ident: &Ident) -> Tokens {
+        quote_spanned! { table_ident.span() =>
+            ::rusqlite::Connection
+        }
+    }
+
     fn convert_index(&self, index: usize) -> Tokens {
         let index = index as i32;
         quote! {
@@ -54,6 +62,36 @@ impl BackendGen for SqliteBackend {
         quote! { i32 }
     }
 
+    fn enum_impls(&self, enum_ident: &Ident, to_label: Tokens, from_label: Tokens) -> Tokens {
+        let rusqlite_ident = quote_spanned! { enum_ident.span() =>
+            ::rusqlite
+        };
+        quote! {
+            impl #rusqlite_ident::types::ToSql for #enum_ident {
+                fn to_sql(&self) -> #rusqlite_ident::Result<#rusqlite_ident::types::ToSqlOutput> {
+                    let label: &str = #to_label;
+                    Ok(#rusqlite_ident::types::ToSqlOutput::from(label))
+                }
+            }
+
+            impl #rusqlite_ident::types::FromSql for #enum_ident {
+                fn column_result(value: #rusqlite_ident::types::ValueRef)
+                    -> #rusqlite_ident::types::FromSqlResult<Self>
+                {
+                    let label = value.as_str()?;
+                    #from_label.ok_or_else(|| #rusqlite_ident::types::FromSqlError::Other(
+                        format!("invalid value for enum {}: {}", stringify!(#enum_ident), label).into()))
+                }
+            }
+        }
+    }
+
+    fn error_type(&self, table_ident: &Ident) -> Tokens {
+        quote_spanned! { table_ident.span() =>
+            ::rusqlite::Error
+        }
+    }
+
     fn gen_query_expr(&self, connection_expr: Tokens, args: &SqlQueryWithArgs, args_expr: Tokens, struct_expr: Tokens,
                       aggregate_struct: Tokens, aggregate_expr: Tokens) -> Tokens
     {
@@ -92,31 +130,82 @@ impl BackendGen for SqliteBackend {
                         })
                 }}
             },
+            QueryType::Count | QueryType::Exists => {
+                let typ =
+                    match args.query_type {
+                        QueryType::Count => quote! { i64 },
+                        _ => quote! { bool },
+                    };
+                quote! {
+                    #connection_expr.prepare(#sql_query)
+                        .and_then(|mut #result_ident| {
+                            #result_ident.query_map(&#args_expr, |__tql_item_row| {
+                                    let value: #typ = __tql_item_row.get(0);
+                                    value
+                                })?
+                                .next()
+                                .ok_or_else(|| #rusqlite_ident::Error::QueryReturnedNoRows)?
+                        })
+                }
+            },
             QueryType::Create => {
                 quote! {
                     #connection_expr.prepare(#sql_query)
                         .and_then(|mut result| result.execute(&[]))
                 }
             },
+            QueryType::InsertMany => {
+                let table_ident = &args.table_name;
+                let automatic_values_macro_name = Ident::new(&format!("tql_{}_automatic_values", table_ident),
+                    Span::call_site());
+                let get_macro_name = Ident::new(&format!("tql_{}_primary_key_get", table_ident), Span::call_site());
+                let primary_key_type = quote! { <#table_ident as ::tql::SqlTable>::PrimaryKey };
+                quote! {
+                    (|| -> ::std::result::Result<Vec<#primary_key_type>, #rusqlite_ident::Error> {
+                        let mut __tql_ids = vec![];
+                        for __tql_chunk in ::tql::insert_many_chunks::<#table_ident, _, _>(#args_expr) {
+                            ::tql::validate_rows::<#table_ident, _>(&__tql_chunk)?;
+                            let __tql_query = format!(#sql_query,
+                                                      ::tql::insert_many_values::<#table_ident, _>(&__tql_chunk,
+                                                          #automatic_values_macro_name!()));
+                            let mut #result_ident = #connection_expr.prepare(&__tql_query)?;
+                            let __tql_parameters = ::tql::insert_many_parameters::<#table_ident, _>(&__tql_chunk);
+                            let __tql_rows = #result_ident.query_map(&__tql_parameters, |__tql_item_row| {
+                                    #get_macro_name!(__tql_item_row, 0)
+                                })?;
+                            for __tql_id in __tql_rows {
+                                __tql_ids.push(__tql_id?);
+                            }
+                        }
+                        Ok(__tql_ids)
+                    })()
+                }
+            },
             QueryType::InsertOne => {
+                let rowid_macro_name = Ident::new(&format!("tql_{}_rowid_primary_key", args.table_name),
+                    Span::call_site());
                 quote! {
                     #connection_expr.prepare(#sql_query)
                         .and_then(|mut result| result.execute(&#args_expr))
-                        .map(|_| #connection_expr.last_insert_rowid() as i32) // FIXME: don't cast?
+                        .and_then(|_| #rowid_macro_name!(#connection_expr, #connection_expr.last_insert_rowid()))
                 }
             },
-            QueryType::SelectMulti => {
+            QueryType::Upsert => {
+                let get_macro_name = Ident::new(&format!("tql_{}_primary_key_get", args.table_name),
+                    Span::call_site());
                 quote! {
                     #connection_expr.prepare(#sql_query)
                         .and_then(|mut #result_ident| {
-                            let #result_ident = #result_ident.query_map(&#args_expr, |__tql_item_row| {
-                                    #struct_expr
-                                })?;
-                            #result_ident.collect::<Result<Vec<_>, _>>()
-                            // TODO: return an iterator instead of a vector.
+                            // NOTE: no row is returned when the conflicting row is ignored.
+                            #result_ident.query_map(&#args_expr, |__tql_item_row| {
+                                    #get_macro_name!(__tql_item_row, 0)
+                                })?
+                                .next()
+                                .map_or(Ok(None), |id| id.map(Some))
                         })
                 }
             },
+            QueryType::SelectMulti => self.gen_select_multi_expr(connection_expr, sql_query, args_expr, struct_expr),
             QueryType::SelectOne => {
                 quote! {
                     #connection_expr.prepare(#sql_query)
@@ -129,6 +218,18 @@ impl BackendGen for SqliteBackend {
                         })
                 }
             },
+            QueryType::SelectOptional => {
+                quote! {
+                    #connection_expr.prepare(#sql_query)
+                        .and_then(|mut #result_ident| {
+                            #result_ident.query_map(&#args_expr, |__tql_item_row| {
+                                    #struct_expr
+                                })?
+                                .next()
+                                .map_or(Ok(None), |row| row.map(Some))
+                        })
+                }
+            },
             QueryType::Exec => {
                 quote! {
                     #connection_expr.prepare(#sql_query)
@@ -138,6 +239,22 @@ impl BackendGen for SqliteBackend {
         }
     }
 
+    fn gen_select_multi_expr(&self, connection_expr: Tokens, sql_query: &Tokens, args_expr: Tokens, struct_expr: Tokens)
+        -> Tokens
+    {
+        let result_ident = Ident::from("result");
+        quote! {
+            #connection_expr.prepare(#sql_query)
+                .and_then(|mut #result_ident| {
+                    let #result_ident = #result_ident.query_map(&#args_expr, |__tql_item_row| {
+                            #struct_expr
+                        })?;
+                    #result_ident.collect::<Result<Vec<_>, _>>()
+                    // TODO: return an iterator instead of a vector.
+                })
+        }
+    }
+
     fn int_literal(&self, num: usize) -> Expr {
         Expr::Lit(ExprLit {
             attrs: vec![],
@@ -145,15 +262,48 @@ impl BackendGen for SqliteBackend {
         })
     }
 
+    fn is_to_sql_type(&self, typ: &Type) -> bool {
+        match *typ {
+            Type::Char | Type::F32 => false,
+            Type::Nullable(ref typ) => self.is_to_sql_type(typ),
+            _ => true,
+        }
+    }
+
+    fn newtype_impls(&self, type_ident: &Ident, field_type: &syn::Type) -> Tokens {
+        let rusqlite_ident = quote_spanned! { type_ident.span() =>
+            ::rusqlite
+        };
+        quote! {
+            impl #rusqlite_ident::types::ToSql for #type_ident {
+                fn to_sql(&self) -> #rusqlite_ident::Result<#rusqlite_ident::types::ToSqlOutput> {
+                    #rusqlite_ident::types::ToSql::to_sql(&self.0)
+                }
+            }
+
+            impl #rusqlite_ident::types::FromSql for #type_ident {
+                fn column_result(value: #rusqlite_ident::types::ValueRef)
+                    -> #rusqlite_ident::types::FromSqlResult<Self>
+                {
+                    <#field_type as #rusqlite_ident::types::FromSql>::column_result(value).map(#type_ident)
+                }
+            }
+        }
+    }
+
+    fn row_count_type(&self) -> Tokens {
+        quote! { usize }
+    }
+
     fn row_type_ident(&self, table_ident: &Ident) -> Tokens {
         quote_spanned! { table_ident.span() =>
             ::rusqlite::Row
         }
     }
 
-    fn to_sql(&self, primary_key_ident: &Ident) -> Tokens {
+    fn to_sql(&self, primary_key_value: Tokens) -> Tokens {
         quote! {
-            self.#primary_key_ident.to_sql()
+            #primary_key_value.to_sql()
         }
     }
 
@@ -170,4 +320,10 @@ impl BackendGen for SqliteBackend {
             }
         }
     }
+
+    fn to_sql_trait(&self, table_ident: &Ident) -> Tokens {
+        quote_spanned! { table_ident.span() =>
+            ::rusqlite::types::ToSql
+        }
+    }
 }
@@ -24,7 +24,6 @@
  * TODO: looks like the function annotate does not exist anymore.
  * TODO: return an iterator instead of a Vec.
  * TODO: add support for default value.
- * TODO: support INSERT OR IGNORE.
  *
  * TODO: support the missing types
  * (https://docs.rs/postgres/0.15.1/postgres/types/trait.ToSql.html).
@@ -86,6 +85,7 @@ extern crate proc_macro2;
 #[macro_use]
 extern crate quote;
 extern crate rand;
+extern crate regex;
 #[macro_use]
 extern crate syn;
 
@@ -101,6 +101,7 @@ mod methods;
 mod optimizer;
 mod parser;
 mod plugin;
+mod raw;
 mod sql;
 mod stable;
 mod state;
@@ -132,9 +133,13 @@ use analyzer::{
     analyze_methods,
     analyze_types,
     get_aggregate_calls,
+    get_conflict_idents,
+    get_distinct_idents,
     get_insert_idents,
     get_limit_args,
+    get_literal_assignments,
     get_method_calls,
+    get_returning_idents,
     get_sort_idents,
     get_values_idents,
 };
@@ -148,26 +153,39 @@ use ast::{
     MethodCall,
     Query,
     QueryType,
+    Returning,
+    is_none_literal,
     query_type,
 };
 use error::{Error, Result};
 #[cfg(not(feature = "unstable"))]
 use error::compiler_error;
 use gen::{
+    aggregate_argument_to_rust,
+    gen_check_field_values,
     gen_check_missing_fields,
     generate_errors,
     gen_query,
+    gen_raw_query,
+    get_enum_variants,
+    get_newtype_field_type,
     get_struct_fields,
+    insert_row_impls,
+    instance_methods,
+    sql_enum_impl,
+    sql_type_impl,
     table_macro,
     table_methods,
     tosql_impl,
+    validate_method,
 };
 use optimizer::optimize;
 use parser::Parser;
+use raw::to_raw_query;
 use stable::generate_macro_patterns;
 
 struct SqlQueryWithArgs {
-    aggregate_calls: Vec<(String, Expr)>,
+    aggregate_calls: Vec<(Aggregate, Expr)>,
     aggregates: Vec<Aggregate>,
     arguments: Args,
     filter_method_calls: Vec<(MethodCall, Option<Expression>)>,
@@ -178,7 +196,9 @@ struct SqlQueryWithArgs {
     joins: Vec<Join>,
     limit_exprs: Vec<Expr>,
     literal_arguments: Args,
+    literal_assignments: Vec<(Ident, Expression)>,
     query_type: QueryType,
+    returning: Returning,
     sql: Tokens,
     stable_macro_query: Tokens,
     table_name: Ident,
@@ -232,6 +252,39 @@ pub fn to_sql(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Expand the `sql_raw!()` macro.
+/// This macro executes the raw SQL query provided as argument and converts the resulting rows to
+/// the table struct.
+#[cfg(feature = "unstable")]
+#[proc_macro]
+pub fn sql_raw(input: TokenStream) -> TokenStream {
+    let arguments: Arguments =
+        match parse(input) {
+            Ok(args) => args,
+            Err(error) => return generate_errors(vec![Error::new(
+                    &format!("cannot parse expression in sql_raw!(): {}", error), Span::call_site())]),
+        };
+    let arguments: Vec<_> = arguments.0.into_iter().collect();
+    // NOTE: the connection is the first argument when the second one is the table name.
+    let (connection_expr, arguments) =
+        match arguments.get(1) {
+            Some(&Expr::Path(_)) => {
+                let connection_expr = &arguments[0];
+                (quote! { #connection_expr }, &arguments[1..])
+            },
+            _ => (default_connection_expr(), &arguments[..]),
+        };
+    match to_raw_query(arguments, Span::call_site()) {
+        Ok(query) => {
+            let arg_refs: Vec<_> = query.arguments.iter()
+                .map(|arg| quote! { &(#arg) })
+                .collect();
+            gen_raw_query(&query, connection_expr, &arg_refs)
+        },
+        Err(errors) => generate_errors(errors),
+    }
+}
+
 /// Convert the Rust code to an SQL string with its type, arguments, joins, and aggregate fields.
 fn to_sql_query(input: proc_macro2::TokenStream) -> Result<SqlQueryWithArgs> {
     if input.is_empty() {
@@ -264,9 +317,19 @@ fn to_sql_query(input: proc_macro2::TokenStream) -> Result<SqlQueryWithArgs> {
             _ => vec![],
         };
     let query_type = query_type(&query);
+    let returning =
+        match query {
+            Query::Delete { ref returning, .. } | Query::Insert { ref returning, .. } |
+                Query::Update { ref returning, .. } => returning.clone(),
+            _ => Returning::NoReturning,
+        };
     let mut idents = get_sort_idents(&query);
+    idents.extend(get_distinct_idents(&query));
+    idents.extend(get_conflict_idents(&query));
+    idents.extend(get_returning_idents(&query));
     idents.extend(get_values_idents(&query));
     let insert_idents = get_insert_idents(&query);
+    let literal_assignments = get_literal_assignments(&query);
     let limit_exprs = get_limit_args(&query);
     let filter_method_calls = get_method_calls(&query);
     let aggregate_calls = get_aggregate_calls(&query);
@@ -284,7 +347,9 @@ fn to_sql_query(input: proc_macro2::TokenStream) -> Result<SqlQueryWithArgs> {
         joins,
         limit_exprs,
         literal_arguments,
+        literal_assignments,
         query_type,
+        returning,
         sql,
         stable_macro_query,
         table_name,
@@ -293,7 +358,7 @@ fn to_sql_query(input: proc_macro2::TokenStream) -> Result<SqlQueryWithArgs> {
 
 /// Expand the `#[SqlTable]` attribute.
 /// This attribute must be used on structs to tell tql that it represents an SQL table.
-#[proc_macro_derive(SqlTable)]
+#[proc_macro_derive(SqlTable, attributes(tql))]
 pub fn sql_table(input: TokenStream) -> TokenStream {
     let item: Item =
         match parse(input) {
@@ -317,14 +382,20 @@ pub fn sql_table(input: TokenStream) -> TokenStream {
                     quote! {
                     }
                 };
+            let instance_methods = instance_methods(&item_struct, primary_key.clone());
             let code = tosql_impl(&item_struct, primary_key);
+            let insert_row_impls = insert_row_impls(&item_struct);
             let methods = table_methods(&item_struct);
             let table_macro = table_macro(&item_struct);
+            let validate_method = validate_method(&item_struct);
             let code = quote! {
                 #errors
                 #methods
+                #validate_method
                 #code
+                #insert_row_impls
                 #table_macro
+                #instance_methods
             };
             concat_token_stream(code.into(), impls)
         }
@@ -338,6 +409,62 @@ pub fn sql_table(input: TokenStream) -> TokenStream {
     gen
 }
 
+/// Expand the `#[SqlEnum]` attribute.
+/// This attribute must be used on enums to tell tql that they can be used as the type of a field.
+#[proc_macro_derive(SqlEnum)]
+pub fn sql_enum(input: TokenStream) -> TokenStream {
+    let item: Item =
+        match parse(input) {
+            Ok(item) => item,
+            Err(error) => return generate_errors(vec![Error::new(
+                    &format!("cannot parse expression in SqlEnum: {}", error), Span::call_site())]),
+        };
+
+    let mut compiler_errors = quote! {};
+    if let Item::Enum(item_enum) = item {
+        match get_enum_variants(&item_enum) {
+            Ok(variants) => return sql_enum_impl(&item_enum, &variants).into(),
+            Err(errors) => {
+                for error in errors {
+                    add_error(error, &mut compiler_errors);
+                }
+            },
+        }
+    }
+    else {
+        add_error(Error::new("Expected enum but found", item.span()), &mut compiler_errors);
+    }
+    compiler_errors.into()
+}
+
+/// Expand the `#[SqlType]` attribute.
+/// This attribute must be used on newtypes to tell tql that they can be used as the type of a field.
+#[proc_macro_derive(SqlType)]
+pub fn sql_type(input: TokenStream) -> TokenStream {
+    let item: Item =
+        match parse(input) {
+            Ok(item) => item,
+            Err(error) => return generate_errors(vec![Error::new(
+                    &format!("cannot parse expression in SqlType: {}", error), Span::call_site())]),
+        };
+
+    let mut compiler_errors = quote! {};
+    if let Item::Struct(item_struct) = item {
+        match get_newtype_field_type(&item_struct) {
+            Ok(field_type) => return sql_type_impl(&item_struct, &field_type).into(),
+            Err(errors) => {
+                for error in errors {
+                    add_error(error, &mut compiler_errors);
+                }
+            },
+        }
+    }
+    else {
+        add_error(Error::new("Expected struct but found", item.span()), &mut compiler_errors);
+    }
+    compiler_errors.into()
+}
+
 #[cfg(feature = "unstable")]
 fn respan_tokens_with(tokens: Tokens, span: proc_macro::Span) -> Tokens {
     let tokens: proc_macro2::TokenStream = respan_with(tokens.into(), span).into();
@@ -379,11 +506,20 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
     );
 
     let ident = Ident::from("_table");
+    // NOTE: the rows of insert_many() are not sent as parameters, but are consumed to create them.
+    let reference =
+        if let QueryType::InsertMany = args.query_type {
+            quote! {}
+        }
+        else {
+            quote! { & }
+        };
     {
         let mut add_arg = |arg: &Arg| {
             let arg_name =
                 match arg.expression {
                     Expr::Lit(_) => None,
+                    ref expr if is_none_literal(expr) => None,
                     _ => Some(next_name.next().expect("Next name")),
                 };
             if let Some(name) = arg.field_name.as_ref()
@@ -405,9 +541,24 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
                 });
                 #[cfg(feature = "unstable")]
                 let expr = &arg.expression;
-                assigns.push(quote_spanned! { arg.expression.span() =>
-                    #ident.#name = #convert_ident(&#expr.#to_owned_ident());
-                });
+                if is_none_literal(&arg.expression) {
+                    assigns.push(quote_spanned! { arg.expression.span() =>
+                        #ident.#name = #expr;
+                    });
+                }
+                else if arg.nullable {
+                    let some_ident = quote_spanned! { arg.expression.span() =>
+                        ::std::option::Option::Some
+                    };
+                    assigns.push(quote_spanned! { arg.expression.span() =>
+                        #ident.#name = #some_ident(#convert_ident(&#expr.#to_owned_ident()));
+                    });
+                }
+                else {
+                    assigns.push(quote_spanned! { arg.expression.span() =>
+                        #ident.#name = #convert_ident(&#expr.#to_owned_ident());
+                    });
+                }
                 fns.push(quote_spanned! { arg.expression.span() =>
                     // NOTE: hack to get the type required by the field struct.
                     fn #convert_ident<T: ::std::ops::Deref>(_arg: T) -> T::Target
@@ -420,6 +571,28 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
             arg_name
         };
 
+        // NOTE: the value of a field is converted by the parameter macro of the table, because the
+        // values of some types are sent as the type stored in the database.
+        let parameter_macro_name = Ident::new(&format!("tql_{}_parameter", table_ident), Span::call_site());
+        // NOTE: the value assigned to a field is checked by the validated parameter macro of the
+        // table, which makes the query fail if the value does not respect the validation attributes.
+        let validated_parameter_macro_name = Ident::new(&format!("tql_{}_validated_parameter", table_ident),
+            Span::call_site());
+        let parameter = |arg: &Arg, value: Tokens| {
+            match (&arg.field_name, &arg.field_name_prefix) {
+                (&Some(ref field_name), _) if arg.validated && arg.nullable =>
+                    quote! { #validated_parameter_macro_name!(#field_name, nullable #value) },
+                (&Some(ref field_name), _) if arg.validated =>
+                    quote! { #validated_parameter_macro_name!(#field_name, #value) },
+                (&Some(ref field_name), _) => quote! { #parameter_macro_name!(#field_name, #value) },
+                (&None, &Some(ref table)) if arg.primary_key => {
+                    let macro_name = Ident::new(&format!("tql_{}_primary_key_parameter", table), Span::call_site());
+                    quote! { #macro_name!(#value) }
+                },
+                _ => value,
+            }
+        };
+
         for arg in &args.arguments {
             let _name = add_arg(&arg);
             match arg.expression {
@@ -430,17 +603,17 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
                     {
                         if let Some(name) = _name {
                             metavars.push(quote! { #name });
-                            arg_refs.push(quote! { &#name })
+                            arg_refs.push(parameter(arg, quote! { #reference #name }))
                         }
                         else {
                             let expr = &arg.expression;
-                            arg_refs.push(quote! { &(#expr) });
+                            arg_refs.push(parameter(arg, quote! { #reference (#expr) }));
                         }
                     }
                     #[cfg(feature = "unstable")]
                     {
                         let expr = &arg.expression;
-                        arg_refs.push(quote! { &(#expr) });
+                        arg_refs.push(parameter(arg, quote! { #reference (#expr) }));
                     }
                 },
             }
@@ -476,6 +649,15 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
         typechecks.push(code);
     }
 
+    if !args.literal_assignments.is_empty() {
+        let macro_name = Ident::new(&format!("tql_{}_check_field_values", table_ident), Span::call_site());
+        let fields = args.literal_assignments.iter().map(|&(ref field, _)| field);
+        let values = args.literal_assignments.iter().map(|&(_, ref value)| value);
+        typechecks.push(quote! {
+            #macro_name!(#(#fields = #values),*);
+        });
+    }
+
     for data in &args.filter_method_calls {
         let call = &data.0;
         let field = &call.object_name;
@@ -509,10 +691,12 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
     let tql_ident = quote_spanned! { Span::call_site() =>
         ::tql
     };
-    for &(ref function, ref expr) in &args.aggregate_calls {
-        let function = Ident::new(function, Span::call_site());
+    for &(ref aggregate, ref expr) in &args.aggregate_calls {
+        let function = Ident::new(&aggregate.function, Span::call_site());
+        let instance = quote! { #ident };
+        let argument = aggregate_argument_to_rust(aggregate.argument.as_ref().expect("Aggregate argument"), &instance);
         typechecks.push(quote! {
-            let mut _data = #tql_ident::aggregates::#function();
+            let mut _data = #tql_ident::aggregates::compared_value(#tql_ident::aggregates::#function(&#argument));
             _data = #expr;
         });
     }
@@ -521,6 +705,14 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
         ::tql::SqlTable
     };
 
+    let args_expr =
+        if let QueryType::InsertMany = args.query_type {
+            quote! { #(#arg_refs)* }
+        }
+        else {
+            quote! { [#(#arg_refs),*] }
+        };
+
     let tokens = quote_spanned! { table_ident.span() => {
         // Type check the arguments by creating a dummy struct.
         // TODO: check that this let is not in the generated binary.
@@ -535,7 +727,7 @@ fn typecheck_arguments(args: &SqlQueryWithArgs) -> (Tokens, Vec<Tokens>) {
             };
         }
 
-        [#(#arg_refs),*]
+        #args_expr
     }};
     (tokens, metavars)
 }
@@ -570,6 +762,12 @@ pub fn check_missing_fields(input: TokenStream) -> TokenStream {
     gen_check_missing_fields(input)
 }
 
+#[cfg(feature = "unstable")]
+#[proc_macro]
+pub fn check_field_values(input: TokenStream) -> TokenStream {
+    gen_check_field_values(input)
+}
+
 struct Arguments(Punctuated<Expr, Token![,]>);
 
 impl syn::synom::Synom for Arguments {
@@ -612,6 +810,33 @@ pub fn stable_check_missing_fieds(input: TokenStream) -> TokenStream {
     empty_token_stream()
 }
 
+#[proc_macro_derive(StableCheckFieldValues)]
+pub fn stable_check_field_values(input: TokenStream) -> TokenStream {
+    let enumeration: Item = parse(input).unwrap();
+    if let Item::Enum(ItemEnum { ref variants, .. }) = enumeration {
+        let variant = &variants.first().unwrap().value().discriminant;
+        if let Expr::Field(ref field) = variant.as_ref().unwrap().1 {
+            if let Expr::Tuple(ref tuple) = *field.base {
+                if let Expr::Macro(ref macr) = **tuple.elems.first().unwrap().value() {
+                    let code = gen_check_field_values(macr.mac.tts.clone().into());
+                    let code = proc_macro2::TokenStream::from(code);
+
+                    let gen = quote! {
+                        macro_rules! __tql_call_macro_field_values {
+                            () => {{
+                                #code
+                            }};
+                        }
+                    };
+                    return gen.into();
+                }
+            }
+        }
+    }
+
+    empty_token_stream()
+}
+
 // TODO: make this function more robust.
 #[proc_macro_derive(StableToSql)]
 pub fn stable_to_sql(input: TokenStream) -> TokenStream {
@@ -679,3 +904,74 @@ pub fn stable_to_sql(input: TokenStream) -> TokenStream {
 
     empty_token_stream()
 }
+
+#[proc_macro_derive(StableSqlRaw)]
+pub fn stable_sql_raw(input: TokenStream) -> TokenStream {
+    let enumeration: Item = parse(input).unwrap();
+    if let Item::Enum(ItemEnum { ref variants, .. }) = enumeration {
+        let variant = &variants.first().unwrap().value().discriminant;
+        if let Expr::Field(ref field) = variant.as_ref().unwrap().1 {
+            if let Expr::Tuple(ref tuple) = *field.base {
+                if let Expr::Macro(ref macr) = **tuple.elems.first().unwrap().value() {
+                    let arguments: Vec<_> =
+                        match parse2::<Arguments>(macr.mac.tts.clone()) {
+                            Ok(arguments) => arguments.0.into_iter().collect(),
+                            Err(error) => return generate_errors(vec![Error::new(
+                                    &format!("cannot parse expression in sql_raw!(): {}", error),
+                                    Span::call_site())]),
+                        };
+                    let (code, metavars) =
+                        match to_raw_query(&arguments, macr.mac.tts.span()) {
+                            Ok(query) => {
+                                let metavars: Vec<_> = (0..query.arguments.len())
+                                    .map(|index| Ident::from(format!("__tql_arg{}", index)))
+                                    .collect();
+                                let arg_refs: Vec<_> = metavars.iter()
+                                    .map(|name| quote! { &#name })
+                                    .collect();
+                                (gen_raw_query(&query, default_connection_expr(), &arg_refs), metavars)
+                            },
+                            Err(errors) => (generate_errors(errors), vec![]),
+                        };
+                    let code = proc_macro2::TokenStream::from(code);
+                    let patterns: Vec<_> = metavars.iter()
+                        .map(|name| quote! { $#name:expr })
+                        .collect();
+                    let exprs: Vec<_> = metavars.iter()
+                        .map(|name| quote! { &$#name })
+                        .collect();
+                    let vars =
+                        match metavars.len() {
+                            0 => quote! {},
+                            1 => quote! {
+                                let #(#metavars),* = __tql_extract_raw_exprs!($($tt)*);
+                            },
+                            _ => quote! {
+                                let (#(#metavars),*) = __tql_extract_raw_exprs!($($tt)*);
+                            }
+                        };
+
+                    let gen = quote! {
+                        #[allow(unused)]
+                        macro_rules! __tql_extract_raw_exprs {
+                            ($__tql_table:ident, $__tql_query:expr #(, #patterns)*) => {
+                                (#(#exprs),*)
+                            };
+                        }
+
+                        macro_rules! __tql_call_macro_raw {
+                            ($connection:ident, $($tt:tt)*) => {{
+                                let ref connection = $connection;
+                                #vars
+                                #code
+                            }};
+                        }
+                    };
+                    return gen.into();
+                }
+            }
+        }
+    }
+
+    empty_token_stream()
+}
@@ -31,7 +31,7 @@ pub fn add_method<'a, T: Into<Option<&'a str>>>(object_type: &Type, return_type:
                                                 method: &str, template: T)
 {
     let methods = methods_singleton();
-    methods.insert(method.to_string(), SqlMethodTypes {
+    methods.entry(method.to_string()).or_insert_with(Vec::new).push(SqlMethodTypes {
         argument_types,
         object_type: object_type.clone(),
         return_type,
@@ -39,15 +39,33 @@ pub fn add_method<'a, T: Into<Option<&'a str>>>(object_type: &Type, return_type:
     });
 }
 
-/// Add a new aggregate `rust_function` mapping to `sql_function`.
-pub fn add_aggregate(rust_function: &str, sql_function: &str) {
+/// Add a new aggregate `rust_function` mapping to the SQL `template`.
+/// The template is the resulting SQL with `$0` as a placeholder for the aggregated field.
+pub fn add_aggregate(rust_function: &str, template: &str) {
     let aggregates = aggregates_singleton();
-    aggregates.insert(rust_function.to_string(), sql_function.to_string());
+    aggregates.insert(rust_function.to_string(), template.to_string());
 }
 
 /// Add the default SQL aggregate functions.
 pub fn add_initial_aggregates() {
-    add_aggregate("avg", "AVG");
+    // NOTE: the average is cast to the type of the field by the PostgreSQL backend.
+    add_aggregate("avg", "AVG($0)");
+    add_aggregate("count", "COUNT($0)");
+    add_aggregate("count_distinct", "COUNT(DISTINCT $0)");
+    add_aggregate("max", "MAX($0)");
+    add_aggregate("min", "MIN($0)");
+    add_aggregate("sum", "SUM($0)");
+
+    #[cfg(feature = "postgres")]
+    {
+        add_aggregate("group_concat", "STRING_AGG($0, ',')");
+        add_aggregate("string_agg", "STRING_AGG($0, ',')");
+    }
+    #[cfg(not(feature = "postgres"))]
+    {
+        add_aggregate("group_concat", "GROUP_CONCAT($0, ',')");
+        add_aggregate("string_agg", "GROUP_CONCAT($0, ',')");
+    }
 }
 
 /// Add the default SQL methods.
@@ -106,7 +124,65 @@ pub fn add_initial_methods() {
     #[cfg(feature = "rusqlite")]
     add_method(&Type::String, Type::Bool, vec![Type::String], "iregex", "$0 LIKE $1");
 
+    // JSON methods.
+    #[cfg(feature = "postgres")]
+    add_method(&Type::Json, Type::String, vec![Type::String], "get", "$0 ->> $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&Type::Json, Type::String, vec![Type::String], "get", "JSON_EXTRACT($0, '$.' || $1)");
+
+    #[cfg(feature = "postgres")]
+    add_method(&Type::Json, Type::Bool, vec![Type::Json], "contains", "$0 @> $1");
+    // NOTE: SQLite has no containment operator, so every node of the argument is checked against
+    // the document with the json1 functions (the elements of an array must be in the array of the
+    // document at the same path). The document is selected in a subquery since the columns of
+    // JSON_TREE() and JSON_EACH() would shadow a field with the same name.
+    #[cfg(feature = "rusqlite")]
+    add_method(&Type::Json, Type::Bool, vec![Type::Json], "contains",
+        "NOT EXISTS(SELECT 1 FROM (SELECT $0 AS __tql_document) AS __tql_d, JSON_TREE($1) AS __tql_node \
+         WHERE (__tql_node.parent IS NULL OR JSON_TYPE($1, __tql_node.path) IS NOT 'array') \
+         AND CASE __tql_node.type \
+         WHEN 'object' THEN JSON_TYPE(__tql_d.__tql_document, __tql_node.fullkey) IS NOT 'object' \
+         WHEN 'array' THEN JSON_TYPE(__tql_d.__tql_document, __tql_node.fullkey) IS NOT 'array' \
+         OR EXISTS(SELECT 1 FROM JSON_EACH(__tql_node.value) AS __tql_element \
+         WHERE __tql_element.value NOT IN (SELECT __tql_value.value \
+         FROM JSON_EACH(__tql_d.__tql_document, __tql_node.fullkey) AS __tql_value)) \
+         WHEN 'null' THEN JSON_TYPE(__tql_d.__tql_document, __tql_node.fullkey) IS NOT 'null' \
+         ELSE JSON_EXTRACT(__tql_d.__tql_document, __tql_node.fullkey) IS NOT __tql_node.value END)");
+
+    // Array methods.
+    #[cfg(any(feature = "postgres", feature = "rusqlite"))]
+    let array_generic = Type::Array(Box::new(Type::Generic));
+    #[cfg(feature = "postgres")]
+    add_method(&array_generic, Type::Bool, vec![Type::Generic], "contains", "$1 = ANY($0)");
+    #[cfg(feature = "rusqlite")]
+    add_method(&array_generic, Type::Bool, vec![Type::Generic], "contains", None);
+
+    #[cfg(feature = "postgres")]
+    add_method(&array_generic, Type::I32, vec![], "len", "CARDINALITY($0)");
+    #[cfg(feature = "rusqlite")]
+    add_method(&array_generic, Type::I32, vec![], "len", None);
+
+    #[cfg(feature = "postgres")]
+    add_method(&array_generic, Type::Bool, vec![array_generic.clone()], "overlaps", "$0 && $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&array_generic, Type::Bool, vec![array_generic.clone()], "overlaps", None);
+
     // Option methods.
     add_method(&Type::Nullable(Box::new(Type::Generic)), Type::Bool, vec![], "is_some", "$0 IS NOT NULL");
     add_method(&Type::Nullable(Box::new(Type::Generic)), Type::Bool, vec![], "is_none", "$0 IS NULL");
+
+    // Null-safe comparison methods.
+    #[cfg(any(feature = "postgres", feature = "rusqlite"))]
+    let nullable_generic = Type::Nullable(Box::new(Type::Generic));
+    #[cfg(feature = "postgres")]
+    add_method(&nullable_generic, Type::Bool, vec![nullable_generic.clone()], "is_distinct_from",
+        "$0 IS DISTINCT FROM $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&nullable_generic, Type::Bool, vec![nullable_generic.clone()], "is_distinct_from", "$0 IS NOT $1");
+
+    #[cfg(feature = "postgres")]
+    add_method(&nullable_generic, Type::Bool, vec![nullable_generic.clone()], "is_not_distinct_from",
+        "$0 IS NOT DISTINCT FROM $1");
+    #[cfg(feature = "rusqlite")]
+    add_method(&nullable_generic, Type::Bool, vec![nullable_generic.clone()], "is_not_distinct_from", "$0 IS $1");
 }
@@ -68,11 +68,16 @@ fn evaluate(expression: &Expression) -> i64 {
 /// Optimize the query.
 pub fn optimize(query: &mut Query) {
     match *query {
-        Query::Aggregate { .. } => (), // TODO
+        Query::Aggregate { ref mut limit, .. } => {
+            *limit = optimize_limit(limit);
+        },
+        Query::Count { .. } => (), // TODO
         Query::CreateTable { .. } => (), // Nothing to optimize.
         Query::Delete { .. } => (), // TODO
         Query::Drop { .. } => (), // Nothing to optimize.
+        Query::Exists { .. } => (), // TODO
         Query::Insert { .. } => (), // TODO
+        Query::InsertMany { .. } => (), // Nothing to optimize.
         Query::Select { ref mut limit, .. } => {
             *limit = optimize_limit(limit);
         },
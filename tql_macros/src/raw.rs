@@ -0,0 +1,227 @@
+/*
+ * Copyright (c) 2018 Boucher, Antoni <bouanto@zoho.com>
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy of
+ * this software and associated documentation files (the "Software"), to deal in
+ * the Software without restriction, including without limitation the rights to
+ * use, copy, modify, merge, publish, distribute, sublicense, and/or sell copies of
+ * the Software, and to permit persons to whom the Software is furnished to do so,
+ * subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY, FITNESS
+ * FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE AUTHORS OR
+ * COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, WHETHER
+ * IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN
+ * CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+ */
+
+//! Raw SQL queries analyzer.
+
+use std::cmp;
+
+use proc_macro2::Span;
+use syn::{
+    Expr,
+    ExprLit,
+    ExprMacro,
+    Ident,
+    Lit,
+    parse2,
+};
+use syn::spanned::Spanned;
+
+use error::{Error, Result, res};
+use string::{plural, plural_verb};
+use Arguments;
+
+/// A raw SQL query whose rows are converted to the `table_name` struct.
+pub struct RawQuery {
+    pub arguments: Vec<Expr>,
+    pub query: Expr,
+    pub table_name: Ident,
+}
+
+/// Convert the arguments of `sql_raw!()` (without the connection) to a `RawQuery`.
+/// It checks that the number of placeholders in the query matches the number of arguments.
+pub fn to_raw_query(arguments: &[Expr], position: Span) -> Result<RawQuery> {
+    let mut errors = vec![];
+    if arguments.len() < 2 {
+        return Err(vec![Error::new_with_code(
+            &format!("this macro takes at least 2 parameters but {} parameter{} supplied", arguments.len(),
+                     plural_verb(arguments.len())),
+            position, "E0061")]);
+    }
+
+    let table_name =
+        match arguments[0] {
+            Expr::Path(ref path) if path.qself.is_none() && path.path.segments.len() == 1 =>
+                path.path.segments.first().unwrap().into_value().ident,
+            ref expr => return Err(vec![Error::new("Expected table name", expr.span())]), // TODO: improve this message.
+        };
+
+    let query = arguments[1].clone();
+    let mut sql = String::new();
+    query_literal(&query, &mut sql, &mut errors);
+    let arguments = arguments[2..].to_vec();
+    if errors.is_empty() {
+        let count = placeholder_count(&sql);
+        if count != arguments.len() {
+            errors.push(Error::new(
+                &format!("this query has {} placeholder{} but {} argument{} supplied", count, plural(count),
+                         arguments.len(), plural_verb(arguments.len())),
+                query.span(),
+            ));
+        }
+    }
+
+    res(RawQuery {
+        arguments,
+        query,
+        table_name,
+    }, errors)
+}
+
+/// Get the SQL string literals from the query.
+/// The macro calls other than `concat!()`, like `tql_Table_field_list!()`, are skipped since they do not
+/// contain placeholders.
+fn query_literal(query: &Expr, sql: &mut String, errors: &mut Vec<Error>) {
+    match *query {
+        Expr::Lit(ExprLit { lit: Lit::Str(ref string), .. }) => sql.push_str(&string.value()),
+        Expr::Macro(ExprMacro { ref mac, .. }) => {
+            let is_concat = mac.path.segments.last()
+                .map(|segment| segment.value().ident == "concat")
+                .unwrap_or(false);
+            if is_concat {
+                match parse2::<Arguments>(mac.tts.clone()) {
+                    Ok(arguments) => {
+                        for argument in arguments.0 {
+                            query_literal(&argument, sql, errors);
+                        }
+                    },
+                    Err(error) => errors.push(Error::new(&error.to_string(), mac.tts.span())),
+                }
+            }
+            else {
+                // NOTE: separate the strings around the macro call so that they do not form a placeholder.
+                sql.push(' ');
+            }
+        },
+        _ => errors.push(Error::new("Expected string literal or concat!()", query.span())),
+    }
+}
+
+/// Get the number of arguments required by the placeholders of the query.
+/// The numbered placeholders ($1, ?2) can be used many times and the ? placeholders (SQLite
+/// only) take the next argument.
+/// The strings, the quoted identifiers, the comments and the dollar-quoted strings (PostgreSQL
+/// only) are skipped.
+fn placeholder_count(sql: &str) -> usize {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut count = 0;
+    let mut index = 0;
+    while index < chars.len() {
+        let character = chars[index];
+        index += 1;
+        match character {
+            // NOTE: a doubled quote inside a string or an identifier is skipped as the end of a
+            // string followed by the start of another one.
+            '\'' | '"' => index = skip_past(&chars, index, &[character]),
+            '-' if chars.get(index) == Some(&'-') => index = skip_past(&chars, index + 1, &['\n']),
+            '/' if chars.get(index) == Some(&'*') => index = skip_past(&chars, index + 1, &['*', '/']),
+            '$' if dollar_quote_tag(&chars[index - 1..]).is_some() => {
+                let tag = dollar_quote_tag(&chars[index - 1..]).expect("dollar quote tag");
+                index = skip_past(&chars, index - 1 + tag.len(), tag);
+            },
+            '$' | '?' if character == '$' || cfg!(feature = "rusqlite") => {
+                let start = index;
+                while index < chars.len() && chars[index].is_digit(10) {
+                    index += 1;
+                }
+                let number: String = chars[start..index].iter().collect();
+                count =
+                    match number.parse() {
+                        Ok(number) => cmp::max(count, number),
+                        Err(_) if character == '?' => count + 1,
+                        Err(_) => count,
+                    };
+            },
+            _ => (),
+        }
+    }
+    count
+}
+
+/// Get the index following the first occurrence of `end` in `chars` from `index` (or the length of
+/// `chars` when the string or comment is not terminated).
+fn skip_past(chars: &[char], index: usize, end: &[char]) -> usize {
+    (index..chars.len())
+        .find(|&start| chars[start..].starts_with(end))
+        .map_or(chars.len(), |start| start + end.len())
+}
+
+/// Get the tag, like `$$` or `$body$`, of the dollar-quoted string starting at the beginning of
+/// `chars`, if any.
+fn dollar_quote_tag(chars: &[char]) -> Option<&[char]> {
+    // NOTE: SQLite has no dollar-quoted strings and `$name` is a named parameter.
+    if cfg!(feature = "rusqlite") || chars.get(1).map_or(true, |character| character.is_digit(10)) {
+        return None;
+    }
+    let end = chars.iter()
+        .skip(1)
+        .position(|&character| !character.is_alphanumeric() && character != '_')? + 1;
+    if chars[end] == '$' {
+        Some(&chars[..end + 1])
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::placeholder_count;
+
+    #[test]
+    fn test_placeholder_count() {
+        assert_eq!(0, placeholder_count("SELECT * FROM Table"));
+        assert_eq!(2, placeholder_count("SELECT * FROM Table WHERE field1 = $1 AND field2 = $2 OR field1 = $1"));
+        assert_eq!(1, placeholder_count("SELECT * FROM Table WHERE field1 = '$2' AND field2 = $1"));
+        assert_eq!(1, placeholder_count("SELECT * FROM Table WHERE field1 = 'it''s $2' AND field2 = $1"));
+    }
+
+    #[test]
+    fn test_placeholder_count_quoted_identifiers() {
+        assert_eq!(1, placeholder_count(r#"SELECT "$2" FROM Table WHERE field1 = $1"#));
+        assert_eq!(1, placeholder_count(r#"SELECT "a ""$2"" b" FROM Table WHERE field1 = $1"#));
+    }
+
+    #[test]
+    fn test_placeholder_count_comments() {
+        assert_eq!(1, placeholder_count("SELECT * FROM Table -- field1 = $2\nWHERE field1 = $1"));
+        assert_eq!(1, placeholder_count("SELECT * FROM Table WHERE field1 = $1 -- $2"));
+        assert_eq!(1, placeholder_count("SELECT * FROM Table /* field1 = $2 */ WHERE field1 = $1"));
+        assert_eq!(2, placeholder_count("SELECT * FROM Table WHERE field1 = $1 - -$2"));
+    }
+
+    #[cfg(not(feature = "rusqlite"))]
+    #[test]
+    fn test_placeholder_count_dollar_quoted_strings() {
+        assert_eq!(1, placeholder_count("SELECT $$ $2 ' $$ FROM Table WHERE field1 = $1"));
+        assert_eq!(1, placeholder_count("SELECT $body$ $$ $2 $body$ FROM Table WHERE field1 = $1"));
+        assert_eq!(2, placeholder_count("SELECT * FROM Table WHERE field1 = $1 AND field2 = $2"));
+    }
+
+    #[cfg(feature = "rusqlite")]
+    #[test]
+    fn test_placeholder_count_question_marks() {
+        assert_eq!(2, placeholder_count("SELECT * FROM Table WHERE field1 = ? AND field2 = ?"));
+        assert_eq!(1, placeholder_count("SELECT * FROM Table WHERE field1 = '?' AND field2 = ? -- ?"));
+        assert_eq!(3, placeholder_count("SELECT * FROM Table WHERE field1 = ?2 AND field2 = ?"));
+        assert_eq!(2, placeholder_count("SELECT * FROM Table WHERE field1 = ? AND field2 = ?1 OR field3 = ?"));
+        assert_eq!(3, placeholder_count("SELECT * FROM Table WHERE field1 = ?3 AND field2 = ?1"));
+    }
+}
@@ -24,7 +24,7 @@
 use quote::Tokens;
 
 use ast::Aggregate;
-use sql::{SqlBackend, ToSql};
+use sql::{EnumSql, SqlBackend};
 
 pub struct DummySqlBackend {}
 
@@ -32,14 +32,24 @@ pub fn create_sql_backend() -> DummySqlBackend {
     DummySqlBackend { }
 }
 
-impl ToSql for Aggregate {
-    fn to_sql(&self, _index: &mut usize) -> String {
+impl SqlBackend for DummySqlBackend {
+    fn aggregate_to_tokens(&self, _aggregate: &Aggregate, _table: &str) -> Tokens {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
-}
 
-impl SqlBackend for DummySqlBackend {
-    fn insert_query(&self, _table: &str, _fields: &[String], _values: &[String]) -> Tokens {
+    fn enum_to_sql(&self, _name: &str, _variants: &[String]) -> EnumSql {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn insert_many_query(&self, _table: &str, _query_start: Tokens) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn insert_query(&self, _table: &str, _query_start: Tokens, _returning: Option<Tokens>) -> Tokens {
+        unreachable!("Enable one of the following features: sqlite, pg");
+    }
+
+    fn regex_check(&self, _column: &str, _regex: &str) -> Option<String> {
         unreachable!("Enable one of the following features: sqlite, pg");
     }
 }
@@ -33,7 +33,7 @@ use std::str::from_utf8;
 
 use proc_macro2::Span;
 use quote::Tokens;
-use syn::{Expr, Ident, Lit};
+use syn::{BinOp, Expr, ExprField, Ident, Lit, Member};
 
 use ast::{
     Aggregate,
@@ -42,6 +42,7 @@ use ast::{
     AggregateFilterExpression,
     Assignment,
     AssignmentOperator,
+    Distinct,
     Expression,
     Filter,
     FilterExpression,
@@ -51,11 +52,17 @@ use ast::{
     Limit,
     LogicalOperator,
     MethodCall,
+    OnConflict,
     Order,
     Query,
     RelationalOperator,
+    Returning,
+    SoftDelete,
     TypedField,
+    is_none_literal,
+    some_inner_expr,
 };
+use attribute::FieldAttribute;
 use ast::Limit::{
     EndRange,
     Index,
@@ -76,7 +83,21 @@ use self::postgres::create_sql_backend;
 use self::sqlite::create_sql_backend;
 
 trait SqlBackend {
-    fn insert_query(&self, table: &str, fields: &[String], values: &[String]) -> Tokens;
+    fn aggregate_to_tokens(&self, aggregate: &Aggregate, table: &str) -> Tokens;
+    fn enum_to_sql(&self, name: &str, variants: &[String]) -> EnumSql;
+    fn insert_many_query(&self, table: &str, query_start: Tokens) -> Tokens;
+    fn insert_query(&self, table: &str, query_start: Tokens, returning: Option<Tokens>) -> Tokens;
+    fn regex_check(&self, column: &str, regex: &str) -> Option<String>;
+}
+
+/// The SQL needed to use an enum as the type of a column.
+pub struct EnumSql {
+    /// The type of the column.
+    pub column_type: String,
+    /// The query creating the enum type, when the backend supports enum types.
+    pub create_query: Option<String>,
+    /// The condition checked on the column, when the backend does not support enum types.
+    pub values_check: Option<String>,
 }
 
 /// A generic trait for converting a value to SQL.
@@ -84,43 +105,102 @@ pub trait ToSql {
     fn to_sql(&self, index: &mut usize) -> String;
 }
 
-impl ToSql for [Aggregate] {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.iter().map(|aggregate| aggregate.to_sql(index)).collect::<Vec<_>>().join(", ")
+impl Aggregate {
+    /// Convert the aggregate to SQL, with the fields qualified by their `table` name.
+    fn to_tokens(&self, table: &str) -> Tokens {
+        let argument = aggregate_argument_to_tokens(self.argument.as_ref().expect("Aggregate argument"), table);
+        let mut template = self.template.splitn(2, "$0");
+        let start = template.next().unwrap_or("");
+        let end = template.next().unwrap_or("");
+        quote! {
+            #start, #argument, #end
+        }
     }
 }
 
-impl ToSql for AggregateFilter {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.operand1.to_sql(index) + " " +
-            &self.operator.to_sql(index) + " " +
-            &self.operand2.to_sql(index)
+/// Convert the argument of an aggregate function to SQL.
+fn aggregate_argument_to_tokens(expression: &Expression, table: &str) -> Tokens {
+    match *expression {
+        Expr::Binary(ref bin) => {
+            let left = aggregate_argument_to_tokens(&bin.left, table);
+            let right = aggregate_argument_to_tokens(&bin.right, table);
+            let operator =
+                match bin.op {
+                    BinOp::Add(_) => " + ",
+                    BinOp::Div(_) => " / ",
+                    BinOp::Mul(_) => " * ",
+                    BinOp::Rem(_) => " % ",
+                    BinOp::Sub(_) => " - ",
+                    _ => unreachable!("Check is done in check_aggregate_argument()"),
+                };
+            quote! {
+                #left, #operator, #right
+            }
+        },
+        Expr::Field(ExprField { ref base, member: Member::Named(member), .. }) => {
+            let related_table_macro_name =
+                Ident::new(&format!("tql_{}_related_tables", table), Span::call_site());
+            let member = format!(".{}", member);
+            quote_spanned! { Span::call_site() =>
+                #related_table_macro_name!(#base), #member
+            }
+        },
+        Expr::Paren(ref paren) => {
+            let expression = aggregate_argument_to_tokens(&paren.expr, table);
+            quote! {
+                "(", #expression, ")"
+            }
+        },
+        Expr::Path(ref path) => {
+            let field = path.path.segments.first().unwrap().into_value().ident;
+            string_token(&format!("{}.{}", table, field))
+        },
+        _ => string_token(&expression.to_sql(&mut 1)),
     }
 }
 
-impl ToSql for AggregateFilterExpression {
-    fn to_sql(&self, index: &mut usize) -> String {
+impl AggregateFilter {
+    fn to_tokens(&self, index: &mut usize, table: &str) -> Tokens {
+        let operand1 = create_sql_backend().aggregate_to_tokens(&self.operand1, table);
+        let operator = self.operator.to_sql(index);
+        let operand2 = self.operand2.to_sql(index);
+        quote! {
+            #operand1, " ", #operator, " ", #operand2
+        }
+    }
+}
+
+impl AggregateFilterExpression {
+    fn to_tokens(&self, index: &mut usize, table: &str) -> Tokens {
         match *self {
-            AggregateFilterExpression::Filter(ref filter) => filter.to_sql(index),
-            AggregateFilterExpression::Filters(ref filters) => filters.to_sql(index),
-            AggregateFilterExpression::NegFilter(ref filter) =>
-                "NOT ".to_string() +
-                &filter.to_sql(index),
-            AggregateFilterExpression::NoFilters => "".to_string(),
-            AggregateFilterExpression::ParenFilter(ref filter) =>
-                "(".to_string() +
-                &filter.to_sql(index) +
-                ")",
-            AggregateFilterExpression::FilterValue(ref filter_value) => filter_value.node.to_sql(index),
+            AggregateFilterExpression::Filter(ref filter) => filter.to_tokens(index, table),
+            AggregateFilterExpression::Filters(ref filters) => filters.to_tokens(index, table),
+            AggregateFilterExpression::NegFilter(ref filter) => {
+                let filter = filter.to_tokens(index, table);
+                quote! {
+                    "NOT ", #filter
+                }
+            },
+            AggregateFilterExpression::NoFilters => quote! { "" },
+            AggregateFilterExpression::ParenFilter(ref filter) => {
+                let filter = filter.to_tokens(index, table);
+                quote! {
+                    "(", #filter, ")"
+                }
+            },
+            AggregateFilterExpression::FilterValue(ref filter_value) => filter_value.node.to_tokens(table),
         }
     }
 }
 
-impl ToSql for AggregateFilters {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.operand1.to_sql(index) + " " +
-            &self.operator.to_sql(index) + " " +
-            &self.operand2.to_sql(index)
+impl AggregateFilters {
+    fn to_tokens(&self, index: &mut usize, table: &str) -> Tokens {
+        let operand1 = self.operand1.to_tokens(index, table);
+        let operator = self.operator.to_sql(index);
+        let operand2 = self.operand2.to_tokens(index, table);
+        quote! {
+            #operand1, " ", #operator, " ", #operand2
+        }
     }
 }
 
@@ -175,7 +255,7 @@ impl FilterExpression {
                     "(", #filter, ")"
                 }
             }
-            FilterExpression::FilterValue(ref filter_value) => filter_value.node.to_tokens(),
+            FilterExpression::FilterValue(ref filter_value) => filter_value.node.to_tokens(index),
         }
     }
 }
@@ -286,8 +366,8 @@ impl ToSql for String {
 impl Query {
     pub fn to_tokens(&self) -> Tokens {
         match *self {
-            Query::Aggregate { ref aggregates, ref aggregate_filter, ref filter, ref groups, ref joins, ref table } => {
-                let where_clause = filter_to_where_clause(filter);
+            Query::Aggregate { ref aggregates, ref aggregate_filter, ref filter, ref groups, ref joins, ref limit, ref order,
+                soft_delete, ref table } => {
                 let group_clause =
                     if !groups.is_empty() {
                         " GROUP BY "
@@ -302,17 +382,37 @@ impl Query {
                     else {
                         " HAVING "
                     };
-                let aggregates = aggregates.to_sql(&mut 1);
+                let order_clause =
+                    if has_order_clauses(order) {
+                        " ORDER BY "
+                    }
+                    else {
+                        ""
+                    };
+                let order = aggregate_order_to_tokens(order, aggregates, table);
+                let backend = create_sql_backend();
+                let aggregates = sep_by(aggregates.iter().map(|aggregate| backend.aggregate_to_tokens(aggregate, table)),
+                    ", ");
                 let check_joins = joins_to_check(&joins);
                 let joins = joins_to_tokens(&joins);
                 let index = &mut 1;
-                let filter = filter.to_tokens(index);
-                let groups = groups.to_sql(&mut 1);
-                let aggregate_filter = aggregate_filter.to_sql(index);
+                let filter = where_clause_to_tokens(filter, soft_delete, table, index);
+                let groups = groups_to_sql(groups, table);
+                let aggregate_filter = aggregate_filter.to_tokens(index, table);
+                let limit = limit.to_sql(index);
+                quote! {{
+                    #check_joins
+                    concat!("SELECT ", #aggregates, " FROM ", #table, #joins, #filter, #group_clause,
+                            #groups, #having_clause, #aggregate_filter, #order_clause, #order, #limit)
+                }}
+            },
+            Query::Count { ref filter, ref joins, soft_delete, ref table } => {
+                let check_joins = joins_to_check(&joins);
+                let joins = joins_to_tokens(&joins);
+                let filter = where_clause_to_tokens(filter, soft_delete, table, &mut 1);
                 quote! {{
                     #check_joins
-                    concat!("SELECT ", #aggregates, " FROM ", #table, #joins, #where_clause, #filter, #group_clause,
-                            #groups, #having_clause, #aggregate_filter)
+                    concat!("SELECT COUNT(*) FROM ", #table, #joins, #filter)
                 }}
             },
             Query::CreateTable { ref table } => {
@@ -321,30 +421,85 @@ impl Query {
                     #macro_name!()
                 }
             },
-            Query::Delete { ref filter, ref table, use_pk: _use_pk } => {
-                let where_clause = filter_to_where_clause(filter);
-                let filter = filter.to_tokens(&mut 1);
+            Query::Delete { ref filter, ref returning, soft_delete, ref table, use_pk: _use_pk } => {
+                // NOTE: the rows of a table with a soft_delete field are updated instead of deleted,
+                // so the rows that are already deleted are excluded to keep their deletion date.
+                // The rows are really deleted after a call to with_deleted() or only_deleted().
+                let delete =
+                    match soft_delete {
+                        SoftDelete::NotDeleted => Ident::new("delete", Span::call_site()),
+                        SoftDelete::OnlyDeleted | SoftDelete::WithDeleted => Ident::new("hard_delete", Span::call_site()),
+                    };
+                let filter = where_clause_to_tokens(filter, soft_delete, table, &mut 1);
+                let returning = returning_to_tokens(returning, table).unwrap_or_else(|| quote! { "" });
+                let macro_name = Ident::new(&format!("tql_{}_soft_delete", table), Span::call_site());
                 quote! {
-                    concat!("DELETE FROM ", #table, #where_clause, #filter)
+                    concat!(#macro_name!(#delete), #filter, #returning)
                 }
             },
             Query::Drop { ref table } => {
                 string_token(format!("DROP TABLE {table}", table = table).as_str())
             },
-            Query::Insert { ref assignments, ref table } => {
+            Query::Exists { ref filter, ref joins, soft_delete, ref table } => {
+                let check_joins = joins_to_check(&joins);
+                let joins = joins_to_tokens(&joins);
+                let filter = where_clause_to_tokens(filter, soft_delete, table, &mut 1);
+                quote! {{
+                    #check_joins
+                    concat!("SELECT EXISTS(SELECT 1 FROM ", #table, #joins, #filter, ")")
+                }}
+            },
+            Query::Insert { ref assignments, ref on_conflict, ref returning, ref table } => {
                 let fields: Vec<_> = assignments.iter().map(|assign|
                     assign.identifier.expect("Assignment identifier").to_sql(&mut 1)).collect();
                 let index = &mut 1;
                 let values: Vec<_> = assignments.iter().map(|assign|
                     assign.value.to_sql(index)
                 ).collect();
+                let returning =
+                    match (on_conflict, returning) {
+                        // NOTE: the primary key is returned for the inserted or updated row, and no row is
+                        // returned when the conflicting row is ignored.
+                        (&OnConflict::Ignore, &Returning::NoReturning) |
+                            (&OnConflict::Update(..), &Returning::NoReturning) => {
+                            let macro_name = Ident::new(&format!("tql_{}_primary_key_field", table),
+                                Span::call_site());
+                            Some(quote_spanned! { Span::call_site() =>
+                                " RETURNING ", #macro_name!()
+                            })
+                        },
+                        _ => returning_to_tokens(returning, table),
+                    };
+                let on_conflict = on_conflict_to_sql(on_conflict, table, index);
+                // NOTE: the fields with the auto_now or auto_now_add attributes are inserted with the
+                // current date/time.
+                let columns_macro_name = Ident::new(&format!("tql_{}_automatic_columns", table), Span::call_site());
+                let values_macro_name = Ident::new(&format!("tql_{}_automatic_values", table), Span::call_site());
+                let columns = string_token(&format!("INSERT INTO {}({}", table, fields.to_sql(&mut 1)));
+                let values = string_token(&format!(") VALUES({}", values.to_sql(&mut 1)));
+                let query_end = string_token(&format!("){}", on_conflict));
                 // Add the SQL code to get the inserted primary key.
                 // TODO: what to do when there is no primary key?
                 let backend = create_sql_backend();
-                backend.insert_query(table, &fields, &values)
+                backend.insert_query(table, quote! {
+                    #columns, #columns_macro_name!(), #values, #values_macro_name!(), #query_end
+                }, returning)
+            },
+            Query::InsertMany { ref table, .. } => {
+                // NOTE: the placeholders of the VALUES clause are added at runtime since they
+                // depend on the number of rows.
+                // The values of the automatic fields are added to the placeholders of each row.
+                let macro_name = Ident::new(&format!("tql_{}_insert_field_list", table), Span::call_site());
+                let columns_macro_name = Ident::new(&format!("tql_{}_automatic_columns", table), Span::call_site());
+                let query_start = string_token(&format!("INSERT INTO {}(", table));
+                let backend = create_sql_backend();
+                backend.insert_many_query(table, quote! {
+                    #query_start, #macro_name!(), #columns_macro_name!(), ") VALUES {}"
+                })
             },
-            Query::Select { ref filter, get: _get, ref joins, ref limit, ref order, ref table, use_pk: _use_pk } => {
-                let where_clause = filter_to_where_clause(filter);
+            Query::Select { ref distinct, ref filter, get: _get, ref joins, ref limit, ref order, soft_delete, ref table,
+                use_pk: _use_pk } => {
+                let distinct = distinct_to_tokens(distinct, table);
                 let order_clause =
                     if has_order_clauses(order) {
                         " ORDER BY "
@@ -357,22 +512,27 @@ impl Query {
                 let check_joins = joins_to_check(&joins);
                 let joins = joins_to_tokens(&joins);
                 let index = &mut 1;
-                let filter = filter.to_tokens(index);
+                let filter = where_clause_to_tokens(filter, soft_delete, table, index);
                 let order = order.to_sql(&mut 1);
                 let limit = limit.to_sql(&mut 1);
                 quote_spanned! { Span::call_site() => {
                     #check_joins
-                    concat!("SELECT ", #macro_name!() #joined_fields, " FROM ", #table, #joins, #where_clause, #filter,
+                    concat!("SELECT ", #distinct #macro_name!() #joined_fields, " FROM ", #table, #joins, #filter,
                         #order_clause, #order, #limit)
                 }}
             },
-            Query::Update { ref assignments, ref filter, ref table, use_pk: _use_pk } => {
-                let where_clause = filter_to_where_clause(filter);
+            Query::Update { ref assignments, ref filter, ref returning, soft_delete, ref table, use_pk: _use_pk } => {
                 let index = &mut 1;
                 let assignments = assignments.to_sql(index);
-                let filter = filter.to_tokens(index);
+                let filter = where_clause_to_tokens(filter, soft_delete, table, index);
+                let returning = returning_to_tokens(returning, table).unwrap_or_else(|| quote! { "" });
+                let automatic_assignments_macro_name =
+                    Ident::new(&format!("tql_{}_automatic_assignments", table), Span::call_site());
+                let version_assignment_macro_name =
+                    Ident::new(&format!("tql_{}_version_assignment", table), Span::call_site());
                 quote! {
-                    concat!("UPDATE ", #table, " SET ", #assignments, #where_clause, #filter)
+                    concat!("UPDATE ", #table, " SET ", #assignments, #automatic_assignments_macro_name!(),
+                            #version_assignment_macro_name!(), #filter, #returning)
                 }
             },
         }
@@ -381,7 +541,19 @@ impl Query {
 
 impl Filter {
     fn to_tokens(&self, index: &mut usize) -> Tokens {
-        let operand1 = self.operand1.to_tokens();
+        let operand1 = self.operand1.to_tokens(index);
+        if is_none_literal(&self.operand2) {
+            // NOTE: comparing with NULL always gives NULL, so use IS NULL instead.
+            // The analyzer only allows == and != to be used with None.
+            let operator =
+                match self.operator {
+                    RelationalOperator::NotEqual => "IS NOT NULL",
+                    _ => "IS NULL",
+                };
+            return quote! {
+                #operand1, " ", #operator
+            };
+        }
         let operator = self.operator.to_sql(index);
         let operand2 = self.operand2.to_sql(index);
         quote! {
@@ -402,29 +574,39 @@ impl Filters {
 }
 
 impl FilterValue {
-    fn to_tokens(&self) -> Tokens {
+    fn to_tokens(&self, index: &mut usize) -> Tokens {
         let sql =
             match *self {
                 FilterValue::Identifier(ref table, ref identifier) => format!("{}.{}", table, identifier.to_sql(&mut 1)),
-                FilterValue::MethodCall(MethodCall { ref arguments, ref object_name, ref method_name, ..  }) => {
+                FilterValue::MethodCall(MethodCall { ref arguments, ref object_name, ref method_name, ref table, ..  }) => {
                     let methods = methods_singleton();
-                    if let Some(method) = methods.get(&method_name.to_string()) {
-                        // In the template, $0 represents the object identifier and $1, $2, ... the
-                        // arguments.
-                        let mut sql = method.template.as_ref().map(|string| string.as_str()).unwrap_or_default()
-                            // NOTE: it's safe to use unwrap_or_default() because we check if the method exists for the
-                            // backend in the method analyzer.
-                            .replace("$0", &object_name.to_string());
-                        let mut index = 1;
-                        for argument in arguments {
-                            sql = sql.replace(&format!("${}", index), &argument.to_sql(&mut 1));
-                            index += 1;
-                        }
-                        sql
-                    }
-                    else {
-                        // NOTE: type checking will disallow this code to be executed.
-                        String::new()
+                    match methods.get(&method_name.to_string()).map(Vec::as_slice) {
+                        Some(&[ref method]) => {
+                            // In the template, $0 represents the object identifier and $1, $2, ... the
+                            // arguments.
+                            let mut sql = method.template.as_ref().map(|string| string.as_str()).unwrap_or_default()
+                                // NOTE: it's safe to use unwrap_or_default() because we check if the method exists for the
+                                // backend in the method analyzer.
+                                .replace("$0", &object_name.to_string());
+                            for (arg_index, argument) in arguments.iter().enumerate() {
+                                sql = sql.replace(&format!("${}", arg_index + 1), &argument.to_sql(index));
+                            }
+                            sql
+                        },
+                        Some(_) => {
+                            // NOTE: the template of a method defined on many types depends on the type of the
+                            // field, which is only known by the table macro.
+                            let macro_name = Ident::new(&format!("tql_{}_method", table), Span::call_site());
+                            let arguments = arguments.iter()
+                                .map(|argument| argument.to_sql(index));
+                            return quote! {
+                                #macro_name!(#object_name, #method_name #(, #arguments)*)
+                            };
+                        },
+                        None => {
+                            // NOTE: type checking will disallow this code to be executed.
+                            String::new()
+                        },
                     }
                 },
                 FilterValue::None => unreachable!("FilterValue::None in FilterValue::to_sql()"),
@@ -444,8 +626,15 @@ impl FilterValue {
 
 /// Convert a literal expression to its SQL representation.
 /// A non-literal is converted to ? for use with query parameters.
+/// `None` is converted to NULL and the value of `Some(value)` is converted.
 impl ToSql for Expression {
     fn to_sql(&self, index: &mut usize) -> String {
+        if is_none_literal(self) {
+            return "NULL".to_string();
+        }
+        if let Some(expr) = some_inner_expr(self) {
+            return expr.to_sql(index);
+        }
         match *self {
             Expr::Lit(ref literal) => {
                 match literal.lit {
@@ -491,23 +680,43 @@ fn escape(string: String) -> String {
 pub fn type_to_sql(typ: &Type, nullable: bool) -> Tokens {
     let sql_type =
         match *typ {
+            Type::Array(ref typ) => {
+                let sql = type_to_sql(&*typ, true);
+                let not_null =
+                    if nullable {
+                        quote! {}
+                    }
+                    else {
+                        quote! { , " NOT NULL" }
+                    };
+                return quote! {
+                    #sql, "[]" #not_null
+                };
+            },
             Type::Bool => "BOOLEAN",
             Type::ByteString => "BYTEA",
             Type::I8 | Type::Char => "CHARACTER(1)",
             Type::Custom(ref related_table_name) => {
                 let pk_macro_name = Ident::new(&format!("tql_{}_primary_key_field", related_table_name),
                     Span::call_site());
+                let pk_type_macro_name = Ident::new(&format!("tql_{}_primary_key_sql_type", related_table_name),
+                    Span::call_site());
                 return quote! {
-                    "INTEGER REFERENCES ", #related_table_name, "(", #pk_macro_name!(), ") NOT NULL"
+                    #pk_type_macro_name!(), " REFERENCES ", #related_table_name, "(", #pk_macro_name!(), ") NOT NULL"
                 };
                 // NOTE: if the field type is not an SQL table, an error is thrown.
             },
+            Type::Decimal => "NUMERIC",
             Type::F32 => "REAL",
             Type::F64 => "DOUBLE PRECISION",
             Type::Generic => "", // TODO: document why this is empty.
             Type::I16 => "SMALLINT",
             Type::I32 => "INTEGER",
             Type::I64 => "BIGINT",
+            #[cfg(feature = "rusqlite")]
+            Type::Json => "TEXT",
+            #[cfg(not(feature = "rusqlite"))]
+            Type::Json => "JSONB",
             Type::LocalDateTime => "TIMESTAMP WITH TIME ZONE",
             Type::NaiveDate => "DATE",
             Type::NaiveDateTime => "TIMESTAMP",
@@ -525,8 +734,31 @@ pub fn type_to_sql(typ: &Type, nullable: bool) -> Tokens {
             #[cfg(not(any(feature = "rusqlite", feature = "postgres")))]
             Type::Serial => unreachable!("Enable one of the following features: sqlite, pg"),
             Type::String => "CHARACTER VARYING",
+            // NOTE: the unsigned integers are stored in a signed type large enough to hold them,
+            // except u64 which is checked when it is sent.
+            Type::U8 => "SMALLINT",
+            Type::U16 => "INTEGER",
+            Type::U32 | Type::U64 => "BIGINT",
             Type::UnsupportedType(_) => "", // TODO: should panic. TODO: document why.
+            Type::UserDefined(ref type_name) => {
+                let macro_name = Ident::new(&format!("tql_{}_sql_type", type_name), Span::call_site());
+                let not_null =
+                    if nullable {
+                        quote! {}
+                    }
+                    else {
+                        quote! { , " NOT NULL" }
+                    };
+                return quote! {
+                    #macro_name!() #not_null
+                };
+                // NOTE: if the field type does not derive SqlEnum or SqlType, an error is thrown.
+            },
             Type::UtcDateTime => "TIMESTAMP WITH TIME ZONE",
+            #[cfg(feature = "rusqlite")]
+            Type::Uuid => "BLOB",
+            #[cfg(not(feature = "rusqlite"))]
+            Type::Uuid => "UUID",
         };
 
     let expr = string_literal(sql_type);
@@ -605,6 +837,42 @@ impl ToSql for RelationalOperator {
     }
 }
 
+/// Convert the validation attributes of a field to the CHECK constraints of its column.
+pub fn checks_to_sql(column: &str, attributes: &[FieldAttribute]) -> String {
+    let backend = create_sql_backend();
+    attributes.iter()
+        .filter_map(|attribute| {
+            let condition =
+                match *attribute {
+                    FieldAttribute::MaxLength(max_length) => format!("LENGTH({}) <= {}", column, max_length),
+                    FieldAttribute::Range(min, max) => {
+                        let min = min.map(|min| format!("{} >= {}", column, min));
+                        let max = max.map(|max| format!("{} <= {}", column, max));
+                        min.into_iter().chain(max).collect::<Vec<_>>().join(" AND ")
+                    },
+                    FieldAttribute::Regex(ref regex) => backend.regex_check(column, regex)?,
+                    _ => return None,
+                };
+            Some(format!(" CHECK ({})", condition))
+        })
+        .collect()
+}
+
+/// Get the SQL used for an enum with the specified `variants`.
+pub fn enum_to_sql(name: &str, variants: &[String]) -> EnumSql {
+    let backend = create_sql_backend();
+    backend.enum_to_sql(name, variants)
+}
+
+/// Convert the enum `variants` to a list of SQL strings.
+#[cfg(any(feature = "rusqlite", feature = "postgres"))]
+fn enum_labels(variants: &[String]) -> String {
+    variants.iter()
+        .map(|variant| format!("'{}'", variant))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub fn fields_to_sql(fields: &[TypedField]) -> Tokens {
     let fields = fields.iter()
         .map(|field| {
@@ -617,6 +885,63 @@ pub fn fields_to_sql(fields: &[TypedField]) -> Tokens {
     sep_by(fields, ", ")
 }
 
+/// Convert a `Distinct` to the start of the SELECT list.
+fn distinct_to_tokens(distinct: &Distinct, table: &str) -> Tokens {
+    match *distinct {
+        Distinct::Distinct => quote! { "DISTINCT ", },
+        Distinct::DistinctOn(ref fields) => {
+            let fields: Vec<_> = fields.iter()
+                .map(|field| format!("{}.{}", table, field))
+                .collect();
+            let fields = string_token(&fields.join(", "));
+            quote! { "DISTINCT ON (", #fields, ") ", }
+        },
+        Distinct::NoDistinct => quote! {},
+    }
+}
+
+/// Convert an `OnConflict` to SQL.
+/// The fields in the assignments are qualified by the `table` name since the row that would have
+/// been inserted is also available (as `excluded`) in the DO UPDATE clause.
+fn on_conflict_to_sql(on_conflict: &OnConflict, table: &str, index: &mut usize) -> String {
+    match *on_conflict {
+        OnConflict::Ignore => " ON CONFLICT DO NOTHING".to_string(),
+        OnConflict::NoConflict => String::new(),
+        OnConflict::Update(ref fields, ref assignments) => {
+            let fields: Vec<_> = fields.iter().map(|field| field.to_string()).collect();
+            let assignments: Vec<_> = assignments.iter()
+                .map(|assignment| {
+                    let identifier = assignment.identifier.expect("Assignment identifier").to_sql(index);
+                    let qualified_identifier = format!("{}.{}", table, identifier);
+                    identifier +
+                        &assignment.operator.node.to_sql(index).replace("{}", &qualified_identifier) +
+                        &assignment.value.to_sql(index)
+                })
+                .collect();
+            format!(" ON CONFLICT ({}) DO UPDATE SET {}", fields.join(", "), assignments.join(", "))
+        },
+    }
+}
+
+/// Convert a `Returning` to the SQL RETURNING clause, if any.
+/// The returned columns are in the order expected to build the table struct.
+fn returning_to_tokens(returning: &Returning, table: &str) -> Option<Tokens> {
+    match *returning {
+        Returning::Fields(ref fields) => {
+            let fields: Vec<_> = fields.iter().map(|field| field.to_string()).collect();
+            let fields = fields.join(", ");
+            Some(quote! { " RETURNING ", #fields })
+        },
+        Returning::NoReturning => None,
+        Returning::Row => {
+            let macro_name = Ident::new(&format!("tql_{}_column_list", table), Span::call_site());
+            Some(quote_spanned! { Span::call_site() =>
+                " RETURNING ", #macro_name!()
+            })
+        },
+    }
+}
+
 /// Convert a `FilterExpression` to either " WHERE " or the empty string if there are no filters.
 fn filter_to_where_clause(filter: &FilterExpression) -> &str {
     match *filter {
@@ -625,6 +950,65 @@ fn filter_to_where_clause(filter: &FilterExpression) -> &str {
     }
 }
 
+/// Convert a filter to a WHERE clause which also filters on the `#[tql(soft_delete)]` field of the
+/// table, if any.
+/// The condition on this field is given by the `tql_{table}_soft_delete!()` macro since only the
+/// table struct knows whether it has such a field.
+fn where_clause_to_tokens(filter: &FilterExpression, soft_delete: SoftDelete, table: &str, index: &mut usize) -> Tokens {
+    let where_clause = filter_to_where_clause(filter);
+    let filter_tokens = filter.to_tokens(index);
+    let rows =
+        match soft_delete {
+            SoftDelete::NotDeleted => "not_deleted",
+            SoftDelete::OnlyDeleted => "only_deleted",
+            SoftDelete::WithDeleted => return quote! { #where_clause, #filter_tokens },
+        };
+    let rows = Ident::new(rows, Span::call_site());
+    let macro_name = Ident::new(&format!("tql_{}_soft_delete", table), Span::call_site());
+    if let FilterExpression::NoFilters = *filter {
+        quote! { #macro_name!(where_clause, #rows) }
+    }
+    else {
+        // NOTE: the filter is wrapped in parentheses because it might contain a OR.
+        quote! { " WHERE ", #macro_name!(filter_start, #rows), #filter_tokens, #macro_name!(filter_end) }
+    }
+}
+
+/// Convert the order of an aggregate query to SQL.
+/// An aggregate result name is replaced by the aggregate function call since the result is not
+/// aliased in the SELECT list.
+fn aggregate_order_to_tokens(orders: &[Order], aggregates: &[Aggregate], table: &str) -> Tokens {
+    let orders = orders.iter()
+        .filter_map(|order| {
+            let (field, direction) =
+                match *order {
+                    Order::Ascending(ref field) => (field, ""),
+                    Order::Descending(ref field) => (field, " DESC"),
+                    Order::NoOrder => return None,
+                };
+            let aggregate = aggregates.iter()
+                .find(|aggregate| aggregate.result_name.as_ref() == Some(field));
+            let field =
+                match aggregate {
+                    Some(aggregate) => aggregate.to_tokens(table),
+                    None => string_token(&format!("{}.{}", table, field)),
+                };
+            Some(quote! {
+                #field, #direction
+            })
+        });
+    sep_by(orders, ", ")
+}
+
+/// Convert the fields of a `values()` to SQL, qualified with the table name so that they are not
+/// ambiguous when the query has joins.
+fn groups_to_sql(groups: &[Ident], table: &str) -> String {
+    groups.iter()
+        .map(|group| format!("{}.{}", table, group))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn has_order_clauses(orders: &[Order]) -> bool {
     for order in orders {
         if let Order::NoOrder = *order {
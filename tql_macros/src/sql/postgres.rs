@@ -25,8 +25,8 @@ use proc_macro2::Span;
 use quote::Tokens;
 use syn::Ident;
 
-use ast::Aggregate;
-use sql::{SqlBackend, ToSql, string_token};
+use ast::{Aggregate, aggregate_fields};
+use sql::{EnumSql, SqlBackend, enum_labels, escape};
 
 pub struct PostgresSqlBackend {}
 
@@ -34,26 +34,59 @@ pub fn create_sql_backend() -> PostgresSqlBackend {
     PostgresSqlBackend { }
 }
 
-impl ToSql for Aggregate {
-    fn to_sql(&self, index: &mut usize) -> String {
-        // TODO: do not hard-code the type.
-        "CAST(".to_string() + &self.sql_function.to_sql(index) + "(" +
-            &self.field.expect("Aggregate field").to_sql(index) + ") AS DOUBLE PRECISION)"
+impl SqlBackend for PostgresSqlBackend {
+    fn aggregate_to_tokens(&self, aggregate: &Aggregate, table: &str) -> Tokens {
+        let sql = aggregate.to_tokens(table);
+        let argument = aggregate.argument.as_ref().expect("Aggregate argument");
+        // NOTE: the fields of an arithmetic expression all have the same type (otherwise, the
+        // type checking fails), so the first one is used to get the type of the sum.
+        match aggregate_fields(argument).first() {
+            // NOTE: PostgreSQL returns a NUMERIC for the sum of BIGINT and for the average of
+            // integers, so cast the result to the type expected by the Rust code.
+            Some(field) if aggregate.function == "avg" || aggregate.function == "sum" => {
+                let macro_name = Ident::new(&format!("tql_{}_{}_type", table, aggregate.function),
+                    Span::call_site());
+                quote! {
+                    "CAST(", #sql, " AS ", #macro_name!(#field), ")"
+                }
+            },
+            _ if aggregate.function == "avg" => quote! {
+                "CAST(", #sql, " AS DOUBLE PRECISION)"
+            },
+            _ => sql,
+        }
     }
-}
 
-impl SqlBackend for PostgresSqlBackend {
-    fn insert_query(&self, table: &str, fields: &[String], values: &[String]) -> Tokens {
-        let query_start =
-            format!("INSERT INTO {table}({fields}) VALUES({values}) RETURNING ",
-            table = table,
-            fields = fields.to_sql(&mut 1),
-            values = values.to_sql(&mut 1),
-            );
-        let query_start = string_token(&query_start);
+    fn enum_to_sql(&self, name: &str, variants: &[String]) -> EnumSql {
+        // NOTE: PostgreSQL has no CREATE TYPE IF NOT EXISTS, so the error is ignored when the type
+        // was already created by another table.
+        let create_query = format!("DO $$ BEGIN CREATE TYPE {} AS ENUM ({}); \
+                                   EXCEPTION WHEN duplicate_object THEN NULL; END $$", name, enum_labels(variants));
+        EnumSql {
+            column_type: name.to_string(),
+            create_query: Some(create_query),
+            values_check: None,
+        }
+    }
+
+    fn insert_many_query(&self, table: &str, query_start: Tokens) -> Tokens {
         let macro_name = Ident::new(format!("tql_{}_primary_key_field", table).as_str(), Span::call_site());
         quote! {
-            concat!(#query_start, #macro_name!())
+            concat!(#query_start, " RETURNING ", #macro_name!())
         }
     }
+
+    fn insert_query(&self, table: &str, query_start: Tokens, returning: Option<Tokens>) -> Tokens {
+        let returning = returning.unwrap_or_else(|| {
+            let macro_name = Ident::new(format!("tql_{}_primary_key_field", table).as_str(), Span::call_site());
+            quote! { " RETURNING ", #macro_name!() }
+        });
+        quote! {
+            concat!(#query_start, #returning)
+        }
+    }
+
+    fn regex_check(&self, column: &str, regex: &str) -> Option<String> {
+        Some(format!("{} ~ '{}'", column, escape(regex.to_string())))
+    }
 }
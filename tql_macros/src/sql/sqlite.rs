@@ -21,10 +21,12 @@
 
 //! The SQLite code generator.
 
+use proc_macro2::Span;
 use quote::Tokens;
+use syn::Ident;
 
 use ast::Aggregate;
-use sql::{SqlBackend, ToSql};
+use sql::{EnumSql, SqlBackend, enum_labels};
 
 pub struct SqliteSqlBackend {}
 
@@ -32,22 +34,39 @@ pub fn create_sql_backend() -> SqliteSqlBackend {
     SqliteSqlBackend { }
 }
 
-impl ToSql for Aggregate {
-    fn to_sql(&self, index: &mut usize) -> String {
-        self.sql_function.to_sql(index) + "(" + &self.field.expect("Aggregate field").to_sql(index) + ")"
+impl SqlBackend for SqliteSqlBackend {
+    fn aggregate_to_tokens(&self, aggregate: &Aggregate, table: &str) -> Tokens {
+        aggregate.to_tokens(table)
     }
-}
 
-impl SqlBackend for SqliteSqlBackend {
-    fn insert_query(&self, table: &str, fields: &[String], values: &[String]) -> Tokens {
-        let query =
-            format!("INSERT INTO {table}({fields}) VALUES({values})",
-            table = table,
-            fields = fields.to_sql(&mut 1),
-            values = values.to_sql(&mut 1),
-            );
+    fn enum_to_sql(&self, _name: &str, variants: &[String]) -> EnumSql {
+        // NOTE: SQLite has no enum type, so the name of the variant is saved in a text column.
+        EnumSql {
+            column_type: "TEXT".to_string(),
+            create_query: None,
+            values_check: Some(format!("IN ({})", enum_labels(variants))),
+        }
+    }
+
+    fn insert_many_query(&self, table: &str, query_start: Tokens) -> Tokens {
+        // NOTE: the inserted rows do not necessarily have consecutive rowids, so their primary keys
+        // are returned.
+        let macro_name = Ident::new(format!("tql_{}_primary_key_field", table).as_str(), Span::call_site());
         quote! {
-            concat!(#query)
+            concat!(#query_start, " RETURNING ", #macro_name!())
         }
     }
+
+    fn insert_query(&self, _table: &str, query_start: Tokens, returning: Option<Tokens>) -> Tokens {
+        let returning = returning.unwrap_or_else(|| quote! { "" });
+        quote! {
+            concat!(#query_start, #returning)
+        }
+    }
+
+    fn regex_check(&self, _column: &str, _regex: &str) -> Option<String> {
+        // NOTE: SQLite does not provide a REGEXP function by default, so the regex is only checked
+        // at runtime, by the validate() method and when the value is sent as a query parameter.
+        None
+    }
 }
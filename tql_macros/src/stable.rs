@@ -1,5 +1,6 @@
 //! Workaround to make get the hygiene right on stable.
 
+use proc_macro2::Span;
 use quote::Tokens;
 use syn::{
     Expr,
@@ -10,6 +11,7 @@ use ast::{
     Aggregate,
     Assignment,
     AssignmentOperator,
+    Distinct,
     FilterExpression,
     FilterValue,
     Groups,
@@ -17,9 +19,14 @@ use ast::{
     Limit,
     LogicalOperator,
     MethodCall,
+    OnConflict,
     Order,
     Query,
     RelationalOperator,
+    Returning,
+    is_none_literal,
+    mirror_operator,
+    some_inner_expr,
 };
 use parser::MethodCalls;
 
@@ -33,7 +40,9 @@ pub fn generate_macro_patterns(query: &Query, calls: &MethodCalls) -> Tokens {
         let name = call.name;
         let args =
             match name.as_ref() {
-                "all" | "create" | "delete" | "drop" => quote! {},
+                "all" | "count" | "create" | "delete" | "distinct" | "drop" | "exists" | "on_conflict_ignore" |
+                    "only_deleted" | "with_deleted" =>
+                    quote! {},
                 "aggregate" =>
                     if let Query::Aggregate { ref aggregates, .. } = *query {
                         aggregates_to_args(aggregates)
@@ -41,38 +50,66 @@ pub fn generate_macro_patterns(query: &Query, calls: &MethodCalls) -> Tokens {
                     else {
                         quote! {}
                     },
+                "distinct_on" =>
+                    match *query {
+                        Query::Select { distinct: Distinct::DistinctOn(ref fields), .. } => quote! { #(#fields),* },
+                        _ => quote! {},
+                    },
                 "filter" | "get" =>
                     match *query {
-                        Query::Aggregate { ref filter, .. } | Query::Delete { ref filter, .. } |
+                        Query::Aggregate { ref filter, .. } | Query::Count { ref filter, .. } |
+                            Query::Delete { ref filter, .. } | Query::Exists { ref filter, .. } |
                             Query::Select { ref filter, .. } | Query::Update { ref filter, .. } =>
                             filter_to_args(filter, &mut dummy_count, &mut count, &mut args),
                         _ => quote! {},
                     },
                 "join" =>
                     match *query {
-                        Query::Aggregate { ref joins, .. } | Query::Select { ref joins, .. } =>
+                        Query::Aggregate { ref joins, .. } | Query::Count { ref joins, .. } |
+                            Query::Exists { ref joins, .. } | Query::Select { ref joins, .. } =>
                             joins_to_args(joins),
                         _ => quote! {},
                     }
-                "insert" | "update" =>
+                "insert" =>
                     match *query {
-                        Query::Insert { ref assignments, .. } | Query::Update { ref assignments, .. } =>
+                        Query::Insert { ref assignments, .. } =>
+                            assignments_to_args(assignments, &mut dummy_count, &mut count, &mut args),
+                        _ => quote! {},
+                    },
+                "insert_many" =>
+                    match *query {
+                        Query::InsertMany { ref rows, .. } => expr_to_args(rows, &mut dummy_count, &mut count, &mut args),
+                        _ => quote! {},
+                    },
+                "on_conflict" =>
+                    match *query {
+                        Query::Insert { on_conflict: OnConflict::Update(ref fields, _), .. } => quote! { #(#fields),* },
+                        _ => quote! {},
+                    },
+                "update" =>
+                    match *query {
+                        Query::Insert { on_conflict: OnConflict::Update(_, ref assignments), .. } |
+                            Query::Update { ref assignments, .. } =>
                             assignments_to_args(assignments, &mut dummy_count, &mut count, &mut args),
                         _ => quote! {},
                     },
                 "limit" =>
-                    if let Query::Select { ref limit, .. } = *query {
-                        limit_to_args(limit, &mut dummy_count, &mut count, &mut args)
-                    }
-                    else {
-                        quote! {}
+                    match *query {
+                        Query::Aggregate { ref limit, .. } | Query::Select { ref limit, .. } =>
+                            limit_to_args(limit, &mut dummy_count, &mut count, &mut args),
+                        _ => quote! {},
+                    },
+                "returning" =>
+                    match *query {
+                        Query::Delete { returning: Returning::Fields(ref fields), .. } |
+                            Query::Insert { returning: Returning::Fields(ref fields), .. } |
+                            Query::Update { returning: Returning::Fields(ref fields), .. } => quote! { #(#fields),* },
+                        _ => quote! {},
                     },
                 "sort" =>
-                    if let Query::Select { ref order, .. } = *query {
-                        order_to_args(order)
-                    }
-                    else {
-                        quote! {}
+                    match *query {
+                        Query::Aggregate { ref order, .. } | Query::Select { ref order, .. } => order_to_args(order),
+                        _ => quote! {},
                     },
                 "values" =>
                     if let Query::Aggregate { ref groups, .. } = *query {
@@ -122,13 +159,23 @@ fn filter_to_args(filter: &FilterExpression, dummy_count: &mut i32, count: &mut
                 if left == quote! {} {
                     quote! {}
                 }
+                else if filter.mirrored {
+                    rel_op_to_args(mirror_operator(filter.operator))
+                }
                 else {
                     rel_op_to_args(filter.operator)
                 };
             let right = &filter.operand2;
             let right = expr_to_args(right, dummy_count, count, args);
-            quote! {
-                #left #op #right
+            if filter.mirrored {
+                quote! {
+                    #right #op #left
+                }
+            }
+            else {
+                quote! {
+                    #left #op #right
+                }
             }
         },
         FilterExpression::Filters(ref filters) => {
@@ -249,6 +296,13 @@ fn limit_to_args(limit: &Limit, dummy_count: &mut i32, count: &mut i32, args: &m
 }
 
 fn expr_to_args(expr: &Expr, dummy_count: &mut i32, count: &mut i32, args: &mut Vec<Ident>) -> Tokens {
+    if is_none_literal(expr) {
+        return quote! { None };
+    }
+    if let Some(expr) = some_inner_expr(expr) {
+        let expr = expr_to_args(expr, dummy_count, count, args);
+        return quote! { Some(#expr) };
+    }
     match *expr {
         Expr::Lit(_) => {
             *dummy_count += 1;
@@ -293,10 +347,10 @@ fn aggregates_to_args(aggregates: &[Aggregate]) -> Tokens {
                 else {
                     quote! {}
                 };
-            let function = &aggregate.function;
-            let field = &aggregate.field;
+            let function = Ident::new(&aggregate.function, Span::call_site());
+            let argument = &aggregate.argument;
             quote! {
-                #result #function(#field)
+                #result #function(#argument)
             }
         });
     quote! {
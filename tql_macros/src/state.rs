@@ -37,7 +37,7 @@ use ast::WithSpan;
 use methods::{add_initial_aggregates, add_initial_methods};
 use types::Type;
 
-/// A collection of tql aggregate functions.
+/// A collection mapping tql aggregate functions to SQL templates.
 pub type SqlAggregates = HashMap<String, String>;
 
 #[derive(Debug)]
@@ -49,8 +49,8 @@ pub struct BothTypes {
 /// A collection of fields from an `SqlTable`.
 pub type SqlFields = BTreeMap<Ident, BothTypes>;
 
-/// A collection mapping method names to methods.
-pub type SqlMethods = HashMap<String, SqlMethodTypes>;
+/// A collection mapping method names to the methods of this name (one per object type).
+pub type SqlMethods = HashMap<String, Vec<SqlMethodTypes>>;
 
 /// Tql method return type, argument types and template.
 pub struct SqlMethodTypes {
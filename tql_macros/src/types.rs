@@ -33,8 +33,11 @@ use syn::{
     Lit,
     Path,
     PathArguments,
+    PathSegment,
     TypePath,
 };
+use syn::punctuated::Punctuated;
+use syn::token::Colon2;
 
 use ast::Expression;
 use sql;
@@ -42,10 +45,12 @@ use sql;
 /// A field type.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Type {
+    Array(Box<Type>),
     Bool,
     ByteString,
     Char,
     Custom(String),
+    Decimal,
     F32,
     F64,
     Generic,
@@ -53,6 +58,7 @@ pub enum Type {
     I16,
     I32,
     I64,
+    Json,
     LocalDateTime,
     NaiveDate,
     NaiveDateTime,
@@ -60,18 +66,26 @@ pub enum Type {
     Nullable(Box<Type>),
     Serial,
     String,
+    U8,
+    U16,
+    U32,
+    U64,
     UnsupportedType(String),
+    UserDefined(String),
     UtcDateTime,
+    Uuid,
 }
 
 impl Display for Type {
     /// Get a string representation of the SQL `Type` for display in error messages.
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         let typ = match *self {
+            Type::Array(ref typ) => "Vec<".to_string() + &typ.to_string() + ">",
             Type::Bool => "bool".to_string(),
             Type::ByteString => "Vec<u8>".to_string(),
             Type::Char => "char".to_string(),
             Type::Custom(ref typ) => typ.clone(),
+            Type::Decimal => "rust_decimal::Decimal".to_string(),
             Type::F32 => "f32".to_string(),
             Type::F64 => "f64".to_string(),
             Type::Generic => "".to_string(),
@@ -79,6 +93,7 @@ impl Display for Type {
             Type::I16 => "i16".to_string(),
             Type::I32 => "i32".to_string(),
             Type::I64 => "i64".to_string(),
+            Type::Json => "serde_json::Value".to_string(),
             Type::LocalDateTime => "chrono::datetime::DateTime<chrono::offset::Local>".to_string(),
             Type::NaiveDate => "chrono::naive::NaiveDate".to_string(),
             Type::NaiveDateTime => "chrono::naive::NaiveDateTime".to_string(),
@@ -86,13 +101,30 @@ impl Display for Type {
             Type::Nullable(ref typ) => "Option<".to_string() + &typ.to_string() + ">",
             Type::Serial => "i32".to_string(),
             Type::String => "String".to_string(),
+            Type::U8 => "u8".to_string(),
+            Type::U16 => "u16".to_string(),
+            Type::U32 => "u32".to_string(),
+            Type::U64 => "u64".to_string(),
             Type::UnsupportedType(_) => "".to_string(),
+            Type::UserDefined(ref typ) => typ.clone(),
             Type::UtcDateTime => "chrono::datetime::DateTime<chrono::offset::Utc>".to_string(),
+            Type::Uuid => "uuid::Uuid".to_string(),
         };
         write!(f, "{}", typ)
     }
 }
 
+impl Type {
+    /// Check if the type is an unsigned integer, which is stored as a wider signed integer.
+    pub fn is_unsigned(&self) -> bool {
+        match *self {
+            Type::Nullable(ref typ) => typ.is_unsigned(),
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 => true,
+            _ => false,
+        }
+    }
+}
+
 /// Convert a `Type` to its SQL representation.
 pub fn type_to_sql(typ: &Type) -> Tokens {
     sql::type_to_sql(typ, false)
@@ -126,14 +158,21 @@ impl PartialEq<Expression> for Type {
                     IntSuffix::I16 => *typ == Type::I16,
                     IntSuffix::I32 => *typ == Type::I32 || *typ == Type::Serial,
                     IntSuffix::I64 => *typ == Type::I64,
-                    IntSuffix::U8 | IntSuffix::U16 | IntSuffix::U32 | IntSuffix::U64 | IntSuffix::U128 |
-                        IntSuffix::Usize | IntSuffix::I128 => false,
+                    IntSuffix::U8 => *typ == Type::U8,
+                    IntSuffix::U16 => *typ == Type::U16,
+                    IntSuffix::U32 => *typ == Type::U32,
+                    IntSuffix::U64 => *typ == Type::U64,
+                    IntSuffix::U128 | IntSuffix::Usize | IntSuffix::I128 => false,
                     IntSuffix::None =>
                         *typ == Type::I8 ||
                         *typ == Type::I16 ||
                         *typ == Type::I32 ||
                         *typ == Type::I64 ||
-                        *typ == Type::Serial,
+                        *typ == Type::Serial ||
+                        *typ == Type::U8 ||
+                        *typ == Type::U16 ||
+                        *typ == Type::U32 ||
+                        *typ == Type::U64,
                 },
             Expr::Lit(ExprLit { lit: Lit::Str(_), .. }) => *typ == Type::String,
             _ => true, // Returns true, because the type checking for non-literal is done later.
@@ -160,12 +199,16 @@ impl<'a> From<&'a Path> for Type {
                     },
                     None => Type::UnsupportedType("DateTime".to_string()),
                 },
+                "Decimal" => Type::Decimal,
                 "f32" => Type::F32,
                 "f64" => Type::F64,
                 "i8" => Type::I8,
                 "i16" => Type::I16,
                 "i32" => Type::I32,
                 "i64" => Type::I64,
+                // NOTE: the types recognized by their name (Decimal, Json, Value and Uuid) are checked to
+                // be the types from the other crates by the code generated by get_struct_fields().
+                "Json" | "Value" => Type::Json,
                 "ForeignKey" => match get_type_parameter(&first_segment.arguments) {
                     Some(ty) => Type::Custom(ty),
                     None => Type::UnsupportedType("ForeignKey".to_string()),
@@ -194,22 +237,67 @@ impl<'a> From<&'a Path> for Type {
                 "String" => {
                     Type::String
                 },
+                "u8" => Type::U8,
+                "u16" => Type::U16,
+                "u32" => Type::U32,
+                "u64" => Type::U64,
+                "Uuid" => Type::Uuid,
                 "Vec" => match get_type_parameter(&first_segment.arguments) {
                     Some(ty) => match ty.as_ref() {
                         "u8" => Type::ByteString,
-                        parameter_type => Type::UnsupportedType("Vec<".to_string() + parameter_type + ">"),
+                        parameter_type => {
+                            let typ = get_type_parameter_as_path(&first_segment.arguments)
+                                .map(Type::from)
+                                .filter(is_array_element_type);
+                            match typ {
+                                Some(typ) => Type::Array(Box::new(typ)),
+                                None => Type::UnsupportedType("Vec<".to_string() + parameter_type + ">"),
+                            }
+                        },
                     },
                     None => Type::UnsupportedType("Vec".to_string()),
                 },
+                // NOTE: a type name without generic arguments is assumed to be a user-defined type: an
+                // error is thrown if it does not derive SqlEnum or SqlType.
+                typ if typ.starts_with(char::is_uppercase) && first_segment.arguments.is_empty() =>
+                    Type::UserDefined(typ.to_string()),
                 typ => Type::UnsupportedType(typ.to_string()), // TODO: show the generic types as well.
             }
         }
+        else if is_path(segments, &["rust_decimal", "Decimal"]) {
+            Type::Decimal
+        }
+        else if is_path(segments, &["serde_json", "Value"]) {
+            Type::Json
+        }
+        else if is_path(segments, &["uuid", "Uuid"]) {
+            Type::Uuid
+        }
+        else if segments.len() == 2 && segments[0].ident == "tql" && segments[1].ident == "Json" {
+            Type::Json
+        }
         else {
             unsupported
         }
     }
 }
 
+/// Check if `typ` can be the type of the elements of an array column.
+fn is_array_element_type(typ: &Type) -> bool {
+    match *typ {
+        Type::Bool | Type::F32 | Type::F64 | Type::I16 | Type::I32 | Type::I64 | Type::LocalDateTime |
+            Type::NaiveDate | Type::NaiveDateTime | Type::NaiveTime | Type::String | Type::UtcDateTime |
+            Type::Uuid => true,
+        _ => false,
+    }
+}
+
+/// Check if the path `segments` are the `names`.
+fn is_path(segments: &Punctuated<PathSegment, Colon2>, names: &[&str]) -> bool {
+    segments.len() == names.len() &&
+        segments.iter().zip(names).all(|(segment, name)| segment.ident == name && segment.arguments.is_empty())
+}
+
 /// Get the type between < and > as a String.
 pub fn get_type_parameter(parameters: &PathArguments) -> Option<String> {
     get_type_parameter_as_path(parameters).map(|path| path.segments.first()